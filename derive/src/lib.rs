@@ -0,0 +1,201 @@
+use proc_macro::TokenStream;
+use proc_macro2::{Ident, Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote};
+use syn::{parse_macro_input, spanned::Spanned, Data, DeriveInput, Error, Fields, Type};
+
+/// Derives `RcDst` for a `#[repr(C)]` struct whose last field is `[T]` or `str`.
+///
+/// Along with the trait, this generates `new_rc` and `new_arc` constructors that take the sized
+/// fields in order, followed by an iterator for the last field (of `T`s, or of `char`s for a
+/// `str`), and build the whole `Rc<Self>`/`Arc<Self>` in a single allocation.
+///
+/// # Examples
+/// ```rust
+/// use collect_into_rc_slice::RcDst;
+/// use std::rc::Rc;
+///
+/// #[derive(RcDst)]
+/// #[repr(C)]
+/// struct Token {
+///     kind: u8,
+///     line: u32,
+///     text: str,
+/// }
+///
+/// let token: Rc<Token> = Token::new_rc(3, 42, "ident".chars());
+///
+/// assert_eq!(token.kind, 3);
+/// assert_eq!(token.line, 42);
+/// assert_eq!(&token.text, "ident");
+/// ```
+#[proc_macro_derive(RcDst)]
+pub fn derive_rc_dst(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    if !is_repr_c(&input)? {
+        return Err(Error::new(
+            Span::call_site(),
+            "`RcDst` can only be derived for `#[repr(C)]` structs",
+        ));
+    }
+
+    let fields = match &input.data {
+        Data::Struct(data) => &data.fields,
+        _ => {
+            return Err(Error::new(
+                Span::call_site(),
+                "`RcDst` can only be derived for structs",
+            ))
+        }
+    };
+
+    let (tail, sized) = match fields {
+        Fields::Named(fields) => fields.named.iter().collect::<Vec<_>>(),
+        Fields::Unnamed(fields) => fields.unnamed.iter().collect(),
+        Fields::Unit => Vec::new(),
+    }
+    .split_last()
+    .map(|(tail, sized)| (*tail, sized.to_vec()))
+    .ok_or_else(|| {
+        Error::new(
+            Span::call_site(),
+            "`RcDst` needs a last field of type `[T]` or `str`",
+        )
+    })?;
+
+    // the tail is collected from `T`s for a `[T]` and from `char`s for a `str`
+    let (item, new_rc, new_arc) = match &tail.ty {
+        Type::Slice(slice) => {
+            let elem = &slice.elem;
+            (quote!(#elem), quote!(new_rc), quote!(new_arc))
+        }
+        Type::Path(path) if path.qself.is_none() && path.path.is_ident("str") => (
+            quote!(char),
+            quote!(new_rc_from_chars),
+            quote!(new_arc_from_chars),
+        ),
+        ty => {
+            return Err(Error::new(
+                ty.span(),
+                "the last field must be of type `[T]` or `str`",
+            ))
+        }
+    };
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let krate = quote!(::collect_into_rc_slice);
+    let private = quote!(#krate::__private);
+
+    let types: Vec<_> = sized.iter().map(|field| &field.ty).collect();
+    let idents: Vec<_> = sized
+        .iter()
+        .enumerate()
+        .map(|(i, field)| {
+            field
+                .ident
+                .clone()
+                .unwrap_or_else(|| format_ident!("field_{}", i))
+        })
+        .collect();
+    let tail_ident = tail
+        .ident
+        .clone()
+        .unwrap_or_else(|| format_ident!("field_{}", sized.len()));
+    let tail_ty = &tail.ty;
+
+    // the locals of the generated code are resolved at the macro's own site, so that they can't
+    // shadow or be shadowed by fields of the same name
+    let layout = Ident::new("layout", Span::mixed_site());
+    let offset = Ident::new("offset", Span::mixed_site());
+    let ptr = Ident::new("ptr", Span::mixed_site());
+
+    let rc_doc = format!(
+        "Builds an `Rc<{name}>` in a single allocation, collecting `{tail_ident}` from an iterator."
+    );
+    let arc_doc = format!(
+        "Builds an `Arc<{name}>` in a single allocation, collecting `{tail_ident}` from an iterator."
+    );
+
+    Ok(quote! {
+        unsafe impl #impl_generics #krate::RcDst for #name #ty_generics #where_clause {
+            type Fields = (#(#types,)*);
+            type Tail = #tail_ty;
+
+            fn layout(tail: #private::Layout) -> (#private::Layout, usize) {
+                let layout = #private::Layout::new::<()>();
+                #(
+                    let (layout, _) = layout
+                        .extend(#private::Layout::new::<#types>())
+                        .expect("capacity overflow");
+                )*
+                let (layout, offset) = layout.extend(tail).expect("capacity overflow");
+                (layout.pad_to_align(), offset)
+            }
+
+            unsafe fn write_fields((#(#idents,)*): Self::Fields, #ptr: *mut u8) {
+                let #layout = #private::Layout::new::<()>();
+                #(
+                    let (#layout, #offset) = #layout
+                        .extend(#private::Layout::new::<#types>())
+                        .expect("capacity overflow");
+                    unsafe { #ptr.add(#offset).cast::<#types>().write(#idents) };
+                )*
+                let _ = #layout;
+            }
+
+            fn from_raw_parts(ptr: *mut u8, len: usize) -> *mut Self {
+                #private::ptr::slice_from_raw_parts_mut(
+                    ptr as *mut <#tail_ty as #krate::DstTail>::Item,
+                    len,
+                ) as *mut Self
+            }
+        }
+
+        impl #impl_generics #name #ty_generics #where_clause {
+            #[doc = #rc_doc]
+            pub fn new_rc(
+                #(#idents: #types,)*
+                #tail_ident: impl ::core::iter::IntoIterator<Item = #item>,
+            ) -> #private::Rc<Self> {
+                #private::#new_rc((#(#idents,)*), #tail_ident.into_iter())
+            }
+
+            #[doc = #arc_doc]
+            #[cfg(target_has_atomic = "ptr")]
+            pub fn new_arc(
+                #(#idents: #types,)*
+                #tail_ident: impl ::core::iter::IntoIterator<Item = #item>,
+            ) -> #private::Arc<Self> {
+                #private::#new_arc((#(#idents,)*), #tail_ident.into_iter())
+            }
+        }
+    })
+}
+
+fn is_repr_c(input: &DeriveInput) -> syn::Result<bool> {
+    let mut repr_c = false;
+
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("repr"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("C") {
+                repr_c = true;
+                Ok(())
+            } else {
+                // `packed` and `align` change the layout in ways the generated code doesn't follow
+                Err(meta.error("`RcDst` only supports `#[repr(C)]`"))
+            }
+        })?;
+    }
+
+    Ok(repr_c)
+}
@@ -0,0 +1,27 @@
+use collect_into_rc_slice::RcDst;
+use std::rc::Rc;
+
+#[derive(RcDst)]
+#[repr(C)]
+struct Shadowing {
+    offset: usize,
+    layout: u8,
+    ptr: u16,
+    text: str,
+}
+
+#[test]
+fn test_fields_named_like_generated_locals() {
+    let shadowing: Rc<Shadowing> = Shadowing::new_rc(1234, 5, 678, "text".chars());
+
+    assert_eq!(shadowing.offset, 1234);
+    assert_eq!(shadowing.layout, 5);
+    assert_eq!(shadowing.ptr, 678);
+    assert_eq!(&shadowing.text, "text");
+}
+
+#[cfg_attr(miri, ignore = "miri can't run the compiler")]
+#[test]
+fn test_rejected_shapes() {
+    trybuild::TestCases::new().compile_fail("tests/ui/*.rs");
+}
@@ -0,0 +1,10 @@
+use collect_into_rc_slice::RcDst;
+
+#[derive(RcDst)]
+#[repr(C)]
+enum Token {
+    Ident,
+    Number,
+}
+
+fn main() {}
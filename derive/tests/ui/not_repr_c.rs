@@ -0,0 +1,9 @@
+use collect_into_rc_slice::RcDst;
+
+#[derive(RcDst)]
+struct Token {
+    kind: u8,
+    text: str,
+}
+
+fn main() {}
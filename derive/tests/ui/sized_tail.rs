@@ -0,0 +1,10 @@
+use collect_into_rc_slice::RcDst;
+
+#[derive(RcDst)]
+#[repr(C)]
+struct Token {
+    kind: u8,
+    text: String,
+}
+
+fn main() {}
@@ -0,0 +1,7 @@
+use collect_into_rc_slice::RcDst;
+
+#[derive(RcDst)]
+#[repr(C)]
+struct Token;
+
+fn main() {}
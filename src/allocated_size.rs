@@ -0,0 +1,87 @@
+#[cfg(target_has_atomic = "ptr")]
+use crate::arc::ArcBuf;
+use crate::rc::RcBuf;
+use std::rc::Rc;
+#[cfg(target_has_atomic = "ptr")]
+use std::sync::Arc;
+
+/// The size of the heap allocation behind a pointer, e.g. to keep a cache of collected slices
+/// within a byte budget.
+///
+/// The size is the one the allocation was requested with: the reference counts, the padding after
+/// them and the data. The allocations of this crate are trimmed to the length of the data before
+/// they are handed over, so it is the same for pointers collected here and for the ones made by
+/// `std`. The global allocator may still round it up.
+///
+/// # Examples
+/// ```rust
+/// use collect_into_rc_slice::*;
+///
+/// let rc = (0..10u64).collect_into_rc_slice();
+///
+/// // two reference counts and ten `u64`s
+/// assert_eq!(rc.allocated_size(), 2 * size_of::<usize>() + 10 * 8);
+/// ```
+pub trait AllocatedSize {
+    fn allocated_size(&self) -> usize;
+}
+
+impl<T> AllocatedSize for Rc<[T]> {
+    #[inline]
+    fn allocated_size(&self) -> usize {
+        RcBuf::<T>::layout(self.len()).size()
+    }
+}
+
+impl AllocatedSize for Rc<str> {
+    #[inline]
+    fn allocated_size(&self) -> usize {
+        RcBuf::<u8>::layout(self.len()).size()
+    }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl<T> AllocatedSize for Arc<[T]> {
+    #[inline]
+    fn allocated_size(&self) -> usize {
+        ArcBuf::<T>::layout(self.len()).size()
+    }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl AllocatedSize for Arc<str> {
+    #[inline]
+    fn allocated_size(&self) -> usize {
+        ArcBuf::<u8>::layout(self.len()).size()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::alloc::Layout;
+
+    #[test]
+    fn test_allocated_size_padding() {
+        // the `u128`s are aligned past the reference counts, and the total is padded to them
+        let rc: Rc<[u128]> = Rc::from([1, 2, 3]);
+        let (layout, _) = Layout::new::<[usize; 2]>()
+            .extend(Layout::array::<u128>(3).unwrap())
+            .unwrap();
+        assert_eq!(rc.allocated_size(), layout.pad_to_align().size());
+
+        // and the bytes of a string are padded to the reference counts
+        let s: Rc<str> = Rc::from("abc");
+        assert_eq!(
+            s.allocated_size(),
+            (2 * size_of::<usize>() + 3).next_multiple_of(align_of::<usize>())
+        );
+    }
+
+    #[cfg(target_has_atomic = "ptr")]
+    #[test]
+    fn test_allocated_size_arc_zst() {
+        let arc: Arc<[()]> = Arc::from([(), ()]);
+        assert_eq!(arc.allocated_size(), 2 * size_of::<usize>());
+    }
+}
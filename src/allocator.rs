@@ -0,0 +1,79 @@
+use std::alloc::{self, Layout};
+
+/// The allocator a [`RawBuf`](crate::raw::RawBuf) gets its memory from.
+///
+/// # Safety
+/// The methods must behave like their counterparts in `std::alloc`, returning a null pointer when
+/// the allocation fails.
+pub(crate) unsafe trait RawAlloc {
+    fn alloc(&self, layout: Layout) -> *mut u8;
+
+    /// # Safety
+    /// `ptr` must have been allocated by this allocator with `old_layout`, and `new_layout` must
+    /// have the same alignment.
+    unsafe fn realloc(&self, ptr: *mut u8, old_layout: Layout, new_layout: Layout) -> *mut u8;
+
+    /// # Safety
+    /// `ptr` must have been allocated by this allocator with `layout`.
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout);
+}
+
+/// The global allocator, which is what `Rc` and `Arc` use by default.
+#[derive(Clone, Copy, Default)]
+pub(crate) struct Global;
+
+// SAFETY: Forwards to the global allocator.
+unsafe impl RawAlloc for Global {
+    #[inline]
+    fn alloc(&self, layout: Layout) -> *mut u8 {
+        #[cfg(any(test, feature = "test-util"))]
+        if crate::test_util::should_fail() {
+            return std::ptr::null_mut();
+        }
+
+        // SAFETY: Prefixes are never zero-sized, so neither is the layout.
+        unsafe { alloc::alloc(layout) }
+    }
+
+    #[inline]
+    unsafe fn realloc(&self, ptr: *mut u8, old_layout: Layout, new_layout: Layout) -> *mut u8 {
+        #[cfg(any(test, feature = "test-util"))]
+        if crate::test_util::should_fail() {
+            return std::ptr::null_mut();
+        }
+
+        alloc::realloc(ptr, old_layout, new_layout.size())
+    }
+
+    #[inline]
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        alloc::dealloc(ptr, layout)
+    }
+}
+
+// SAFETY: Forwards to the `Allocator`, mapping errors to null pointers.
+#[cfg(feature = "allocator_api")]
+unsafe impl<A: alloc::Allocator> RawAlloc for A {
+    #[inline]
+    fn alloc(&self, layout: Layout) -> *mut u8 {
+        self.allocate(layout)
+            .map_or(std::ptr::null_mut(), |ptr| ptr.as_ptr() as *mut u8)
+    }
+
+    #[inline]
+    unsafe fn realloc(&self, ptr: *mut u8, old_layout: Layout, new_layout: Layout) -> *mut u8 {
+        let ptr = std::ptr::NonNull::new_unchecked(ptr);
+        let result = if new_layout.size() >= old_layout.size() {
+            self.grow(ptr, old_layout, new_layout)
+        } else {
+            self.shrink(ptr, old_layout, new_layout)
+        };
+
+        result.map_or(std::ptr::null_mut(), |ptr| ptr.as_ptr() as *mut u8)
+    }
+
+    #[inline]
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        self.deallocate(std::ptr::NonNull::new_unchecked(ptr), layout)
+    }
+}
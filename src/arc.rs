@@ -1,5 +1,12 @@
 #![cfg(target_has_atomic = "ptr")]
-use std::{alloc::Layout, mem, sync::atomic::AtomicUsize};
+use crate::{
+    raw::{Prefix, RawBuf},
+    AllocError,
+};
+use std::{
+    ptr,
+    sync::{atomic::AtomicUsize, Arc},
+};
 
 #[repr(C)]
 pub(crate) struct ArcInner<T: ?Sized> {
@@ -8,14 +15,87 @@ pub(crate) struct ArcInner<T: ?Sized> {
     pub(crate) data: T,
 }
 
-pub(crate) fn data_offset<T>() -> usize {
-    let layout = Layout::new::<ArcInner<()>>();
-    layout.size() + padding_needed(layout.size(), mem::align_of::<T>())
-}
+// SAFETY: `Arc` lays out its allocation as an `ArcInner`.
+unsafe impl Prefix for ArcInner<()> {}
+
+pub(crate) type ArcBuf<T> = RawBuf<ArcInner<()>, T>;
 
+/// Writes the reference counts and hands the buffer over to an `Arc<[T]>`.
 #[inline]
-pub(crate) fn padding_needed(len: usize, align: usize) -> usize {
-    // Math for computing padding is taken from `Layout::padding_needed_for`.
-    let padding = len.wrapping_add(align).wrapping_sub(1) & !align.wrapping_sub(1);
-    padding.wrapping_sub(len)
+pub(crate) fn into_arc<T>(buf: ArcBuf<T>) -> Arc<[T]> {
+    try_into_arc(buf).unwrap_or_else(|err| err.handle())
+}
+
+/// Like `into_arc`, but returns an error instead of aborting if the allocation can't be trimmed.
+pub(crate) fn try_into_arc<T>(buf: ArcBuf<T>) -> Result<Arc<[T]>, AllocError> {
+    let (alloc, data) = buf.try_into_raw_parts()?;
+
+    // SAFETY:
+    // - The prefix of the allocation is reserved for the `ArcInner` header.
+    // - `data` is a valid pointer to a `[T]` located at the heap
+    // - `data` is part of an ArcInner with proper metadata.
+    unsafe {
+        ptr::write(
+            alloc.as_ptr() as *mut ArcInner<()>,
+            ArcInner {
+                strong: AtomicUsize::new(1),
+                weak: AtomicUsize::new(1),
+                data: (),
+            },
+        );
+
+        Ok(Arc::from_raw(data))
+    }
+}
+
+/// Writes the reference counts and hands the buffer over to an `Arc<[T], A>`.
+#[cfg(feature = "allocator_api")]
+pub(crate) fn into_arc_in<T, A: std::alloc::Allocator>(
+    buf: RawBuf<ArcInner<()>, T, A>,
+) -> Arc<[T], A> {
+    let (alloc, data, allocator) = buf.into_raw_parts_with_alloc();
+
+    // SAFETY:
+    // - The prefix of the allocation is reserved for the `ArcInner` header.
+    // - `data` is a valid pointer to a `[T]` allocated with `allocator`
+    // - `data` is part of an ArcInner with proper metadata.
+    unsafe {
+        ptr::write(
+            alloc.as_ptr() as *mut ArcInner<()>,
+            ArcInner {
+                strong: AtomicUsize::new(1),
+                weak: AtomicUsize::new(1),
+                data: (),
+            },
+        );
+
+        Arc::from_raw_in(data, allocator)
+    }
+}
+
+/// Hands a buffer of UTF-8 data over to an `Arc<str>`.
+///
+/// # Safety
+/// The buffer must contain valid UTF-8.
+pub(crate) unsafe fn into_arc_str(buf: ArcBuf<u8>) -> Arc<str> {
+    Arc::from_raw(Arc::into_raw(into_arc(buf)) as *const str)
+}
+
+/// Like `into_arc_str`, but returns an error instead of aborting if the allocation can't be
+/// trimmed.
+///
+/// # Safety
+/// The buffer must contain valid UTF-8.
+pub(crate) unsafe fn try_into_arc_str(buf: ArcBuf<u8>) -> Result<Arc<str>, AllocError> {
+    try_into_arc(buf).map(|arc| Arc::from_raw(Arc::into_raw(arc) as *const str))
+}
+
+/// Hands a buffer of a C string, including its nul terminator, over to an `Arc<CStr>`.
+///
+/// # Safety
+/// The buffer must end with a nul byte and contain no other nul bytes.
+pub(crate) unsafe fn into_arc_cstr(buf: ArcBuf<u8>) -> Arc<std::ffi::CStr> {
+    // `CStr` is a transparent wrapper around `[c_char]`, which `std` relies on as well when
+    // converting boxed and reference counted byte slices into C strings
+    Arc::from_raw(Arc::into_raw(into_arc(buf)) as *const std::ffi::CStr)
 }
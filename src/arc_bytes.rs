@@ -0,0 +1,549 @@
+#![cfg(target_has_atomic = "ptr")]
+use crate::arc::{into_arc, ArcBuf};
+use std::{
+    borrow::Borrow,
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+    ops::{Bound, Deref, Index, Range, RangeBounds},
+    slice::{self, SliceIndex},
+    sync::Arc,
+};
+
+/// A thread-safe, cheaply cloneable view of a range of an `Arc<[u8]>`.
+///
+/// This is the `Send + Sync` counterpart of [`RcBytes`](crate::RcBytes). With the `bytes` feature
+/// it implements `bytes::Buf`, so it can be passed to tokio and hyper style code while the storage
+/// stays a plain `Arc<[u8]>`.
+///
+/// # Examples
+/// ```rust
+/// use collect_into_rc_slice::*;
+///
+/// let mut buf = b"\x03abcrest".iter().copied().collect_into_arc_bytes();
+/// let len = buf[0] as usize;
+/// buf.advance(1);
+/// let field = buf.split_to(len);
+///
+/// assert_eq!(&*field, b"abc");
+/// assert_eq!(&*buf, b"rest");
+/// ```
+#[derive(Clone)]
+pub struct ArcBytes {
+    parent: Arc<[u8]>,
+    start: usize,
+    end: usize,
+}
+
+impl ArcBytes {
+    /// A view covering all of `parent`.
+    #[inline]
+    pub fn new(parent: Arc<[u8]>) -> Self {
+        let end = parent.len();
+        Self {
+            parent,
+            start: 0,
+            end,
+        }
+    }
+
+    #[inline]
+    pub fn as_slice(&self) -> &[u8] {
+        // SAFETY: The range is always in bounds of the parent.
+        unsafe { self.parent.get_unchecked(self.start..self.end) }
+    }
+
+    /// The whole buffer this is a view of.
+    #[inline]
+    pub fn parent(&self) -> &Arc<[u8]> {
+        &self.parent
+    }
+
+    /// The range of this view within [`parent`](ArcBytes::parent).
+    #[inline]
+    pub fn range(&self) -> Range<usize> {
+        self.start..self.end
+    }
+
+    /// Returns a view of a range of this view, sharing the same allocation.
+    ///
+    /// # Panics
+    /// Panics if the range is out of bounds.
+    pub fn slice(&self, range: impl RangeBounds<usize>) -> Self {
+        let bounds: (Bound<usize>, Bound<usize>) =
+            (range.start_bound().cloned(), range.end_bound().cloned());
+        let sub = &self.as_slice()[bounds];
+        self.slice_ref(sub)
+    }
+
+    /// Returns the view for `sub`, which has to be a part of this view.
+    ///
+    /// # Panics
+    /// Panics if `sub` is not contained in this view.
+    pub fn slice_ref(&self, sub: &[u8]) -> Self {
+        let base = self.as_slice().as_ptr() as usize;
+        let offset = (sub.as_ptr() as usize).wrapping_sub(base);
+
+        assert!(
+            offset <= self.len() && sub.len() <= self.len() - offset,
+            "the slice is not a part of this view"
+        );
+
+        Self {
+            parent: self.parent.clone(),
+            start: self.start + offset,
+            end: self.start + offset + sub.len(),
+        }
+    }
+
+    /// Splits the view in two at `at`, keeping `[at, len)` and returning `[0, at)`.
+    ///
+    /// # Panics
+    /// Panics if `at > len`.
+    pub fn split_to(&mut self, at: usize) -> Self {
+        assert!(
+            at <= self.len(),
+            "split_to out of bounds: {at} > {}",
+            self.len()
+        );
+
+        let head = Self {
+            parent: self.parent.clone(),
+            start: self.start,
+            end: self.start + at,
+        };
+        self.start += at;
+        head
+    }
+
+    /// Splits the view in two at `at`, keeping `[0, at)` and returning `[at, len)`.
+    ///
+    /// # Panics
+    /// Panics if `at > len`.
+    pub fn split_off(&mut self, at: usize) -> Self {
+        assert!(
+            at <= self.len(),
+            "split_off out of bounds: {at} > {}",
+            self.len()
+        );
+
+        let tail = Self {
+            parent: self.parent.clone(),
+            start: self.start + at,
+            end: self.end,
+        };
+        self.end = self.start + at;
+        tail
+    }
+
+    /// Drops the first `cnt` bytes from the view.
+    ///
+    /// # Panics
+    /// Panics if `cnt > len`.
+    #[inline]
+    pub fn advance(&mut self, cnt: usize) {
+        assert!(
+            cnt <= self.len(),
+            "advance out of bounds: {cnt} > {}",
+            self.len()
+        );
+        self.start += cnt;
+    }
+}
+
+impl From<Arc<[u8]>> for ArcBytes {
+    #[inline]
+    fn from(parent: Arc<[u8]>) -> Self {
+        Self::new(parent)
+    }
+}
+
+impl Deref for ArcBytes {
+    type Target = [u8];
+
+    #[inline]
+    fn deref(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+impl AsRef<[u8]> for ArcBytes {
+    #[inline]
+    fn as_ref(&self) -> &[u8] {
+        self
+    }
+}
+
+impl<I: SliceIndex<[u8]>> Index<I> for ArcBytes {
+    type Output = I::Output;
+
+    #[inline]
+    fn index(&self, index: I) -> &I::Output {
+        &(**self)[index]
+    }
+}
+
+/// An empty view.
+impl Default for ArcBytes {
+    #[inline]
+    fn default() -> Self {
+        Self::new(Arc::default())
+    }
+}
+
+impl<'a> IntoIterator for &'a ArcBytes {
+    type Item = &'a u8;
+    type IntoIter = slice::Iter<'a, u8>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl Borrow<[u8]> for ArcBytes {
+    #[inline]
+    fn borrow(&self) -> &[u8] {
+        self
+    }
+}
+
+impl fmt::Debug for ArcBytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_slice(), f)
+    }
+}
+
+impl PartialEq for ArcBytes {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl Eq for ArcBytes {}
+
+impl PartialEq<[u8]> for ArcBytes {
+    #[inline]
+    fn eq(&self, other: &[u8]) -> bool {
+        self.as_slice() == other
+    }
+}
+
+impl PartialEq<&[u8]> for ArcBytes {
+    #[inline]
+    fn eq(&self, other: &&[u8]) -> bool {
+        self.as_slice() == *other
+    }
+}
+
+impl PartialOrd for ArcBytes {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ArcBytes {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_slice().cmp(other.as_slice())
+    }
+}
+
+impl Hash for ArcBytes {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_slice().hash(state);
+    }
+}
+
+impl FromIterator<u8> for ArcBytes {
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = u8>>(iter: I) -> Self {
+        Self::new(into_arc(ArcBuf::from_iter(iter.into_iter())))
+    }
+}
+
+#[cfg(feature = "bytes")]
+impl bytes::Buf for ArcBytes {
+    #[inline]
+    fn remaining(&self) -> usize {
+        self.len()
+    }
+
+    #[inline]
+    fn chunk(&self) -> &[u8] {
+        self
+    }
+
+    #[inline]
+    fn advance(&mut self, cnt: usize) {
+        ArcBytes::advance(self, cnt);
+    }
+
+    /// Splits off the first `len` bytes without copying them.
+    #[inline]
+    fn copy_to_bytes(&mut self, len: usize) -> bytes::Bytes {
+        self.split_to(len).into()
+    }
+}
+
+/// Hands the view over to a `bytes::Bytes` without copying, the `Bytes` keeping the `Arc<[u8]>`
+/// alive until it and all of its clones are dropped.
+///
+/// # Examples
+/// ```rust
+/// use collect_into_rc_slice::*;
+///
+/// let arc = (0..8).collect_into_arc_bytes();
+/// let bytes = bytes::Bytes::from(arc.slice(2..5));
+///
+/// assert_eq!(&bytes[..], &[2, 3, 4]);
+/// assert_eq!(bytes.as_ptr(), arc[2..].as_ptr());
+/// ```
+#[cfg(feature = "bytes")]
+impl From<ArcBytes> for bytes::Bytes {
+    #[inline]
+    fn from(bytes: ArcBytes) -> Self {
+        bytes::Bytes::from_owner(bytes)
+    }
+}
+
+/// Copies the contents of a `bytes::Buf` into a single `Arc<[u8]>` allocation.
+#[cfg(feature = "bytes")]
+pub trait CopyToArcSlice: bytes::Buf {
+    /// Copies all of the remaining bytes into an `Arc<[u8]>` of exactly that length, consuming
+    /// them from the buffer.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bytes::Buf;
+    /// use collect_into_rc_slice::*;
+    ///
+    /// let mut buf = (&b"hello "[..]).chain(&b"world"[..]);
+    /// let arc = buf.copy_to_arc_slice();
+    ///
+    /// assert_eq!(&*arc, b"hello world");
+    /// assert!(!buf.has_remaining());
+    /// ```
+    fn copy_to_arc_slice(&mut self) -> Arc<[u8]> {
+        into_arc(copy_exact(self))
+    }
+
+    /// Copies all of the remaining bytes into an [`ArcBytes`], like
+    /// [`copy_to_arc_slice`](CopyToArcSlice::copy_to_arc_slice).
+    ///
+    /// The copy is made chunk by chunk into a single allocation of the length `remaining` reports,
+    /// so a rope of buffers like `Chain`s or a `BytesMut` split into several `Bytes` ends up in one
+    /// contiguous view.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bytes::{Buf, Bytes};
+    /// use collect_into_rc_slice::*;
+    ///
+    /// let mut body = Bytes::from_static(b"GET / ").chain(Bytes::from_static(b"HTTP/1.1"));
+    /// let mut request = body.copy_to_arc_bytes();
+    ///
+    /// assert_eq!(&*request.split_to(3), b"GET");
+    /// assert_eq!(&*request, b" / HTTP/1.1");
+    /// ```
+    #[inline]
+    fn copy_to_arc_bytes(&mut self) -> ArcBytes {
+        ArcBytes::new(self.copy_to_arc_slice())
+    }
+}
+
+#[cfg(feature = "bytes")]
+impl<B: bytes::Buf + ?Sized> CopyToArcSlice for B {}
+
+/// Copies the remaining bytes of `buf` into an [`ArcBytes`] of exactly that length.
+///
+/// This is [`copy_to_arc_bytes`](CopyToArcSlice::copy_to_arc_bytes) for a `Buf` that is taken by
+/// value, e.g. a `bytes::buf::Chain` built for the call. Like
+/// [`copy_to_arc_slice`](CopyToArcSlice::copy_to_arc_slice), which both copy through, it
+/// allocates once at the length `remaining` reports and copies each chunk straight into its place.
+///
+/// # Panics
+/// Panics if the chunks of `buf` run out before `remaining` bytes, which a `Buf` must not do.
+///
+/// # Examples
+/// ```rust
+/// use bytes::{Buf, Bytes};
+/// use collect_into_rc_slice::*;
+///
+/// let segments = Bytes::from_static(b"abc")
+///     .chain(Bytes::from_static(b"def"))
+///     .chain(Bytes::from_static(b"ghi"));
+/// let bytes = buf_to_arc_bytes_exact(segments);
+///
+/// assert_eq!(&*bytes, b"abcdefghi");
+/// assert_eq!(bytes.parent().len(), 9);
+/// ```
+#[cfg(feature = "bytes")]
+#[inline]
+pub fn buf_to_arc_bytes_exact<B: bytes::Buf>(mut buf: B) -> ArcBytes {
+    buf.copy_to_arc_bytes()
+}
+
+/// Copies the `remaining` bytes of `buf` into a buffer of exactly that length, in one pass.
+#[cfg(feature = "bytes")]
+fn copy_exact<B: bytes::Buf + ?Sized>(buf: &mut B) -> ArcBuf<u8> {
+    let len = buf.remaining();
+    let mut out = ArcBuf::with_capacity(len);
+    let dst = out.spare_capacity_mut().as_mut_ptr() as *mut u8;
+
+    let mut filled = 0;
+    while filled < len {
+        let chunk = buf.chunk();
+        let n = chunk.len().min(len - filled);
+        assert!(
+            n != 0,
+            "the buffer ran out of chunks before its remaining bytes"
+        );
+
+        // SAFETY: `out` has room for `len` bytes, and at most `len - filled` are copied past
+        // `filled`.
+        unsafe { std::ptr::copy_nonoverlapping(chunk.as_ptr(), dst.add(filled), n) };
+        buf.advance(n);
+        filled += n;
+    }
+
+    // SAFETY: All `len` bytes were copied.
+    unsafe { out.set_len(len) };
+    out
+}
+
+pub trait CollectIntoArcBytes {
+    /// Collects the iterator into an [`ArcBytes`] covering the whole buffer.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use collect_into_rc_slice::*;
+    ///
+    /// let bytes = "abc".bytes().collect_into_arc_bytes();
+    ///
+    /// assert_eq!(&*bytes, b"abc");
+    /// ```
+    fn collect_into_arc_bytes(self) -> ArcBytes;
+}
+
+impl<T> CollectIntoArcBytes for T
+where
+    T: IntoIterator<Item = u8>,
+{
+    fn collect_into_arc_bytes(self) -> ArcBytes {
+        self.into_iter().collect()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for ArcBytes {
+    #[inline]
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arc_bytes_split() {
+        let mut bytes = (0..10).collect_into_arc_bytes();
+        let tail = bytes.split_off(6);
+        let head = bytes.split_to(2);
+
+        assert_eq!(&*head, &[0, 1]);
+        assert_eq!(&*bytes, &[2, 3, 4, 5]);
+        assert_eq!(&*tail, &[6, 7, 8, 9]);
+        assert_eq!(bytes.range(), 2..6);
+        assert_eq!(Arc::strong_count(bytes.parent()), 3);
+    }
+
+    #[test]
+    fn test_arc_bytes_slice() {
+        let mut bytes = (0..10).collect_into_arc_bytes();
+        bytes.advance(3);
+        let slice = bytes.slice(1..=2);
+
+        assert_eq!(&*slice, &[4, 5]);
+        assert_eq!(slice.range(), 4..6);
+        assert!(bytes.slice(7..).is_empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_arc_bytes_advance_out_of_bounds() {
+        let mut bytes = (0..2).collect_into_arc_bytes();
+        bytes.advance(3);
+    }
+
+    #[cfg(feature = "bytes")]
+    #[test]
+    fn test_arc_bytes_buf() {
+        use bytes::Buf;
+
+        let mut bytes = [0, 0, 0, 7, 1, 2].into_iter().collect_into_arc_bytes();
+
+        assert_eq!(bytes.get_u32(), 7);
+        assert_eq!(bytes.remaining(), 2);
+        assert_eq!(bytes.copy_to_bytes(2).as_ref(), &[1, 2]);
+        assert!(!bytes.has_remaining());
+    }
+
+    #[cfg(feature = "bytes")]
+    #[test]
+    fn test_copy_to_arc_bytes_partially_consumed() {
+        use bytes::Buf;
+
+        let mut buf = (&b"skip"[..]).chain(&b"keep"[..]).chain(&b"!"[..]);
+        buf.advance(2);
+        let bytes = buf.copy_to_arc_bytes();
+
+        assert_eq!(&*bytes, b"ipkeep!");
+        assert_eq!(bytes.parent().len(), 7);
+        assert!(!buf.has_remaining());
+    }
+
+    #[cfg(feature = "bytes")]
+    #[test]
+    fn test_buf_to_arc_bytes_exact_stops_at_remaining() {
+        use bytes::Buf;
+
+        // `take` limits `remaining` to 5, in the middle of the second chunk
+        let mut chain = (&b"abc"[..]).chain(&b"defgh"[..]);
+        let bytes = buf_to_arc_bytes_exact((&mut chain).take(5));
+
+        assert_eq!(&*bytes, b"abcde");
+        assert_eq!(chain.chunk(), b"fgh");
+        assert!(buf_to_arc_bytes_exact(&b""[..]).is_empty());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_arc_bytes_serialize() {
+        let bytes = (0..5).collect_into_arc_bytes().slice(3..);
+
+        assert_eq!(serde_json::to_string(&bytes).unwrap(), "[3,4]");
+    }
+
+    #[cfg(feature = "bytes")]
+    #[test]
+    fn test_arc_bytes_into_bytes() {
+        use bytes::Buf;
+
+        let mut arc = (0..10).collect_into_arc_bytes();
+        let parent = arc.parent().clone();
+        let head = arc.copy_to_bytes(4);
+        let tail = bytes::Bytes::from(arc);
+
+        assert_eq!(&head[..], &[0, 1, 2, 3]);
+        assert_eq!(tail.as_ptr(), parent[4..].as_ptr());
+        assert_eq!(Arc::strong_count(&parent), 3);
+
+        drop((head, tail));
+        assert_eq!(Arc::strong_count(&parent), 1);
+    }
+}
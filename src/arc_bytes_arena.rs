@@ -0,0 +1,191 @@
+#![cfg(target_has_atomic = "ptr")]
+use crate::{
+    arc::{into_arc, ArcBuf},
+    ArcBytes,
+};
+use std::{fmt, sync::Arc};
+
+const DEFAULT_BLOCK_SIZE: usize = 4096;
+
+/// A thread-safe arena packing many small byte blobs into a few large `Arc<[u8]>` blocks.
+///
+/// This is the binary counterpart of [`RcStrArena`](crate::RcStrArena): blobs are pushed one at a
+/// time and [`finish`](ArcBytesArena::finish) turns them into [`ArcBytes`] views of the blocks, so
+/// retaining many small payload fragments only costs one allocation per block.
+///
+/// # Examples
+/// ```rust
+/// use collect_into_rc_slice::*;
+/// use std::sync::Arc;
+///
+/// let mut arena = ArcBytesArena::new();
+/// let header = arena.push(b"\x01\x02");
+/// let payload = arena.push(b"payload");
+///
+/// assert_eq!(arena.get(header), b"\x01\x02");
+///
+/// let blobs = arena.finish();
+/// assert_eq!(blobs[payload], b"payload"[..]);
+/// assert!(Arc::ptr_eq(blobs[header].parent(), blobs[payload].parent()));
+/// ```
+pub struct ArcBytesArena {
+    block_size: usize,
+    blocks: Vec<Arc<[u8]>>,
+    current: ArcBuf<u8>,
+    spans: Vec<Span>,
+}
+
+/// The location of a pushed blob, as the index of its block and its byte range in it.
+#[derive(Clone, Copy)]
+struct Span {
+    block: usize,
+    start: usize,
+    end: usize,
+}
+
+impl ArcBytesArena {
+    #[inline]
+    pub fn new() -> Self {
+        Self::with_block_size(DEFAULT_BLOCK_SIZE)
+    }
+
+    /// An arena whose blocks are `block_size` bytes long. Blobs longer than that get a block of
+    /// their own.
+    pub fn with_block_size(block_size: usize) -> Self {
+        Self {
+            block_size,
+            blocks: Vec::new(),
+            current: ArcBuf::with_capacity(block_size),
+            spans: Vec::new(),
+        }
+    }
+
+    /// Copies `bytes` into the arena, returning its index in the blobs returned by
+    /// [`finish`](ArcBytesArena::finish).
+    pub fn push(&mut self, bytes: &[u8]) -> usize {
+        if self.current.len() + bytes.len() > self.current.capacity() {
+            let next = ArcBuf::with_capacity(self.block_size.max(bytes.len()));
+            self.seal(next);
+        }
+
+        let start = self.current.len();
+        self.current.extend_from_slice(bytes);
+        self.spans.push(Span {
+            block: self.blocks.len(),
+            start,
+            end: self.current.len(),
+        });
+
+        self.spans.len() - 1
+    }
+
+    /// Finishes the current block, if it has any blobs, and starts filling `next`.
+    fn seal(&mut self, next: ArcBuf<u8>) {
+        let current = std::mem::replace(&mut self.current, next);
+        if current.len() > 0 {
+            self.blocks.push(into_arc(current));
+        }
+    }
+
+    /// Returns the blob pushed at `index`.
+    ///
+    /// # Panics
+    /// Panics if `index` is out of bounds.
+    pub fn get(&self, index: usize) -> &[u8] {
+        let Span { block, start, end } = self.spans[index];
+        match self.blocks.get(block) {
+            Some(block) => &block[start..end],
+            None => &self.current.as_slice()[start..end],
+        }
+    }
+
+    /// The number of blobs pushed into the arena.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.spans.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.spans.is_empty()
+    }
+
+    /// The number of blocks allocated so far, including the one being filled.
+    #[inline]
+    pub fn block_count(&self) -> usize {
+        self.blocks.len() + 1
+    }
+
+    /// Returns the pushed blobs as views of the blocks, in the order they were pushed in.
+    pub fn finish(mut self) -> Vec<ArcBytes> {
+        self.seal(ArcBuf::with_capacity(0));
+        let empty = ArcBytes::new(Arc::from([]));
+
+        self.spans
+            .iter()
+            .map(|&Span { block, start, end }| match self.blocks.get(block) {
+                Some(block) => ArcBytes::new(block.clone()).slice(start..end),
+                // only empty blobs are left in a block that was never sealed
+                None => empty.clone(),
+            })
+            .collect()
+    }
+}
+
+impl Default for ArcBytesArena {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for ArcBytesArena {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries((0..self.len()).map(|i| self.get(i)))
+            .finish()
+    }
+}
+
+impl<'a> Extend<&'a [u8]> for ArcBytesArena {
+    fn extend<I: IntoIterator<Item = &'a [u8]>>(&mut self, iter: I) {
+        for s in iter {
+            self.push(s);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn test_arc_bytes_arena_blocks() {
+        let mut arena = ArcBytesArena::with_block_size(4);
+        arena.extend([&[1, 2][..], &[3, 4], &[5, 6, 7, 8, 9], &[]]);
+
+        assert_eq!(arena.len(), 4);
+        assert_eq!(arena.block_count(), 2);
+        assert_eq!(arena.get(1), [3, 4]);
+
+        let blobs = arena.finish();
+        assert_eq!(&**blobs[0].parent(), [1, 2, 3, 4]);
+        assert_eq!(blobs[2], [5, 6, 7, 8, 9][..]);
+        assert!(blobs[3].is_empty());
+    }
+
+    #[test]
+    fn test_arc_bytes_arena_threads() {
+        let mut arena = ArcBytesArena::new();
+        for i in 0..100u8 {
+            arena.push(&[i; 3]);
+        }
+        let blobs = arena.finish();
+
+        let sum = thread::spawn(move || blobs.iter().map(|b| b[0] as usize).sum::<usize>())
+            .join()
+            .unwrap();
+        assert_eq!(sum, 4950);
+    }
+}
@@ -0,0 +1,202 @@
+#![cfg(target_has_atomic = "ptr")]
+use crate::arc::{into_arc_cstr, ArcBuf};
+use std::{
+    borrow::Borrow,
+    ffi::{CStr, CString},
+    fmt,
+    ops::Deref,
+    sync::Arc,
+};
+
+/// A newtype around an `Arc<CStr>`, for implementing traits that can't be implemented on
+/// `Arc<CStr>` itself.
+///
+/// With the `serde` feature, it deserializes by copying the string and its nul terminator straight
+/// into a new `Arc<CStr>` allocation, rejecting strings that contain nul bytes.
+///
+/// # Examples
+/// ```rust
+/// use collect_into_rc_slice::*;
+///
+/// let name = ArcCStr::new("eth0").unwrap();
+///
+/// assert_eq!(name.to_bytes_with_nul(), b"eth0\0");
+/// assert!(ArcCStr::new("eth\00").is_none());
+/// ```
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct ArcCStr(pub Arc<CStr>);
+
+impl ArcCStr {
+    /// Copies `bytes` and a nul terminator into a new `Arc<CStr>`, or returns `None` if `bytes`
+    /// contains a nul byte.
+    pub fn new(bytes: impl AsRef<[u8]>) -> Option<Self> {
+        let bytes = bytes.as_ref();
+        if bytes.contains(&0) {
+            return None;
+        }
+
+        let mut buf = ArcBuf::with_capacity(bytes.len() + 1);
+        buf.extend_from_slice(bytes);
+        buf.push(0);
+        // SAFETY: The buffer ends with the only nul byte in it.
+        Some(Self(unsafe { into_arc_cstr(buf) }))
+    }
+
+    #[inline]
+    pub fn as_c_str(&self) -> &CStr {
+        &self.0
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> Arc<CStr> {
+        self.0
+    }
+}
+
+impl From<Arc<CStr>> for ArcCStr {
+    #[inline]
+    fn from(s: Arc<CStr>) -> Self {
+        Self(s)
+    }
+}
+
+impl From<&CStr> for ArcCStr {
+    #[inline]
+    fn from(s: &CStr) -> Self {
+        Self(Arc::from(s))
+    }
+}
+
+impl From<CString> for ArcCStr {
+    #[inline]
+    fn from(s: CString) -> Self {
+        Self(Arc::from(s))
+    }
+}
+
+impl From<ArcCStr> for Arc<CStr> {
+    #[inline]
+    fn from(s: ArcCStr) -> Self {
+        s.0
+    }
+}
+
+impl Deref for ArcCStr {
+    type Target = CStr;
+
+    #[inline]
+    fn deref(&self) -> &CStr {
+        &self.0
+    }
+}
+
+impl AsRef<CStr> for ArcCStr {
+    #[inline]
+    fn as_ref(&self) -> &CStr {
+        &self.0
+    }
+}
+
+impl Borrow<CStr> for ArcCStr {
+    #[inline]
+    fn borrow(&self) -> &CStr {
+        &self.0
+    }
+}
+
+impl fmt::Debug for ArcCStr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_c_str(), f)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for ArcCStr {
+    #[inline]
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.as_c_str().serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for ArcCStr {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Visitor;
+
+        impl<'de> serde::de::Visitor<'de> for Visitor {
+            type Value = ArcCStr;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a string without nul bytes")
+            }
+
+            fn visit_str<E: serde::de::Error>(self, s: &str) -> Result<ArcCStr, E> {
+                ArcCStr::new(s)
+                    .ok_or_else(|| E::invalid_value(serde::de::Unexpected::Str(s), &self))
+            }
+
+            fn visit_bytes<E: serde::de::Error>(self, bytes: &[u8]) -> Result<ArcCStr, E> {
+                ArcCStr::new(bytes)
+                    .ok_or_else(|| E::invalid_value(serde::de::Unexpected::Bytes(bytes), &self))
+            }
+
+            fn visit_byte_buf<E: serde::de::Error>(self, bytes: Vec<u8>) -> Result<ArcCStr, E> {
+                self.visit_bytes(&bytes)
+            }
+
+            // formats without a byte string type, like JSON, serialize the bytes as a sequence
+            fn visit_seq<S: serde::de::SeqAccess<'de>>(self, seq: S) -> Result<ArcCStr, S::Error> {
+                let mut buf: ArcBuf<u8> = crate::raw::from_seq(seq)?;
+                if buf.as_slice().contains(&0) {
+                    return Err(serde::de::Error::invalid_value(
+                        serde::de::Unexpected::Seq,
+                        &self,
+                    ));
+                }
+
+                buf.push(0);
+                // SAFETY: The buffer ends with the only nul byte in it.
+                Ok(ArcCStr(unsafe { into_arc_cstr(buf) }))
+            }
+        }
+
+        deserializer.deserialize_bytes(Visitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arc_cstr() {
+        let s = ArcCStr::new(b"").unwrap();
+        let t = ArcCStr::from(c"abc");
+
+        assert_eq!(s.to_bytes_with_nul(), b"\0");
+        assert_eq!(t.to_str().unwrap(), "abc");
+        assert_eq!(t, ArcCStr::new("abc").unwrap());
+        assert!(ArcCStr::new([1, 0]).is_none());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_arc_cstr_deserialize() {
+        let names: Vec<ArcCStr> = serde_json::from_str(r#"["lo", "eth0"]"#).unwrap();
+
+        assert_eq!(names[1].to_bytes(), b"eth0");
+        assert!(serde_json::from_str::<ArcCStr>(r#""a\u0000b""#).is_err());
+        assert!(serde_json::from_str::<ArcCStr>("[97, 0, 98]").is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_arc_cstr_serde_round_trip() {
+        let name = ArcCStr::new("eth0").unwrap();
+        let json = serde_json::to_string(&name).unwrap();
+
+        assert_eq!(json, "[101,116,104,48]");
+        assert_eq!(serde_json::from_str::<ArcCStr>(&json).unwrap(), name);
+    }
+}
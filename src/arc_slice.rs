@@ -1,10 +1,136 @@
 #![cfg(target_has_atomic = "ptr")]
-use crate::arc::{data_offset, padding_needed, ArcInner};
-use std::{
-    alloc::{self, Layout},
-    mem, ptr,
-    sync::{atomic::AtomicUsize, Arc},
-};
+use crate::arc::{into_arc, ArcBuf};
+#[cfg(feature = "allocator_api")]
+use crate::raw::RawBuf;
+use std::{borrow::Borrow, fmt, ops::Deref, sync::Arc};
+
+/// A newtype around an `Arc<[T]>`, for implementing traits that can't be implemented on
+/// `Arc<[T]>` itself.
+///
+/// With the `serde` feature, it deserializes sequences straight into a single `Arc<[T]>`
+/// allocation instead of collecting them into a `Vec<T>` first.
+///
+/// # Examples
+/// ```rust
+/// use collect_into_rc_slice::*;
+///
+/// let slice = ArcSlice::from((1..=3).collect_into_arc_slice());
+///
+/// assert_eq!(&*slice, &[1, 2, 3]);
+/// ```
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct ArcSlice<T>(pub Arc<[T]>);
+
+impl<T> ArcSlice<T> {
+    #[inline]
+    pub fn as_slice(&self) -> &[T] {
+        &self.0
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> Arc<[T]> {
+        self.0
+    }
+}
+
+impl<T> Clone for ArcSlice<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T> From<Arc<[T]>> for ArcSlice<T> {
+    #[inline]
+    fn from(slice: Arc<[T]>) -> Self {
+        Self(slice)
+    }
+}
+
+impl<T> From<ArcSlice<T>> for Arc<[T]> {
+    #[inline]
+    fn from(slice: ArcSlice<T>) -> Self {
+        slice.0
+    }
+}
+
+impl<T> FromIterator<T> for ArcSlice<T> {
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self(into_arc(ArcBuf::from_iter(iter.into_iter())))
+    }
+}
+
+impl<T> Deref for ArcSlice<T> {
+    type Target = [T];
+
+    #[inline]
+    fn deref(&self) -> &[T] {
+        &self.0
+    }
+}
+
+impl<T> AsRef<[T]> for ArcSlice<T> {
+    #[inline]
+    fn as_ref(&self) -> &[T] {
+        &self.0
+    }
+}
+
+impl<T> Borrow<[T]> for ArcSlice<T> {
+    #[inline]
+    fn borrow(&self) -> &[T] {
+        &self.0
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for ArcSlice<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_slice(), f)
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<T: Send> rayon::iter::FromParallelIterator<T> for ArcSlice<T> {
+    /// Collects the items into per-thread segments, which are then moved into the `Arc<[T]>`
+    /// allocation in parallel.
+    fn from_par_iter<I: rayon::iter::IntoParallelIterator<Item = T>>(iter: I) -> Self {
+        Self(into_arc(crate::par::from_par_iter(iter.into_par_iter())))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize> serde::Serialize for ArcSlice<T> {
+    #[inline]
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: serde::Deserialize<'de>> serde::Deserialize<'de> for ArcSlice<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Visitor<T>(std::marker::PhantomData<T>);
+
+        impl<'de, T: serde::Deserialize<'de>> serde::de::Visitor<'de> for Visitor<T> {
+            type Value = ArcSlice<T>;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a sequence")
+            }
+
+            fn visit_seq<S: serde::de::SeqAccess<'de>>(
+                self,
+                seq: S,
+            ) -> Result<ArcSlice<T>, S::Error> {
+                Ok(ArcSlice(into_arc(crate::raw::from_seq(seq)?)))
+            }
+        }
+
+        deserializer.deserialize_seq(Visitor(std::marker::PhantomData))
+    }
+}
 
 pub trait CollectIntoArcSlice<T> {
     /// Collects the iterator into an `Arc<[T]>`.
@@ -28,87 +154,75 @@ pub trait CollectIntoArcSlice<T> {
 
 impl<I, T> CollectIntoArcSlice<T> for I
 where
-    I: Iterator<Item = T>,
+    I: IntoIterator<Item = T>,
 {
     fn collect_into_arc_slice(self) -> Arc<[T]> {
-        let metadata = data_offset::<T>();
-        let align = mem::align_of::<ArcInner<()>>();
-
-        // the size should be at least metadata
-        // but if bounds are known, it should be at least largest_known_bound + metadata
-        let (lower_bound, upper_bound) = self.size_hint();
-        let mut len = metadata;
-        let mut cap = upper_bound.unwrap_or(lower_bound) * mem::size_of::<T>() + metadata;
-
-        // SAFETY:
-        // - `len` is always greater than or equal to `metadata` which is non-zero.
-        // - `align` is always a power of two and non-zero.
-        // - The layout is padded to the alignment.
-        let layout = Layout::from_size_align(cap, align).unwrap().pad_to_align();
-        let mut alloc = unsafe { alloc::alloc(layout) };
-
-        if alloc.is_null() {
-            alloc::handle_alloc_error(layout);
-        }
+        into_arc(ArcBuf::from_iter(self.into_iter()))
+    }
+}
+
+#[cfg(feature = "allocator_api")]
+pub trait CollectIntoArcSliceIn<T> {
+    /// Collects the iterator into an `Arc<[T], A>` allocated with `alloc`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// #![feature(allocator_api)]
+    /// use std::alloc::Global;
+    /// use collect_into_rc_slice::*;
+    ///
+    /// let arr = [1, 2, 3, 4, 5];
+    /// let arc = arr.into_iter().collect_into_arc_slice_in(Global);
+    ///
+    /// assert_eq!(&*arc, &[1, 2, 3, 4, 5]);
+    /// ```
+    fn collect_into_arc_slice_in<A: std::alloc::Allocator>(self, alloc: A) -> Arc<[T], A>;
+}
 
-        // SAFETY: The metadata part is not meant to be valid UTF-8 data, so it's safe to
-        // initialize it with arbitrary data.
-        unsafe {
-            let init: *const u8 = &ArcInner {
-                strong: AtomicUsize::new(1),
-                weak: AtomicUsize::new(1),
-                data: (),
-            } as *const _ as *const u8;
+#[cfg(feature = "allocator_api")]
+impl<I, T> CollectIntoArcSliceIn<T> for I
+where
+    I: IntoIterator<Item = T>,
+{
+    fn collect_into_arc_slice_in<A: std::alloc::Allocator>(self, alloc: A) -> Arc<[T], A> {
+        crate::arc::into_arc_in(RawBuf::from_iter_in(self.into_iter(), alloc))
+    }
+}
 
-            ptr::copy_nonoverlapping(init, alloc, metadata);
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        for item in self {
-            if len + mem::size_of::<T>() > cap {
-                // SAFETY:
-                // - `size` is always non-zero.
-                // - `align` is always a power of two and non-zero.
-                // - The layout is padded to the alignment.
-                let layout = Layout::from_size_align(cap, align).unwrap().pad_to_align();
-                cap *= 2;
-                alloc = unsafe { alloc::realloc(alloc, layout, cap) };
-
-                if alloc.is_null() {
-                    alloc::handle_alloc_error(layout);
-                }
-            }
+    #[test]
+    fn test_arc_slice_newtype() {
+        let slice: ArcSlice<String> = ["a", "b"].into_iter().map(String::from).collect();
+        let inner: Arc<[String]> = slice.clone().into();
 
-            unsafe {
-                ptr::write(alloc.add(len) as *mut T, item);
-            }
-            len += mem::size_of::<T>();
-        }
+        assert_eq!(format!("{slice:?}"), r#"["a", "b"]"#);
+        assert_eq!(Arc::strong_count(&inner), 2);
+    }
 
-        // Trim the allocation down to `len`.
-        if cap > len {
-            // SAFETY:
-            // - `cap` is always non-zero.
-            // - `align` is always a power of two and non-zero.
-            // - The layout is padded to the alignment.
-            let layout = Layout::from_size_align(cap, align).unwrap().pad_to_align();
-            alloc = unsafe { alloc::realloc(alloc, layout, len + padding_needed(len, align)) };
-
-            if alloc.is_null() {
-                alloc::handle_alloc_error(layout);
-            }
-        }
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_arc_slice_deserialize() {
+        let slice: ArcSlice<ArcSlice<u32>> = serde_json::from_str("[[1, 2], [], [3]]").unwrap();
+
+        assert_eq!(slice.len(), 3);
+        assert_eq!(&*slice[0], &[1, 2]);
+        assert!(slice[1].is_empty());
+        assert!(serde_json::from_str::<ArcSlice<u32>>(r#"[1, "2"]"#).is_err());
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_arc_slice_from_par_iter() {
+        use rayon::prelude::*;
+
+        let slice: ArcSlice<String> = (0..10_000).into_par_iter().map(|i| i.to_string()).collect();
+        assert_eq!(slice.len(), 10_000);
+        assert!(slice.iter().enumerate().all(|(i, s)| *s == i.to_string()));
 
-        // SAFETY: The allocation is non-null and has the proper layout.
-        let data = unsafe {
-            ptr::slice_from_raw_parts(
-                alloc.add(metadata) as *mut T,
-                (len - metadata) / mem::size_of::<T>(),
-            )
-        };
-
-        // SAFETY:
-        // - `data` is a valid pointer to a `[T]` located at the heap
-        // - `data` is part of an ArcInner with proper metadata.
-        unsafe { Arc::from_raw(data) }
+        let filtered: ArcSlice<u32> = (0..1000).into_par_iter().filter(|i| i % 3 == 0).collect();
+        assert_eq!(&*filtered, &(0..1000).step_by(3).collect::<Vec<_>>()[..]);
     }
 }
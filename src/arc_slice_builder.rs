@@ -0,0 +1,280 @@
+#![cfg(target_has_atomic = "ptr")]
+use crate::{
+    arc::{into_arc, ArcBuf},
+    read::PROBE_SIZE,
+};
+use std::{
+    fmt,
+    io::{self, ErrorKind, IoSliceMut, Read},
+    mem::MaybeUninit,
+    ptr::NonNull,
+    sync::Arc,
+};
+
+/// A growable buffer that is written in place of the `Arc<[T]>` it becomes once it is finished.
+///
+/// It is for building a slice piece by piece when the pieces don't come from a single iterator,
+/// e.g. from several reads of a socket. [`finish`](ArcSliceBuilder::finish) trims the allocation
+/// and hands it over to the `Arc` without copying the elements.
+///
+/// # Examples
+/// ```rust
+/// use collect_into_rc_slice::*;
+///
+/// let mut builder = ArcSliceBuilder::with_capacity(4);
+/// builder.push(1);
+/// builder.extend([2, 3]);
+///
+/// assert_eq!(&*builder.finish(), &[1, 2, 3]);
+/// ```
+pub struct ArcSliceBuilder<T> {
+    buf: ArcBuf<T>,
+    /// The end of the bytes of the buffer known to be initialized, which may be past its length
+    /// after `read_from` zeroed its spare capacity.
+    initialized: usize,
+}
+
+/// An [`ArcSliceBuilder`] that a frozen `Arc<[T]>` was turned back into with
+/// [`from_unique`](ArcSliceBuilder::from_unique), e.g. to append a batch to it.
+pub type ArcVec<T> = ArcSliceBuilder<T>;
+
+impl<T> ArcSliceBuilder<T> {
+    #[inline]
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    #[inline]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            buf: ArcBuf::with_capacity(capacity),
+            initialized: 0,
+        }
+    }
+
+    /// Takes back the allocation of `arc` if it is the only pointer to it, with no other `Arc`s or
+    /// `Weak`s, so more elements can be appended to it before it is finished again. Otherwise
+    /// `arc` is returned as is.
+    ///
+    /// The elements are neither copied nor cloned. Appending to them grows the allocation with
+    /// `realloc`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use collect_into_rc_slice::*;
+    ///
+    /// let batch = (1..=3).collect_into_arc_slice();
+    ///
+    /// let mut builder = ArcVec::from_unique(batch).unwrap();
+    /// builder.extend(4..=5);
+    /// let batch = builder.finish();
+    ///
+    /// assert_eq!(&*batch, &[1, 2, 3, 4, 5]);
+    ///
+    /// let shared = batch.clone();
+    /// assert!(ArcVec::from_unique(batch).is_err());
+    /// ```
+    pub fn from_unique(mut arc: Arc<[T]>) -> Result<Self, Arc<[T]>> {
+        if Arc::get_mut(&mut arc).is_none() {
+            return Err(arc);
+        }
+
+        let len = arc.len();
+        let data = Arc::into_raw(arc) as *const T as *mut u8;
+        // SAFETY:
+        // - The data is preceded by the reference counts in the same allocation.
+        // - `Arc` allocates its slices with the layout of an `ArcBuf` with no spare capacity.
+        // - The `Arc` was uniquely owned, and it is never used or dropped again.
+        let buf = unsafe {
+            let ptr = NonNull::new_unchecked(data.sub(ArcBuf::<T>::data_offset()));
+            ArcBuf::from_raw_parts(ptr, len)
+        };
+
+        Ok(Self {
+            buf,
+            initialized: len,
+        })
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.buf.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.buf.len() == 0
+    }
+
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.buf.capacity()
+    }
+
+    /// Makes sure there is room for at least `additional` more elements.
+    #[inline]
+    pub fn reserve(&mut self, additional: usize) {
+        self.buf.reserve(additional);
+    }
+
+    #[inline]
+    pub fn push(&mut self, item: T) {
+        self.buf.push(item);
+    }
+
+    #[inline]
+    pub fn as_slice(&self) -> &[T] {
+        self.buf.as_slice()
+    }
+
+    #[inline]
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        self.buf.as_mut_slice()
+    }
+
+    /// Trims the allocation and turns it into an `Arc<[T]>`.
+    #[inline]
+    pub fn finish(self) -> Arc<[T]> {
+        into_arc(self.buf)
+    }
+}
+
+impl ArcSliceBuilder<u8> {
+    /// Reads from `reader` once, appending what was read and returning how many bytes that is,
+    /// which is only 0 at the end of the stream.
+    ///
+    /// The spare capacity of the builder and a small stack buffer for whatever doesn't fit in it
+    /// are passed to `read_vectored` together, so a single call fills the builder and tells
+    /// whether there is more to read. The spare capacity is zeroed once before the first read into
+    /// it, and never again.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use collect_into_rc_slice::*;
+    ///
+    /// let mut reader = &b"header|body"[..];
+    /// let mut builder = ArcSliceBuilder::with_capacity(6);
+    ///
+    /// assert_eq!(builder.read_from(&mut reader).unwrap(), 11);
+    /// assert_eq!(builder.read_from(&mut reader).unwrap(), 0);
+    /// assert_eq!(&*builder.finish(), b"header|body");
+    /// ```
+    pub fn read_from<R: Read + ?Sized>(&mut self, reader: &mut R) -> io::Result<usize> {
+        let len = self.buf.len();
+        let zeroed = self.initialized.max(len) - len;
+
+        let spare = self.buf.spare_capacity_mut();
+        spare[zeroed..].fill(MaybeUninit::new(0));
+        // SAFETY: The whole spare capacity is initialized.
+        let spare = unsafe { &mut *(spare as *mut [MaybeUninit<u8>] as *mut [u8]) };
+        let room = spare.len();
+        self.initialized = len + room;
+
+        let mut overflow = [0; PROBE_SIZE];
+        let n = loop {
+            let mut bufs = [IoSliceMut::new(spare), IoSliceMut::new(&mut overflow)];
+            match reader.read_vectored(&mut bufs) {
+                Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+                result => break result?,
+            }
+        };
+        assert!(
+            n <= room + PROBE_SIZE,
+            "the reader returned an invalid length"
+        );
+
+        // SAFETY: The first `n` bytes of the spare capacity, at most, were read into.
+        unsafe { self.buf.set_len(len + n.min(room)) };
+        if n > room {
+            self.buf.extend_from_slice(&overflow[..n - room]);
+        }
+        Ok(n)
+    }
+
+    /// Reads from `reader` until the end of the stream, returning how many bytes were read.
+    pub fn read_to_end<R: Read + ?Sized>(&mut self, reader: &mut R) -> io::Result<usize> {
+        let start = self.buf.len();
+        while self.read_from(reader)? != 0 {}
+        Ok(self.buf.len() - start)
+    }
+}
+
+impl<T> Default for ArcSliceBuilder<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Extend<T> for ArcSliceBuilder<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.buf.reserve(iter.size_hint().0);
+        for item in iter {
+            self.buf.push(item);
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for ArcSliceBuilder<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_slice(), f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::read::Chunked;
+
+    #[test]
+    fn test_arc_slice_builder_read_vectored_overflow() {
+        let data: Vec<u8> = (0..100).collect();
+        let mut builder = ArcSliceBuilder::with_capacity(90);
+        builder.extend(data[..10].iter().copied());
+
+        // the spare capacity is filled and the rest of the stream lands in the stack buffer
+        assert_eq!(builder.read_from(&mut &data[10..]).unwrap(), 90);
+        assert_eq!(builder.as_slice(), &data[..]);
+        assert_eq!(builder.read_from(&mut &[][..]).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_arc_slice_builder_read_to_end_chunked() {
+        let data: Vec<u8> = (0..=255).cycle().take(1000).collect();
+        let mut builder = ArcSliceBuilder::new();
+        builder.push(b'>');
+
+        let read = builder
+            .read_to_end(&mut Chunked::new(&data, 13).first_buffer_only())
+            .unwrap();
+        assert_eq!(read, 1000);
+
+        let arc = builder.finish();
+        assert_eq!(arc[0], b'>');
+        assert_eq!(&arc[1..], &data[..]);
+    }
+
+    #[test]
+    fn test_arc_vec_from_unique_append() {
+        let arc: Arc<[String]> = Arc::from(["a".to_string(), "b".to_string()]);
+        let weak = Arc::downgrade(&arc);
+        let arc = ArcVec::from_unique(arc).unwrap_err();
+        drop(weak);
+
+        let mut vec = ArcVec::from_unique(arc).unwrap();
+        assert_eq!(vec.capacity(), 2);
+        vec.extend(["c".to_string(), "d".to_string()]);
+        assert_eq!(&*vec.finish(), ["a", "b", "c", "d"]);
+
+        let mut zsts = ArcVec::from_unique(Arc::from([(), ()])).unwrap();
+        zsts.push(());
+        assert_eq!(zsts.finish().len(), 3);
+
+        // empty slices may share a static allocation
+        if let Ok(mut empty) = ArcVec::from_unique(Arc::<[u64]>::default()) {
+            empty.push(1);
+            assert_eq!(&*empty.finish(), &[1]);
+        }
+    }
+}
@@ -0,0 +1,382 @@
+#![cfg(target_has_atomic = "ptr")]
+use std::{
+    borrow::Borrow,
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+    mem::{self, ManuallyDrop},
+    ops::{Bound, Deref, Index, Range, RangeBounds},
+    ptr,
+    slice::{self, SliceIndex},
+    sync::Arc,
+};
+
+/// A thread-safe view of a range of an `Arc<[T]>`, sharing the allocation of the whole slice.
+///
+/// Views own a reference to the whole slice, so windows of one shared buffer can be passed
+/// between threads without any lifetimes.
+///
+/// # Examples
+/// ```rust
+/// use collect_into_rc_slice::*;
+///
+/// let view = ArcSliceView::new((0..10).collect_into_arc_slice());
+/// let (head, tail) = view.slice(2..).split_at(3);
+///
+/// assert_eq!(&*head, &[2, 3, 4]);
+/// assert_eq!(&*tail, &[5, 6, 7, 8, 9]);
+/// ```
+pub struct ArcSliceView<T> {
+    parent: Arc<[T]>,
+    start: usize,
+    end: usize,
+}
+
+impl<T> ArcSliceView<T> {
+    /// A view covering all of `parent`.
+    #[inline]
+    pub fn new(parent: Arc<[T]>) -> Self {
+        let end = parent.len();
+        Self {
+            parent,
+            start: 0,
+            end,
+        }
+    }
+
+    #[inline]
+    pub fn as_slice(&self) -> &[T] {
+        // SAFETY: The range is always in bounds of the parent.
+        unsafe { self.parent.get_unchecked(self.start..self.end) }
+    }
+
+    /// The whole slice this is a view of.
+    #[inline]
+    pub fn parent(&self) -> &Arc<[T]> {
+        &self.parent
+    }
+
+    /// The range of this view within [`parent`](ArcSliceView::parent).
+    #[inline]
+    pub fn range(&self) -> Range<usize> {
+        self.start..self.end
+    }
+
+    /// Returns a view of a range of this view, sharing the same allocation.
+    ///
+    /// # Panics
+    /// Panics if the range is out of bounds.
+    pub fn slice(&self, range: impl RangeBounds<usize>) -> Self {
+        let bounds: (Bound<usize>, Bound<usize>) =
+            (range.start_bound().cloned(), range.end_bound().cloned());
+        // indexing panics with the same messages as slicing a `[T]`
+        let len = self.as_slice()[bounds].len();
+        let start = match bounds.0 {
+            Bound::Included(start) => start,
+            Bound::Excluded(start) => start + 1,
+            Bound::Unbounded => 0,
+        };
+
+        Self {
+            parent: self.parent.clone(),
+            start: self.start + start,
+            end: self.start + start + len,
+        }
+    }
+
+    /// Returns the view for `sub`, which has to be a part of this view.
+    ///
+    /// Zero-sized elements have no address to tell them apart, so for those the view always
+    /// starts at the beginning of this one.
+    ///
+    /// # Panics
+    /// Panics if `sub` is not contained in this view.
+    pub fn slice_ref(&self, sub: &[T]) -> Self {
+        let range = self.as_slice().as_ptr_range();
+        let sub_range = sub.as_ptr_range();
+
+        assert!(
+            range.start <= sub_range.start && sub_range.end <= range.end,
+            "the slice is not a part of this view"
+        );
+
+        let offset = if mem::size_of::<T>() == 0 {
+            0
+        } else {
+            // SAFETY: Both pointers are within the same allocation, and `sub` starts after `self`.
+            unsafe { sub_range.start.offset_from(range.start) as usize }
+        };
+
+        Self {
+            parent: self.parent.clone(),
+            start: self.start + offset,
+            end: self.start + offset + sub.len(),
+        }
+    }
+
+    /// Splits the view in two at `mid`.
+    ///
+    /// # Panics
+    /// Panics if `mid > len`.
+    pub fn split_at(&self, mid: usize) -> (Self, Self) {
+        assert!(mid <= self.len(), "mid > len");
+        (self.slice(..mid), self.slice(mid..))
+    }
+
+    /// Moves the elements of the view out into a `Vec<T>` if this is the only reference to the
+    /// slice, dropping the elements outside of the view. Otherwise the view is returned as is.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use collect_into_rc_slice::*;
+    ///
+    /// let view = ArcSliceView::new((0..5).map(|i| i.to_string()).collect_into_arc_slice());
+    /// let window = view.slice(1..3);
+    ///
+    /// let window = window.try_unwrap().unwrap_err();
+    /// drop(view);
+    ///
+    /// assert_eq!(window.try_unwrap().unwrap(), ["1", "2"]);
+    /// ```
+    pub fn try_unwrap(self) -> Result<Vec<T>, Self> {
+        let Self {
+            mut parent,
+            start,
+            end,
+        } = self;
+
+        if Arc::get_mut(&mut parent).is_none() {
+            return Err(Self { parent, start, end });
+        }
+
+        // SAFETY: `ManuallyDrop<T>` has the same layout as `T`.
+        let mut parent =
+            unsafe { Arc::from_raw(Arc::into_raw(parent) as *const [ManuallyDrop<T>]) };
+        let items = Arc::get_mut(&mut parent).expect("the slice is unique");
+
+        // SAFETY:
+        // - The slice is uniquely owned, and its elements are never dropped by the `Arc` anymore.
+        // - Every element is either moved into the `Vec` or dropped, exactly once.
+        unsafe {
+            let view = items[start..end]
+                .iter()
+                .map(|item| ptr::read(&**item))
+                .collect();
+            ptr::drop_in_place(&mut items[..start] as *mut [ManuallyDrop<T>] as *mut [T]);
+            ptr::drop_in_place(&mut items[end..] as *mut [ManuallyDrop<T>] as *mut [T]);
+            Ok(view)
+        }
+    }
+}
+
+impl<T> Clone for ArcSliceView<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            parent: self.parent.clone(),
+            start: self.start,
+            end: self.end,
+        }
+    }
+}
+
+impl<T> From<Arc<[T]>> for ArcSliceView<T> {
+    #[inline]
+    fn from(parent: Arc<[T]>) -> Self {
+        Self::new(parent)
+    }
+}
+
+impl<T> Deref for ArcSliceView<T> {
+    type Target = [T];
+
+    #[inline]
+    fn deref(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+impl<T> AsRef<[T]> for ArcSliceView<T> {
+    #[inline]
+    fn as_ref(&self) -> &[T] {
+        self
+    }
+}
+
+impl<T, I: SliceIndex<[T]>> Index<I> for ArcSliceView<T> {
+    type Output = I::Output;
+
+    #[inline]
+    fn index(&self, index: I) -> &I::Output {
+        &(**self)[index]
+    }
+}
+
+/// An empty view.
+impl<T> Default for ArcSliceView<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new(Arc::default())
+    }
+}
+
+impl<'a, T> IntoIterator for &'a ArcSliceView<T> {
+    type Item = &'a T;
+    type IntoIter = slice::Iter<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T> Borrow<[T]> for ArcSliceView<T> {
+    #[inline]
+    fn borrow(&self) -> &[T] {
+        self
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for ArcSliceView<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_slice(), f)
+    }
+}
+
+impl<T: PartialEq> PartialEq for ArcSliceView<T> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl<T: PartialEq> PartialEq<[T]> for ArcSliceView<T> {
+    #[inline]
+    fn eq(&self, other: &[T]) -> bool {
+        self.as_slice() == other
+    }
+}
+
+impl<T: Eq> Eq for ArcSliceView<T> {}
+
+impl<T: PartialOrd> PartialOrd for ArcSliceView<T> {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.as_slice().partial_cmp(other.as_slice())
+    }
+}
+
+impl<T: Ord> Ord for ArcSliceView<T> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_slice().cmp(other.as_slice())
+    }
+}
+
+impl<T: Hash> Hash for ArcSliceView<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_slice().hash(state);
+    }
+}
+
+/// Splits `arc` into views of `chunk_len` elements that all share its allocation, with the last
+/// one being shorter if the length doesn't divide evenly, like `slice::chunks`.
+///
+/// # Panics
+/// Panics if `chunk_len` is 0.
+///
+/// # Examples
+/// ```rust
+/// use collect_into_rc_slice::*;
+///
+/// let rc = (0..7).collect_into_arc_slice();
+/// let chunks: Vec<_> = arc_slice_chunks(rc, 3).collect();
+///
+/// assert_eq!(&*chunks[0], &[0, 1, 2]);
+/// assert_eq!(&*chunks[1], &[3, 4, 5]);
+/// assert_eq!(&*chunks[2], &[6]);
+/// ```
+pub fn arc_slice_chunks<T>(
+    arc: Arc<[T]>,
+    chunk_len: usize,
+) -> impl Iterator<Item = ArcSliceView<T>> {
+    assert!(chunk_len != 0, "chunk size must be non-zero");
+
+    let len = arc.len();
+    (0..len).step_by(chunk_len).map(move |start| ArcSliceView {
+        parent: arc.clone(),
+        start,
+        end: len.min(start.saturating_add(chunk_len)),
+    })
+}
+
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize> serde::Serialize for ArcSliceView<T> {
+    #[inline]
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CollectIntoArcSlice;
+
+    #[test]
+    fn test_arc_slice_chunks() {
+        let rc = (0..10).map(|i| i.to_string()).collect_into_arc_slice();
+        let chunks: Vec<_> = arc_slice_chunks(rc.clone(), 4).collect();
+
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(&*chunks[2], &["8", "9"]);
+        assert_eq!(chunks[1].range(), 4..8);
+        assert_eq!(Arc::strong_count(&rc), 4);
+    }
+
+    #[test]
+    fn test_arc_slice_view_slice_ref() {
+        let view = ArcSliceView::new((0..10).collect_into_arc_slice());
+        let sub = view.slice_ref(&view[3..5]);
+
+        assert_eq!(sub.range(), 3..5);
+        assert_eq!(sub.slice(1..), view.slice(4..5));
+    }
+
+    #[test]
+    fn test_arc_slice_view_try_unwrap() {
+        let arc = (0..6).map(|i| vec![i]).collect_into_arc_slice();
+        let chunks: Vec<_> = arc_slice_chunks(arc, 4).collect();
+        let handles: Vec<_> = chunks
+            .into_iter()
+            .map(|chunk| std::thread::spawn(move || chunk.len()))
+            .collect();
+        let lens: Vec<_> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+        assert_eq!(lens, [4, 2]);
+
+        let view = ArcSliceView::new((0..6).map(|i| vec![i]).collect_into_arc_slice());
+        let window = view.slice(2..4);
+        drop(view);
+        assert_eq!(window.try_unwrap().unwrap(), [vec![2], vec![3]]);
+    }
+
+    #[test]
+    fn test_arc_slice_chunks_zst() {
+        let rc = std::iter::repeat_n((), 5).collect_into_arc_slice();
+
+        assert_eq!(arc_slice_chunks(rc, 2).map(|c| c.len()).sum::<usize>(), 5);
+    }
+
+    #[test]
+    fn test_arc_slice_view_std_traits() {
+        let view = ArcSliceView::from(Arc::<[i32]>::from([1, 2, 3, 4])).slice(1..);
+        let mut sum = 0;
+        for item in &view {
+            sum += item;
+        }
+
+        assert_eq!(sum, 9);
+        assert_eq!(view[1..], [3, 4]);
+        assert!(ArcSliceView::<String>::default().is_empty());
+    }
+}
@@ -1,10 +1,182 @@
 #![cfg(target_has_atomic = "ptr")]
-use crate::arc::{data_offset, padding_needed, ArcInner};
-use std::{
-    alloc::{self, Layout},
-    mem, ptr, slice,
-    sync::{atomic::AtomicUsize, Arc},
+use crate::{
+    arc::{into_arc_str, ArcBuf},
+    into_char::IntoChar,
 };
+use std::{borrow::Borrow, fmt, ops::Deref, str::Utf8Error, sync::Arc};
+
+/// A newtype around an `Arc<str>`, for implementing traits that can't be implemented on `Arc<str>`
+/// itself.
+///
+/// With the `serde` feature, it deserializes by copying the string straight into a new
+/// `Arc<str>` allocation, without going through a `String` first.
+///
+/// # Examples
+/// ```rust
+/// use collect_into_rc_slice::*;
+/// use std::sync::Arc;
+///
+/// let s = ArcStr::from("shared");
+/// let t = s.clone();
+///
+/// assert_eq!(&*t, "shared");
+/// assert!(Arc::ptr_eq(&s.0, &t.0));
+/// ```
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct ArcStr(pub Arc<str>);
+
+impl ArcStr {
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> Arc<str> {
+        self.0
+    }
+}
+
+/// Copies `s` into a new `Arc<str>`.
+pub(crate) fn arc_str_from(s: &str) -> Arc<str> {
+    let mut buf = ArcBuf::with_capacity(s.len());
+    buf.extend_from_slice(s.as_bytes());
+    // SAFETY: The buffer is a copy of a `str`.
+    unsafe { into_arc_str(buf) }
+}
+
+/// Converts `bytes` into an `Arc<str>` in place, without copying them, if they are valid UTF-8.
+///
+/// With the `rayon` feature, large buffers are validated in parallel, in chunks split on code point
+/// boundaries.
+///
+/// # Examples
+/// ```rust
+/// use collect_into_rc_slice::*;
+/// use std::sync::Arc;
+///
+/// let bytes: Arc<[u8]> = Arc::from(&b"caf\xc3\xa9"[..]);
+/// assert_eq!(&*arc_str_from_utf8(bytes).unwrap(), "café");
+///
+/// let err = arc_str_from_utf8(Arc::from(&b"caf\xc3"[..])).unwrap_err();
+/// assert_eq!(err.valid_up_to(), 3);
+/// ```
+pub fn arc_str_from_utf8(bytes: Arc<[u8]>) -> Result<Arc<str>, Utf8Error> {
+    #[cfg(feature = "rayon")]
+    crate::par::validate_utf8(&bytes)?;
+    #[cfg(not(feature = "rayon"))]
+    std::str::from_utf8(&bytes)?;
+
+    // SAFETY: `str` has the same layout as `[u8]` and the bytes were validated as UTF-8.
+    Ok(unsafe { Arc::from_raw(Arc::into_raw(bytes) as *const str) })
+}
+
+impl From<Arc<str>> for ArcStr {
+    #[inline]
+    fn from(s: Arc<str>) -> Self {
+        Self(s)
+    }
+}
+
+impl From<&str> for ArcStr {
+    #[inline]
+    fn from(s: &str) -> Self {
+        Self(arc_str_from(s))
+    }
+}
+
+impl From<ArcStr> for Arc<str> {
+    #[inline]
+    fn from(s: ArcStr) -> Self {
+        s.0
+    }
+}
+
+impl Deref for ArcStr {
+    type Target = str;
+
+    #[inline]
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for ArcStr {
+    #[inline]
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Borrow<str> for ArcStr {
+    #[inline]
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for ArcStr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl fmt::Display for ArcStr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self.as_str(), f)
+    }
+}
+
+impl PartialEq<str> for ArcStr {
+    #[inline]
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for ArcStr {
+    #[inline]
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for ArcStr {
+    #[inline]
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for ArcStr {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Visitor;
+
+        impl serde::de::Visitor<'_> for Visitor {
+            type Value = ArcStr;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a string")
+            }
+
+            fn visit_str<E: serde::de::Error>(self, s: &str) -> Result<ArcStr, E> {
+                Ok(ArcStr(arc_str_from(s)))
+            }
+
+            fn visit_bytes<E: serde::de::Error>(self, bytes: &[u8]) -> Result<ArcStr, E> {
+                match std::str::from_utf8(bytes) {
+                    Ok(s) => self.visit_str(s),
+                    Err(_) => Err(E::invalid_value(serde::de::Unexpected::Bytes(bytes), &self)),
+                }
+            }
+        }
+
+        deserializer.deserialize_str(Visitor)
+    }
+}
 
 pub trait CollectIntoArcStr {
     /// Collects the iterator into an `Arc<str>`.
@@ -27,85 +199,16 @@ pub trait CollectIntoArcStr {
 
 impl<T> CollectIntoArcStr for T
 where
-    T: Iterator<Item = char>,
+    T: IntoIterator,
+    T::Item: IntoChar,
 {
     fn collect_into_arc_str(self) -> Arc<str> {
-        let metadata = data_offset::<u8>();
-        let align = mem::align_of::<ArcInner<()>>();
-
-        // the size should be at least metadata
-        // but if bounds are known, it should be at least largest_known_bound+metadata
-        let (lower_bound, upper_bound) = self.size_hint();
-        let mut len = metadata;
-        let mut cap = upper_bound.unwrap_or(lower_bound) + metadata;
-
-        // SAFETY:
-        // - `len` is always greater than or equal to `metadata` which is non-zero.
-        // - `align` is always a power of two and non-zero.
-        // - The layout is padded to the alignment.
-        let layout = Layout::from_size_align(cap, align).unwrap().pad_to_align();
-        let mut alloc = unsafe { alloc::alloc(layout) };
-
-        if alloc.is_null() {
-            alloc::handle_alloc_error(layout);
-        }
-
-        // SAFETY: The metadata part is not meant to be valid UTF-8 data, so it's safe to
-        // initialize it with arbitrary data.
+        // SAFETY: The buffer is made up of UTF-8 encoded `char`s.
         unsafe {
-            let init: *const u8 = &ArcInner {
-                strong: AtomicUsize::new(1),
-                weak: AtomicUsize::new(1),
-                data: (),
-            } as *const _ as *const u8;
-
-            ptr::copy_nonoverlapping(init, alloc, metadata);
-        }
-
-        for c in self {
-            let new_len = len + c.len_utf8();
-            if new_len > cap {
-                // SAFETY:
-                // - `size` is always non-zero.
-                // - `align` is always a power of two and non-zero.
-                // - The layout is padded to the alignment.
-                let layout = Layout::from_size_align(cap, align).unwrap().pad_to_align();
-                cap *= 2;
-                alloc = unsafe { alloc::realloc(alloc, layout, cap) };
-
-                if alloc.is_null() {
-                    alloc::handle_alloc_error(layout);
-                }
-            }
-
-            unsafe {
-                let ptr = alloc.add(len);
-                len = new_len;
-                c.encode_utf8(slice::from_raw_parts_mut(ptr, c.len_utf8()));
-            }
+            into_arc_str(ArcBuf::from_chars(
+                self.into_iter().map(IntoChar::into_char),
+            ))
         }
-
-        // Trim the allocation down to `len`.
-        if cap > len {
-            // SAFETY:
-            // - `cap` is always non-zero.
-            // - `align` is always a power of two and non-zero.
-            // - The layout is padded to the alignment.
-            let layout = Layout::from_size_align(cap, align).unwrap().pad_to_align();
-            alloc = unsafe { alloc::realloc(alloc, layout, len + padding_needed(len, align)) };
-
-            if alloc.is_null() {
-                alloc::handle_alloc_error(layout);
-            }
-        }
-
-        let data =
-            unsafe { ptr::slice_from_raw_parts(alloc.add(metadata), len - metadata) as *const str };
-
-        // SAFETY:
-        // - `data` is a valid pointer to a `str` located at the heap
-        // - `data` is part of an RcBox with proper metadata.
-        unsafe { Arc::from_raw(data) }
     }
 }
 
@@ -123,6 +226,16 @@ mod tests {
         assert_eq!(Arc::weak_count(&s), 0);
     }
 
+    #[test]
+    fn test_arc_str_from_utf8_in_place() {
+        let bytes: Arc<[u8]> = Arc::from("ünïcödé".as_bytes());
+        let ptr = bytes.as_ptr();
+
+        let s = arc_str_from_utf8(bytes).unwrap();
+        assert_eq!(&*s, "ünïcödé");
+        assert_eq!(s.as_ptr(), ptr);
+    }
+
     #[test]
     fn test_collect_into_arc_str_ref() {
         let s = ['a', 'b', 'c'].iter().copied().collect_into_arc_str();
@@ -143,4 +256,24 @@ mod tests {
         assert_eq!(Arc::strong_count(&s), 1);
         assert_eq!(Arc::weak_count(&s), 0);
     }
+
+    #[test]
+    fn test_arc_str_newtype() {
+        let s = ArcStr::from("abc");
+        let inner: Arc<str> = s.clone().into();
+
+        assert_eq!(s.as_str(), "abc");
+        assert_eq!(format!("{s:?}"), "\"abc\"");
+        assert_eq!(Arc::strong_count(&inner), 2);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_arc_str_deserialize() {
+        let s: Vec<ArcStr> = serde_json::from_str(r#"["borrowed", "esc\"aped"]"#).unwrap();
+
+        assert_eq!(s[0].as_str(), "borrowed");
+        assert_eq!(s[1].as_str(), "esc\"aped");
+        assert!(serde_json::from_str::<ArcStr>("1").is_err());
+    }
 }
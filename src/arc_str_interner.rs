@@ -0,0 +1,440 @@
+#![cfg(target_has_atomic = "ptr")]
+use crate::{
+    arc::{into_arc_str, ArcBuf},
+    InternerStats, StaticArcStr,
+};
+use std::{
+    collections::HashSet,
+    fmt,
+    hash::{BuildHasher, RandomState},
+    mem,
+    sync::{Arc, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard},
+};
+
+const DEFAULT_SHARDS: usize = 16;
+
+#[derive(Default)]
+struct Shard {
+    strings: HashSet<StaticArcStr>,
+    string_bytes: usize,
+    interned_since_purge: usize,
+}
+
+impl Shard {
+    fn purge_dead(&mut self) -> usize {
+        let len = self.strings.len();
+        let mut string_bytes = self.string_bytes;
+
+        // A count of 1 means the interner holds the only handle, and since the shard is locked
+        // nothing can clone it anymore.
+        self.strings.retain(|s| {
+            let alive = match s {
+                StaticArcStr::Static(_) => true,
+                StaticArcStr::Arc(arc) => Arc::strong_count(arc) > 1,
+            };
+            if !alive {
+                string_bytes -= s.len();
+            }
+            alive
+        });
+
+        self.string_bytes = string_bytes;
+        self.interned_since_purge = 0;
+        len - self.strings.len()
+    }
+}
+
+/// A thread-safe string interner that hands out deduplicated `Arc<str>`s.
+///
+/// It can be seeded with static strings with [`with_static`](ArcStrInterner::with_static), which
+/// are handed out as they are, without ever allocating, so the strings are returned as a
+/// [`StaticArcStr`].
+///
+/// The strings are spread over a number of independently locked shards by their hash, so threads
+/// interning different strings rarely wait on each other, and looking up a string that is already
+/// interned only takes a read lock.
+///
+/// The interner keeps its strings alive until they are purged, either explicitly with
+/// [`purge_dead`](ArcStrInterner::purge_dead) or automatically with
+/// [`set_auto_purge`](ArcStrInterner::set_auto_purge).
+///
+/// # Examples
+/// ```rust
+/// use collect_into_rc_slice::*;
+/// use std::thread;
+///
+/// let interner = ArcStrInterner::new();
+/// let (a, b) = thread::scope(|s| {
+///     let a = s.spawn(|| interner.get_or_intern("main"));
+///     let b = s.spawn(|| interner.get_or_intern("main"));
+///     (a.join().unwrap(), b.join().unwrap())
+/// });
+///
+/// assert!(StaticArcStr::ptr_eq(&a, &b));
+/// assert_eq!(interner.len(), 1);
+/// ```
+pub struct ArcStrInterner {
+    shards: Box<[RwLock<Shard>]>,
+    hasher: RandomState,
+    auto_purge: Option<usize>,
+}
+
+impl ArcStrInterner {
+    #[inline]
+    pub fn new() -> Self {
+        Self::with_shards(DEFAULT_SHARDS)
+    }
+
+    /// An interner with `shards` shards, rounded up to a power of two.
+    pub fn with_shards(shards: usize) -> Self {
+        let shards = shards.max(1).next_power_of_two();
+
+        Self {
+            shards: (0..shards).map(|_| RwLock::default()).collect(),
+            hasher: RandomState::new(),
+            auto_purge: None,
+        }
+    }
+
+    /// An interner seeded with `strings`, which are never allocated or purged.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use collect_into_rc_slice::*;
+    ///
+    /// const KEYWORDS: &[&str] = &["fn", "let", "match"];
+    ///
+    /// let interner = ArcStrInterner::with_static(KEYWORDS);
+    ///
+    /// assert!(matches!(interner.get_or_intern("let"), StaticArcStr::Static("let")));
+    /// assert!(matches!(interner.get_or_intern("x"), StaticArcStr::Arc(_)));
+    /// ```
+    pub fn with_static(strings: &[&'static str]) -> Self {
+        let interner = Self::new();
+
+        for &s in strings {
+            let mut shard = write(interner.shard(s));
+            if shard.strings.insert(StaticArcStr::Static(s)) {
+                shard.string_bytes += s.len();
+            }
+        }
+
+        interner
+    }
+
+    #[inline]
+    fn shard(&self, s: &str) -> &RwLock<Shard> {
+        let hash = self.hasher.hash_one(s) as usize;
+        &self.shards[hash & (self.shards.len() - 1)]
+    }
+
+    /// Returns the interned copy of `s`, interning it first if needed.
+    pub fn get_or_intern(&self, s: &str) -> StaticArcStr {
+        let shard = self.shard(s);
+
+        if let Some(arc) = read(shard).strings.get(s) {
+            return arc.clone();
+        }
+
+        let mut shard = write(shard);
+        // another thread may have interned it in the meantime
+        if let Some(arc) = shard.strings.get(s) {
+            return arc.clone();
+        }
+
+        let mut buf = ArcBuf::with_capacity(s.len());
+        buf.extend_from_slice(s.as_bytes());
+        // SAFETY: The buffer is a copy of a `str`.
+        let arc = StaticArcStr::Arc(unsafe { into_arc_str(buf) });
+
+        shard.string_bytes += s.len();
+        shard.strings.insert(arc.clone());
+
+        shard.interned_since_purge += 1;
+        if self
+            .auto_purge
+            .is_some_and(|n| shard.interned_since_purge >= n)
+        {
+            shard.purge_dead();
+        }
+
+        arc
+    }
+
+    /// Drops the strings that are only referenced by the interner anymore, returning how many were
+    /// dropped.
+    ///
+    /// The shards are purged one at a time, so this never blocks the whole interner.
+    ///
+    /// A string that is only kept alive through `Weak` handles is dropped too, and if one of them
+    /// is upgraded at the same time the string just isn't deduplicated anymore.
+    pub fn purge_dead(&self) -> usize {
+        self.shards
+            .iter()
+            .map(|shard| write(shard).purge_dead())
+            .sum()
+    }
+
+    /// Makes every shard purge its dead strings after `threshold` strings were newly interned in
+    /// it, or never if it is `None`, which is the default.
+    #[inline]
+    pub fn set_auto_purge(&mut self, threshold: Option<usize>) {
+        self.auto_purge = threshold;
+    }
+
+    /// Returns the interned copy of `s`, if there is one.
+    #[inline]
+    pub fn get(&self, s: &str) -> Option<StaticArcStr> {
+        read(self.shard(s)).strings.get(s).cloned()
+    }
+
+    #[inline]
+    pub fn contains(&self, s: &str) -> bool {
+        read(self.shard(s)).strings.contains(s)
+    }
+
+    /// The number of interned strings.
+    pub fn len(&self) -> usize {
+        self.shards
+            .iter()
+            .map(|shard| read(shard).strings.len())
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The memory used by the interner and its strings.
+    pub fn stats(&self) -> InternerStats {
+        let mut stats = InternerStats {
+            allocated_bytes: mem::size_of_val(&*self.shards),
+            ..InternerStats::default()
+        };
+
+        for shard in self.shards.iter() {
+            let shard = read(shard);
+            let strings: usize = shard
+                .strings
+                .iter()
+                .filter(|s| matches!(s, StaticArcStr::Arc(_)))
+                .map(|s| ArcBuf::<u8>::layout(s.len()).size())
+                .sum();
+
+            stats.len += shard.strings.len();
+            stats.string_bytes += shard.string_bytes;
+            stats.allocated_bytes +=
+                strings + shard.strings.capacity() * mem::size_of::<StaticArcStr>();
+        }
+
+        stats
+    }
+}
+
+#[cfg(feature = "serde")]
+impl ArcStrInterner {
+    /// A `DeserializeSeed` deserializing strings through this interner.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use collect_into_rc_slice::*;
+    /// use serde::de::DeserializeSeed;
+    ///
+    /// let interner = ArcStrInterner::new();
+    /// let tags: Vec<StaticArcStr> = ["\"rust\"", "\"serde\"", "\"rust\""]
+    ///     .into_iter()
+    ///     .map(|json| {
+    ///         let mut de = serde_json::Deserializer::from_str(json);
+    ///         interner.seed().deserialize(&mut de).unwrap()
+    ///     })
+    ///     .collect();
+    ///
+    /// assert!(StaticArcStr::ptr_eq(&tags[0], &tags[2]));
+    /// assert_eq!(interner.len(), 2);
+    /// ```
+    #[inline]
+    pub fn seed(&self) -> InternSeed<'_> {
+        InternSeed(self)
+    }
+}
+
+/// A `DeserializeSeed` that interns the deserialized string in an [`ArcStrInterner`], so repeated
+/// keys and tags of a document share the same `Arc<str>`. It is returned by
+/// [`ArcStrInterner::seed`].
+#[cfg(feature = "serde")]
+#[derive(Clone, Copy, Debug)]
+pub struct InternSeed<'a>(&'a ArcStrInterner);
+
+#[cfg(feature = "serde")]
+impl<'de> serde::de::DeserializeSeed<'de> for InternSeed<'_> {
+    type Value = StaticArcStr;
+
+    fn deserialize<D: serde::Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::de::Visitor<'_> for InternSeed<'_> {
+    type Value = StaticArcStr;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a string")
+    }
+
+    #[inline]
+    fn visit_str<E: serde::de::Error>(self, s: &str) -> Result<StaticArcStr, E> {
+        Ok(self.0.get_or_intern(s))
+    }
+
+    fn visit_bytes<E: serde::de::Error>(self, bytes: &[u8]) -> Result<StaticArcStr, E> {
+        match std::str::from_utf8(bytes) {
+            Ok(s) => self.visit_str(s),
+            Err(_) => Err(E::invalid_value(serde::de::Unexpected::Bytes(bytes), &self)),
+        }
+    }
+}
+
+impl Default for ArcStrInterner {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for ArcStrInterner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ArcStrInterner")
+            .field("shards", &self.shards.len())
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+// the shards are never left in an inconsistent state, so a panic while holding a lock is harmless
+#[inline]
+fn read(shard: &RwLock<Shard>) -> RwLockReadGuard<'_, Shard> {
+    shard.read().unwrap_or_else(PoisonError::into_inner)
+}
+
+#[inline]
+fn write(shard: &RwLock<Shard>) -> RwLockWriteGuard<'_, Shard> {
+    shard.write().unwrap_or_else(PoisonError::into_inner)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn test_arc_str_interner_threads() {
+        let interner = ArcStrInterner::with_shards(3);
+        let words = ["alpha", "beta", "gamma", "delta", "alpha"];
+
+        let interned: Vec<Vec<StaticArcStr>> = thread::scope(|s| {
+            let handles: Vec<_> = (0..4)
+                .map(|_| s.spawn(|| words.map(|w| interner.get_or_intern(w)).to_vec()))
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+
+        assert_eq!(interner.len(), 4);
+        assert_eq!(interner.shards.len(), 4);
+        for strings in &interned {
+            assert!(StaticArcStr::ptr_eq(&strings[0], &interned[0][0]));
+            assert!(StaticArcStr::ptr_eq(&strings[0], &strings[4]));
+        }
+    }
+
+    #[test]
+    fn test_arc_str_interner_purge_dead() {
+        let mut interner = ArcStrInterner::with_shards(1);
+        interner.set_auto_purge(Some(2));
+        let kept = interner.get_or_intern("kept");
+        interner.get_or_intern("a");
+        interner.get_or_intern("b");
+
+        // the auto purge ran while "a" was still being returned
+        assert_eq!(interner.len(), 3);
+        assert_eq!(interner.purge_dead(), 2);
+        assert_eq!(interner.stats().string_bytes, 4);
+        assert!(StaticArcStr::ptr_eq(&kept, &interner.get("kept").unwrap()));
+    }
+
+    #[test]
+    fn test_arc_str_interner_with_static() {
+        static HEADER: &str = "content-type";
+        let interner = ArcStrInterner::with_static(&[HEADER, "host", "host"]);
+
+        assert_eq!(interner.len(), 2);
+        assert_eq!(interner.stats().string_bytes, 16);
+        assert_eq!(interner.purge_dead(), 0);
+
+        let s = interner.get_or_intern(&String::from("content-type"));
+        assert!(matches!(s, StaticArcStr::Static(s) if std::ptr::eq(s, HEADER)));
+    }
+
+    #[test]
+    fn test_arc_str_interner_stats() {
+        let interner = ArcStrInterner::new();
+        interner.get_or_intern("hello");
+        interner.get_or_intern("hello");
+
+        let stats = interner.stats();
+        assert_eq!(stats.len, 1);
+        assert_eq!(stats.string_bytes, 5);
+        assert!(interner.contains("hello"));
+        assert!(interner.get("world").is_none());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_arc_str_interner_seed() {
+        use serde::de::{DeserializeSeed, SeqAccess, Visitor};
+
+        /// Deserializes a sequence of strings through the interner.
+        struct Tags<'a>(&'a ArcStrInterner);
+
+        impl<'de> DeserializeSeed<'de> for Tags<'_> {
+            type Value = Vec<StaticArcStr>;
+
+            fn deserialize<D: serde::Deserializer<'de>>(
+                self,
+                de: D,
+            ) -> Result<Self::Value, D::Error> {
+                de.deserialize_seq(self)
+            }
+        }
+
+        impl<'de> Visitor<'de> for Tags<'_> {
+            type Value = Vec<StaticArcStr>;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a sequence of tags")
+            }
+
+            fn visit_seq<S: SeqAccess<'de>>(self, mut seq: S) -> Result<Self::Value, S::Error> {
+                let mut tags = Vec::new();
+                while let Some(tag) = seq.next_element_seed(self.0.seed())? {
+                    tags.push(tag);
+                }
+                Ok(tags)
+            }
+        }
+
+        let interner = ArcStrInterner::with_static(&["static"]);
+        let mut de = serde_json::Deserializer::from_str(r#"["a", "static", "b", "a", "static"]"#);
+        let tags = Tags(&interner).deserialize(&mut de).unwrap();
+
+        assert_eq!(interner.len(), 3);
+        assert!(StaticArcStr::ptr_eq(&tags[0], &tags[3]));
+        assert!(matches!(tags[4], StaticArcStr::Static("static")));
+
+        let mut de = serde_json::Deserializer::from_str("42");
+        assert!(interner.seed().deserialize(&mut de).is_err());
+    }
+}
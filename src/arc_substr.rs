@@ -0,0 +1,397 @@
+#![cfg(target_has_atomic = "ptr")]
+use crate::{SplitIntoSubstrs, SplitPattern};
+use std::{
+    borrow::Borrow,
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+    ops::{Bound, Deref, Index, Range, RangeBounds},
+    slice::SliceIndex,
+    sync::Arc,
+};
+
+/// A thread-safe substring of an `Arc<str>`, sharing the allocation of the whole string.
+///
+/// This is the `Send + Sync` counterpart of [`RcSubstr`](crate::RcSubstr), so the pieces of one
+/// collected input can be handed out to other threads or async tasks without copying.
+///
+/// # Examples
+/// ```rust
+/// use collect_into_rc_slice::*;
+///
+/// let source = ArcSubstr::from("let x = 42;".chars().collect_into_arc_str());
+/// let ident = source.slice(4..5);
+/// let (value, rest) = source.slice(8..).split_at(2);
+///
+/// assert_eq!(&*ident, "x");
+/// assert_eq!(&*value, "42");
+/// assert_eq!(&*rest, ";");
+/// assert_eq!(&**value.parent(), "let x = 42;");
+/// ```
+#[derive(Clone)]
+pub struct ArcSubstr {
+    parent: Arc<str>,
+    start: usize,
+    end: usize,
+}
+
+impl ArcSubstr {
+    /// A substring covering all of `parent`.
+    #[inline]
+    pub fn new(parent: Arc<str>) -> Self {
+        let end = parent.len();
+        Self {
+            parent,
+            start: 0,
+            end,
+        }
+    }
+
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        // SAFETY: The range is always in bounds and on `char` boundaries of the parent.
+        unsafe { self.parent.get_unchecked(self.start..self.end) }
+    }
+
+    /// The whole string this is a substring of.
+    #[inline]
+    pub fn parent(&self) -> &Arc<str> {
+        &self.parent
+    }
+
+    /// The byte range of this substring within [`parent`](ArcSubstr::parent).
+    #[inline]
+    pub fn range(&self) -> Range<usize> {
+        self.start..self.end
+    }
+
+    /// Returns a substring of this substring, sharing the same allocation.
+    ///
+    /// # Panics
+    /// Panics if the range is out of bounds or doesn't fall on `char` boundaries, just like
+    /// indexing a `str`.
+    pub fn slice(&self, range: impl RangeBounds<usize>) -> Self {
+        let bounds: (Bound<usize>, Bound<usize>) =
+            (range.start_bound().cloned(), range.end_bound().cloned());
+        let sub = &self.as_str()[bounds];
+        self.slice_ref(sub)
+    }
+
+    /// Returns the substring for `sub`, which has to be a part of this substring, e.g. one
+    /// returned by `str::split` or `str::trim`.
+    ///
+    /// # Panics
+    /// Panics if `sub` is not contained in this substring, or if it is an empty string in the
+    /// middle of one of its `char`s.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use collect_into_rc_slice::*;
+    ///
+    /// let line = ArcSubstr::from("  key = value ".chars().collect_into_arc_str());
+    /// let key = line.slice_ref(line.split('=').next().unwrap().trim());
+    ///
+    /// assert_eq!(&*key, "key");
+    /// assert_eq!(key.range(), 2..5);
+    /// ```
+    pub fn slice_ref(&self, sub: &str) -> Self {
+        let base = self.as_str().as_ptr() as usize;
+        let offset = (sub.as_ptr() as usize).wrapping_sub(base);
+
+        assert!(
+            offset <= self.len() && sub.len() <= self.len() - offset,
+            "the string is not a part of this substring"
+        );
+        // only an empty string can start in the middle of a `char` of the parent
+        assert!(
+            self.as_str().is_char_boundary(offset),
+            "the string doesn't start on a `char` boundary of this substring"
+        );
+
+        Self {
+            parent: self.parent.clone(),
+            start: self.start + offset,
+            end: self.start + offset + sub.len(),
+        }
+    }
+
+    /// Splits the substring in two at the byte index `mid`.
+    ///
+    /// # Panics
+    /// Panics if `mid` is out of bounds or not on a `char` boundary, just like `str::split_at`.
+    pub fn split_at(&self, mid: usize) -> (Self, Self) {
+        let (head, tail) = self.as_str().split_at(mid);
+        (self.slice_ref(head), self.slice_ref(tail))
+    }
+
+    /// Splits the substring by `pat`, like `str::split`, returning substrings that share the same
+    /// allocation and can outlive `self`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use collect_into_rc_slice::*;
+    ///
+    /// let csv = ArcSubstr::from("a,b,,c".chars().collect_into_arc_str());
+    /// let fields: Vec<ArcSubstr> = csv.split(',').collect();
+    ///
+    /// assert_eq!(fields, ["a", "b", "", "c"]);
+    /// ```
+    pub fn split<'a, P>(&'a self, pat: P) -> impl Iterator<Item = ArcSubstr> + 'a
+    where
+        P: SplitPattern + 'a,
+    {
+        pat.split(self.as_str()).map(|sub| self.slice_ref(sub))
+    }
+
+    /// Splits the substring by `pat` into at most `n` substrings, like `str::splitn`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use collect_into_rc_slice::*;
+    ///
+    /// let header = ArcSubstr::from("Host: example.com:80".chars().collect_into_arc_str());
+    /// let parts: Vec<ArcSubstr> = header.splitn(2, ':').collect();
+    ///
+    /// assert_eq!(parts, ["Host", " example.com:80"]);
+    /// ```
+    pub fn splitn<'a, P>(&'a self, n: usize, pat: P) -> impl Iterator<Item = ArcSubstr> + 'a
+    where
+        P: SplitPattern + 'a,
+    {
+        pat.splitn(self.as_str(), n).map(|sub| self.slice_ref(sub))
+    }
+
+    /// Iterates over the lines of the substring, like `str::lines`, as substrings sharing the
+    /// same allocation. Lines end with `\n` or `\r\n`, and the line endings are not included.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use collect_into_rc_slice::*;
+    ///
+    /// let text = ArcSubstr::from("first\r\nsecond\nthird\n".chars().collect_into_arc_str());
+    /// let lines: Vec<ArcSubstr> = text.lines().collect();
+    ///
+    /// assert_eq!(lines, ["first", "second", "third"]);
+    /// ```
+    pub fn lines(&self) -> impl Iterator<Item = ArcSubstr> + '_ {
+        self.as_str().lines().map(|line| self.slice_ref(line))
+    }
+}
+
+impl SplitIntoSubstrs for Arc<str> {
+    type Substr = ArcSubstr;
+
+    fn split_substrs<'a, P>(&'a self, pat: P) -> impl Iterator<Item = ArcSubstr> + 'a
+    where
+        P: SplitPattern + 'a,
+    {
+        let whole = ArcSubstr::new(self.clone());
+        pat.split(self).map(move |sub| whole.slice_ref(sub))
+    }
+
+    fn splitn_substrs<'a, P>(&'a self, n: usize, pat: P) -> impl Iterator<Item = ArcSubstr> + 'a
+    where
+        P: SplitPattern + 'a,
+    {
+        let whole = ArcSubstr::new(self.clone());
+        pat.splitn(self, n).map(move |sub| whole.slice_ref(sub))
+    }
+
+    fn lines_substrs(&self) -> impl Iterator<Item = ArcSubstr> + '_ {
+        let whole = ArcSubstr::new(self.clone());
+        self.lines().map(move |line| whole.slice_ref(line))
+    }
+}
+
+impl From<Arc<str>> for ArcSubstr {
+    #[inline]
+    fn from(parent: Arc<str>) -> Self {
+        Self::new(parent)
+    }
+}
+
+impl Deref for ArcSubstr {
+    type Target = str;
+
+    #[inline]
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl AsRef<str> for ArcSubstr {
+    #[inline]
+    fn as_ref(&self) -> &str {
+        self
+    }
+}
+
+impl AsRef<[u8]> for ArcSubstr {
+    #[inline]
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl<I: SliceIndex<str>> Index<I> for ArcSubstr {
+    type Output = I::Output;
+
+    #[inline]
+    fn index(&self, index: I) -> &I::Output {
+        &(**self)[index]
+    }
+}
+
+/// An empty substring.
+impl Default for ArcSubstr {
+    #[inline]
+    fn default() -> Self {
+        Self::new(Arc::default())
+    }
+}
+
+impl Borrow<str> for ArcSubstr {
+    #[inline]
+    fn borrow(&self) -> &str {
+        self
+    }
+}
+
+impl fmt::Debug for ArcSubstr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl fmt::Display for ArcSubstr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self.as_str(), f)
+    }
+}
+
+impl PartialEq for ArcSubstr {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for ArcSubstr {}
+
+impl PartialEq<str> for ArcSubstr {
+    #[inline]
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for ArcSubstr {
+    #[inline]
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl PartialOrd for ArcSubstr {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ArcSubstr {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+impl Hash for ArcSubstr {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state);
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for ArcSubstr {
+    #[inline]
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CollectIntoArcStr;
+
+    #[test]
+    fn test_arc_substr() {
+        let s = ArcSubstr::from("héllo wörld".chars().collect_into_arc_str());
+        let (hello, world) = s.split_at(6);
+
+        assert_eq!(hello, "héllo");
+        assert_eq!(world.slice(1..), "wörld");
+        assert_eq!(world.slice(1..).range(), 7..13);
+        assert!(Arc::ptr_eq(hello.parent(), world.parent()));
+        assert_eq!(Arc::strong_count(s.parent()), 3);
+    }
+
+    #[test]
+    fn test_arc_substr_threads() {
+        let s = ArcSubstr::from("a,b,c".chars().collect_into_arc_str());
+        let handles: Vec<_> = s
+            .split(',')
+            .map(|part| s.slice_ref(&part))
+            .map(|part| std::thread::spawn(move || part.to_uppercase()))
+            .collect();
+        let parts: Vec<_> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+
+        assert_eq!(parts, ["A", "B", "C"]);
+        assert_eq!(Arc::strong_count(s.parent()), 1);
+    }
+
+    #[test]
+    fn test_arc_substr_split() {
+        let parent = "one two  three".chars().collect_into_arc_str();
+        let words: Vec<_> = parent.split_substrs(char::is_whitespace).collect();
+        let rest = ArcSubstr::from(parent.clone())
+            .splitn(2, " ")
+            .nth(1)
+            .unwrap();
+
+        assert_eq!(words, ["one", "two", "", "three"]);
+        assert_eq!(words[3].range(), 9..14);
+        assert_eq!(rest, "two  three");
+        assert!(Arc::ptr_eq(rest.parent(), &parent));
+    }
+
+    #[test]
+    fn test_arc_substr_lines() {
+        let parent = "a\n\r\nb\r\nc".chars().collect_into_arc_str();
+        let lines: Vec<_> = parent.lines_substrs().collect();
+
+        assert_eq!(lines, ["a", "", "b", "c"]);
+        assert_eq!(lines[2].range(), 4..5);
+        assert_eq!(
+            ArcSubstr::from(parent).slice(2..).lines().nth(1).unwrap(),
+            "b"
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_arc_substr_slice_ref_outside() {
+        let s = ArcSubstr::from("hello".chars().collect_into_arc_str());
+        s.slice(1..).slice_ref(&s[..1]);
+    }
+
+    #[test]
+    #[should_panic(expected = "`char` boundary")]
+    // indexing the `str` itself would panic instead of building the empty string
+    #[allow(clippy::string_from_utf8_as_bytes)]
+    fn test_arc_substr_slice_ref_mid_char() {
+        let s = ArcSubstr::from("é".chars().collect_into_arc_str());
+        s.slice_ref(std::str::from_utf8(&s.as_bytes()[1..1]).unwrap());
+    }
+}
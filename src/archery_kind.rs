@@ -0,0 +1,128 @@
+#![cfg(feature = "archery")]
+use crate::{
+    CollectIntoArcSlice, CollectIntoArcStr, CollectIntoRcSlice, CollectIntoRcStr, IntoChar,
+};
+use archery::{ArcK, RcK, SharedPointerKind};
+use std::{rc::Rc, sync::Arc};
+
+/// An `archery` pointer kind that can be collected into.
+///
+/// `archery::SharedPointer` can only hold sized values, so instead of a `SharedPointer<[T], K>`
+/// each kind maps to the slice and string pointers of the smart pointer it is built on.
+///
+/// # Examples
+/// ```rust
+/// use archery::{ArcK, RcK};
+/// use collect_into_rc_slice::*;
+///
+/// fn squares<K: SharedSliceKind>(n: u32) -> K::Slice<u32> {
+///     (1..=n).map(|i| i * i).collect_into_shared_slice::<K>()
+/// }
+///
+/// assert_eq!(&*squares::<RcK>(3), &[1, 4, 9]);
+/// assert_eq!(&*squares::<ArcK>(3), &[1, 4, 9]);
+/// ```
+pub trait SharedSliceKind: SharedPointerKind {
+    /// The pointer to a slice of `T` for this kind, e.g. `Rc<[T]>` for `RcK`.
+    type Slice<T>: std::ops::Deref<Target = [T]> + Clone;
+    /// The pointer to a string for this kind, e.g. `Rc<str>` for `RcK`.
+    type Str: std::ops::Deref<Target = str> + Clone;
+
+    /// Collects the iterator into the slice pointer of this kind.
+    fn collect_slice<T, I: Iterator<Item = T>>(iter: I) -> Self::Slice<T>;
+
+    /// Collects the iterator into the string pointer of this kind.
+    fn collect_str<I: Iterator<Item = char>>(iter: I) -> Self::Str;
+}
+
+impl SharedSliceKind for RcK {
+    type Slice<T> = Rc<[T]>;
+    type Str = Rc<str>;
+
+    fn collect_slice<T, I: Iterator<Item = T>>(iter: I) -> Rc<[T]> {
+        iter.collect_into_rc_slice()
+    }
+
+    fn collect_str<I: Iterator<Item = char>>(iter: I) -> Rc<str> {
+        iter.collect_into_rc_str()
+    }
+}
+
+impl SharedSliceKind for ArcK {
+    type Slice<T> = Arc<[T]>;
+    type Str = Arc<str>;
+
+    fn collect_slice<T, I: Iterator<Item = T>>(iter: I) -> Arc<[T]> {
+        iter.collect_into_arc_slice()
+    }
+
+    fn collect_str<I: Iterator<Item = char>>(iter: I) -> Arc<str> {
+        iter.collect_into_arc_str()
+    }
+}
+
+#[cfg(feature = "triomphe")]
+impl SharedSliceKind for archery::ArcTK {
+    type Slice<T> = triomphe::Arc<[T]>;
+    type Str = triomphe::Arc<str>;
+
+    fn collect_slice<T, I: Iterator<Item = T>>(iter: I) -> triomphe::Arc<[T]> {
+        crate::CollectIntoTriompheSlice::collect_into_triomphe_slice(iter)
+    }
+
+    fn collect_str<I: Iterator<Item = char>>(iter: I) -> triomphe::Arc<str> {
+        crate::CollectIntoTriompheStr::collect_into_triomphe_str(iter)
+    }
+}
+
+pub trait CollectIntoSharedSlice<T> {
+    /// Collects the iterator into the slice pointer of the `archery` pointer kind `K`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use archery::RcK;
+    /// use collect_into_rc_slice::*;
+    /// use std::rc::Rc;
+    ///
+    /// let arr = [1, 2, 3, 4, 5];
+    /// let rc: Rc<[i32]> = arr.into_iter().collect_into_shared_slice::<RcK>();
+    ///
+    /// assert_eq!(&*rc, &[1, 2, 3, 4, 5]);
+    /// ```
+    fn collect_into_shared_slice<K: SharedSliceKind>(self) -> K::Slice<T>;
+}
+
+impl<I, T> CollectIntoSharedSlice<T> for I
+where
+    I: IntoIterator<Item = T>,
+{
+    fn collect_into_shared_slice<K: SharedSliceKind>(self) -> K::Slice<T> {
+        K::collect_slice(self.into_iter())
+    }
+}
+
+pub trait CollectIntoSharedStr {
+    /// Collects the iterator into the string pointer of the `archery` pointer kind `K`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use archery::ArcK;
+    /// use collect_into_rc_slice::*;
+    /// use std::sync::Arc;
+    ///
+    /// let s: Arc<str> = "Hello, world!".chars().collect_into_shared_str::<ArcK>();
+    ///
+    /// assert!(s.as_ref() == "Hello, world!");
+    /// ```
+    fn collect_into_shared_str<K: SharedSliceKind>(self) -> K::Str;
+}
+
+impl<T> CollectIntoSharedStr for T
+where
+    T: IntoIterator,
+    T::Item: IntoChar,
+{
+    fn collect_into_shared_str<K: SharedSliceKind>(self) -> K::Str {
+        K::collect_str(self.into_iter().map(IntoChar::into_char))
+    }
+}
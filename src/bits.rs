@@ -0,0 +1,229 @@
+use crate::rc::{into_rc, RcBuf};
+use std::{fmt, rc::Rc};
+
+/// A packed bitmap sharing its bytes through an `Rc<[u8]>`, e.g. a visibility mask or the bits of a
+/// bloom filter.
+///
+/// The bits are laid out the way [`collect_bits_into_rc_slice`] packs them: bit `i` is bit `i % 8`
+/// of byte `i / 8`, counting from the least significant bit. Cloning it only clones the `Rc`.
+///
+/// [`collect_bits_into_rc_slice`]: CollectBitsIntoRcSlice::collect_bits_into_rc_slice
+///
+/// # Examples
+/// ```rust
+/// use collect_into_rc_slice::*;
+///
+/// let visible: RcBitSlice = (0..10).map(|i| i % 3 == 0).collect();
+///
+/// assert_eq!(visible.get(3), Some(true));
+/// assert_eq!(visible.count_ones(), 4);
+/// // 0 and 3 are visible before 5, and the third visible item is at 6
+/// assert_eq!(visible.rank(5), 2);
+/// assert_eq!(visible.select(2), Some(6));
+/// ```
+#[derive(Clone)]
+pub struct RcBitSlice {
+    bytes: Rc<[u8]>,
+    len: usize,
+}
+
+impl RcBitSlice {
+    /// Wraps the first `len` bits of `bytes`.
+    ///
+    /// # Panics
+    /// Panics if `bytes` has fewer than `len` bits.
+    pub fn new(bytes: Rc<[u8]>, len: usize) -> Self {
+        assert!(
+            len.div_ceil(8) <= bytes.len(),
+            "the bitmap is shorter than {len} bits"
+        );
+        Self { bytes, len }
+    }
+
+    /// The number of bits.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The bytes the bits are packed into.
+    #[inline]
+    pub fn as_bytes(&self) -> &Rc<[u8]> {
+        &self.bytes
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> (Rc<[u8]>, usize) {
+        (self.bytes, self.len)
+    }
+
+    #[inline]
+    pub fn get(&self, i: usize) -> Option<bool> {
+        (i < self.len).then(|| self.bytes[i / 8] >> (i % 8) & 1 == 1)
+    }
+
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = bool> + ExactSizeIterator + '_ {
+        (0..self.len).map(|i| self.bytes[i / 8] >> (i % 8) & 1 == 1)
+    }
+
+    /// The number of bits that are set.
+    #[inline]
+    pub fn count_ones(&self) -> usize {
+        self.rank(self.len)
+    }
+
+    /// The number of bits that are set before bit `i`.
+    ///
+    /// # Panics
+    /// Panics if `i` is greater than the number of bits.
+    pub fn rank(&self, i: usize) -> usize {
+        assert!(i <= self.len, "bit {i} is out of bounds");
+
+        let full = self.bytes[..i / 8]
+            .iter()
+            .map(|byte| byte.count_ones() as usize)
+            .sum::<usize>();
+        let partial = match i % 8 {
+            0 => 0,
+            bits => (self.bytes[i / 8] & ((1 << bits) - 1)).count_ones() as usize,
+        };
+        full + partial
+    }
+
+    /// The index of the bit that is set with `n` set bits before it, if there is one.
+    pub fn select(&self, mut n: usize) -> Option<usize> {
+        for (i, &byte) in self.bytes[..self.len.div_ceil(8)].iter().enumerate() {
+            let ones = byte.count_ones() as usize;
+            if n >= ones {
+                n -= ones;
+                continue;
+            }
+
+            // skip the `n` lowest set bits of the byte
+            let mut byte = byte;
+            for _ in 0..n {
+                byte &= byte - 1;
+            }
+            let index = i * 8 + byte.trailing_zeros() as usize;
+            return (index < self.len).then_some(index);
+        }
+        None
+    }
+}
+
+impl FromIterator<bool> for RcBitSlice {
+    fn from_iter<I: IntoIterator<Item = bool>>(iter: I) -> Self {
+        let (bytes, len) = iter.collect_bits_into_rc_slice();
+        Self { bytes, len }
+    }
+}
+
+impl PartialEq for RcBitSlice {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl Eq for RcBitSlice {}
+
+impl fmt::Debug for RcBitSlice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.iter()
+            .try_for_each(|bit| f.write_str(if bit { "1" } else { "0" }))
+    }
+}
+
+pub trait CollectBitsIntoRcSlice {
+    /// Packs the booleans 8 to a byte into an `Rc<[u8]>`, returning it along with the number of
+    /// bits.
+    ///
+    /// Bit `i` is bit `i % 8` of byte `i / 8`, counting from the least significant bit, and the
+    /// unused bits of the last byte are zero.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use collect_into_rc_slice::*;
+    ///
+    /// let (bytes, len) = [true, false, true, true, false, false, false, false, true]
+    ///     .collect_bits_into_rc_slice();
+    ///
+    /// assert_eq!(len, 9);
+    /// assert_eq!(&*bytes, &[0b1101, 0b1]);
+    /// ```
+    fn collect_bits_into_rc_slice(self) -> (Rc<[u8]>, usize);
+}
+
+impl<I> CollectBitsIntoRcSlice for I
+where
+    I: IntoIterator<Item = bool>,
+{
+    fn collect_bits_into_rc_slice(self) -> (Rc<[u8]>, usize) {
+        let iter = self.into_iter();
+        let (lower_bound, upper_bound) = iter.size_hint();
+        let mut buf = RcBuf::with_capacity(upper_bound.unwrap_or(lower_bound).div_ceil(8));
+
+        let mut len = 0;
+        let mut byte = 0;
+        for bit in iter {
+            byte |= (bit as u8) << (len % 8);
+            len += 1;
+            if len % 8 == 0 {
+                buf.push(byte);
+                byte = 0;
+            }
+        }
+        if len % 8 != 0 {
+            buf.push(byte);
+        }
+
+        (into_rc(buf), len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collect_bits_into_rc_slice_unknown_size() {
+        let (bytes, len) = (0..20)
+            .filter(|i| i % 2 == 0)
+            .map(|i| i % 4 == 0)
+            .collect_bits_into_rc_slice();
+
+        assert_eq!(len, 10);
+        assert_eq!(&*bytes, &[0b0101_0101, 0b01]);
+
+        let (bytes, len) = std::iter::empty().collect_bits_into_rc_slice();
+        assert_eq!((bytes.len(), len), (0, 0));
+    }
+
+    #[test]
+    fn test_rc_bit_slice_ignores_trailing_bits() {
+        // the bits past the length are set, but don't count
+        let bits = RcBitSlice::new(Rc::from([0b1111_0110]), 4);
+
+        assert_eq!(bits.get(4), None);
+        assert_eq!(bits.count_ones(), 2);
+        assert_eq!(bits.select(1), Some(2));
+        assert_eq!(bits.select(2), None);
+        assert_eq!(format!("{bits:?}"), "0110");
+        assert_eq!(bits, [false, true, true, false].into_iter().collect());
+    }
+
+    #[test]
+    fn test_rc_bit_slice_rank_select() {
+        let bits: RcBitSlice = (0..100).map(|i| i % 7 == 0).collect();
+
+        for (n, i) in (0..100).step_by(7).enumerate() {
+            assert_eq!(bits.rank(i), n);
+            assert_eq!(bits.select(n), Some(i));
+        }
+        assert_eq!(bits.rank(100), bits.iter().filter(|&bit| bit).count());
+    }
+}
@@ -0,0 +1,91 @@
+#![cfg(target_has_atomic = "ptr")]
+use crate::arc::{into_arc, ArcBuf};
+use std::sync::{mpsc::Receiver, Arc};
+
+/// Takes all of the messages currently queued in the channel into an `Arc<[T]>`, without waiting
+/// for more.
+///
+/// It is meant for processing a mailbox in batches, so the batch can be shared with other threads
+/// without being copied.
+///
+/// # Examples
+/// ```rust
+/// use collect_into_rc_slice::*;
+/// use std::sync::mpsc;
+///
+/// let (sender, receiver) = mpsc::channel();
+/// for i in 0..3 {
+///     sender.send(i).unwrap();
+/// }
+///
+/// assert_eq!(&*drain_channel_into_arc_slice(&receiver), &[0, 1, 2]);
+/// assert!(drain_channel_into_arc_slice(&receiver).is_empty());
+/// ```
+pub fn drain_channel_into_arc_slice<T>(receiver: &Receiver<T>) -> Arc<[T]> {
+    into_arc(ArcBuf::from_iter(receiver.try_iter()))
+}
+
+/// Takes all of the messages currently queued in a `crossbeam_channel` channel into an
+/// `Arc<[T]>`, without waiting for more.
+///
+/// The allocation is sized with the number of queued messages, so it only grows if more messages
+/// are sent while the channel is drained.
+///
+/// # Examples
+/// ```rust
+/// use collect_into_rc_slice::*;
+///
+/// let (sender, receiver) = crossbeam_channel::unbounded();
+/// sender.send("a").unwrap();
+/// sender.send("b").unwrap();
+///
+/// assert_eq!(&*drain_crossbeam_channel_into_arc_slice(&receiver), &["a", "b"]);
+/// ```
+#[cfg(feature = "crossbeam-channel")]
+pub fn drain_crossbeam_channel_into_arc_slice<T>(
+    receiver: &crossbeam_channel::Receiver<T>,
+) -> Arc<[T]> {
+    let mut buf = ArcBuf::with_capacity(receiver.len());
+    for message in receiver.try_iter() {
+        buf.push(message);
+    }
+    into_arc(buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{sync::mpsc, thread};
+
+    #[test]
+    fn test_drain_channel_into_arc_slice_batches() {
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            for i in 0..10 {
+                sender.send(i.to_string()).unwrap();
+            }
+        })
+        .join()
+        .unwrap();
+
+        let batch = drain_channel_into_arc_slice(&receiver);
+        assert_eq!(batch.len(), 10);
+        assert_eq!(batch[9], "9");
+        assert!(receiver.recv().is_err());
+    }
+
+    #[cfg(feature = "crossbeam-channel")]
+    #[test]
+    fn test_drain_crossbeam_channel_exact_capacity() {
+        let (sender, receiver) = crossbeam_channel::bounded(4);
+        for i in 0..4 {
+            sender.send(i).unwrap();
+        }
+
+        assert_eq!(
+            &*drain_crossbeam_channel_into_arc_slice(&receiver),
+            &[0, 1, 2, 3]
+        );
+        assert!(receiver.is_empty());
+    }
+}
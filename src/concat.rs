@@ -0,0 +1,82 @@
+#[cfg(target_has_atomic = "ptr")]
+use crate::arc::into_arc;
+use crate::{
+    raw::{capacity_overflow, Prefix, RawBuf},
+    rc::into_rc,
+};
+use std::rc::Rc;
+#[cfg(target_has_atomic = "ptr")]
+use std::sync::Arc;
+
+/// Concatenates `slices` into a single `Rc<[T]>`, e.g. to merge the shards of earlier
+/// collections.
+///
+/// The allocation is made once with the total length of the slices, and each slice is then copied
+/// into it in one go.
+///
+/// # Examples
+/// ```rust
+/// use collect_into_rc_slice::*;
+///
+/// let shards = [(1..=2).collect_into_rc_slice(), (3..=5).collect_into_rc_slice()];
+/// let merged = concat_rc_slices(&shards);
+///
+/// assert_eq!(&*merged, &[1, 2, 3, 4, 5]);
+/// ```
+pub fn concat_rc_slices<T: Copy, S: AsRef<[T]>>(slices: &[S]) -> Rc<[T]> {
+    into_rc(concat(slices))
+}
+
+/// Concatenates `slices` into a single `Arc<[T]>`, like [`concat_rc_slices`].
+///
+/// # Examples
+/// ```rust
+/// use collect_into_rc_slice::*;
+///
+/// let shards = [(1..=2).collect_into_arc_slice(), (3..=5).collect_into_arc_slice()];
+/// let merged = concat_arc_slices(&shards);
+///
+/// assert_eq!(&*merged, &[1, 2, 3, 4, 5]);
+/// ```
+#[cfg(target_has_atomic = "ptr")]
+pub fn concat_arc_slices<T: Copy, S: AsRef<[T]>>(slices: &[S]) -> Arc<[T]> {
+    into_arc(concat(slices))
+}
+
+fn concat<P: Prefix, T: Copy, S: AsRef<[T]>>(slices: &[S]) -> RawBuf<P, T> {
+    let len = slices
+        .iter()
+        .map(|slice| slice.as_ref().len())
+        .try_fold(0usize, usize::checked_add)
+        .unwrap_or_else(|| capacity_overflow());
+
+    let mut buf = RawBuf::with_capacity(len);
+    for slice in slices {
+        buf.extend_from_slice(slice.as_ref());
+    }
+    buf
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_concat_rc_slices_exact_capacity() {
+        let shards: [Rc<[char]>; 3] = [Rc::from(['a']), Rc::from([]), Rc::from(['b', 'c'])];
+        let merged: crate::rc::RcBuf<char> = concat(&shards);
+
+        assert_eq!(merged.capacity(), 3);
+        assert_eq!(&*into_rc(merged), ['a', 'b', 'c']);
+        assert!(concat_rc_slices::<u8, Vec<u8>>(&[]).is_empty());
+    }
+
+    #[cfg(target_has_atomic = "ptr")]
+    #[test]
+    fn test_concat_arc_slices_mixed_sources() {
+        let arc: Arc<[u8]> = Arc::from(&b"ab"[..]);
+        let merged = concat_arc_slices(&[&*arc, b"", b"cd"]);
+
+        assert_eq!(&*merged, b"abcd");
+    }
+}
@@ -0,0 +1,246 @@
+#[cfg(target_has_atomic = "ptr")]
+use crate::arc::ArcInner;
+use crate::{
+    raw::{Prefix, RawBuf},
+    rc::RcBox,
+};
+#[cfg(target_has_atomic = "ptr")]
+use std::sync::{atomic::AtomicUsize, Arc};
+use std::{alloc::Layout, marker::PhantomData, ptr, rc::Rc};
+
+/// A `#[repr(C)]` struct whose last field is a slice or a `str`, which can be built in a single
+/// `Rc` or `Arc` allocation from its sized fields and an iterator for the tail.
+///
+/// This is meant to be implemented with `#[derive(RcDst)]`, which is available with the `derive`
+/// feature and also generates `new_rc` and `new_arc` constructors.
+///
+/// # Safety
+/// - `Self` must be a `#[repr(C)]` struct with the sized fields in `Fields`, in order, followed by
+///   a last field of type `Tail`.
+/// - `layout` must return the layout of `Self` when the tail has the layout `tail`, along with
+///   the offset of the tail.
+/// - `write_fields` must write the fields at their offsets from `ptr`.
+/// - `from_raw_parts` must return a pointer to `Self` at `ptr` with a tail of `len` items.
+pub unsafe trait RcDst {
+    /// The sized fields of the struct as a tuple.
+    type Fields;
+    /// The type of the last field, either `[T]` or `str`.
+    type Tail: DstTail + ?Sized;
+
+    fn layout(tail: Layout) -> (Layout, usize);
+
+    /// # Safety
+    /// `ptr` must be valid for writes of the whole struct and aligned for it.
+    unsafe fn write_fields(fields: Self::Fields, ptr: *mut u8);
+
+    fn from_raw_parts(ptr: *mut u8, len: usize) -> *mut Self;
+}
+
+/// The last field of an [`RcDst`] struct.
+pub trait DstTail: sealed::Sealed {
+    /// The type of the elements the tail is stored as.
+    type Item;
+}
+
+impl<T> DstTail for [T] {
+    type Item = T;
+}
+
+impl DstTail for str {
+    type Item = u8;
+}
+
+mod sealed {
+    pub trait Sealed {}
+
+    impl<T> Sealed for [T] {}
+    impl Sealed for str {}
+}
+
+type Item<D> = <<D as RcDst>::Tail as DstTail>::Item;
+
+/// A prefix `P` followed by the struct `D`, whose tail holds the elements.
+struct WithDst<P, D: ?Sized>(PhantomData<(P, *const D)>);
+
+impl<P: Prefix, D: RcDst + ?Sized> WithDst<P, D> {
+    /// The offset of the struct from the start of the allocation.
+    fn struct_offset() -> usize {
+        let (inner, _) = D::layout(Layout::new::<[Item<D>; 0]>());
+        P::layout(inner).1
+    }
+}
+
+// SAFETY: The struct is laid out after `P` as a whole, and `D::layout` describes the struct.
+unsafe impl<P: Prefix, D: RcDst + ?Sized> Prefix for WithDst<P, D> {
+    fn layout(data: Layout) -> (Layout, usize) {
+        let (inner, tail_offset) = D::layout(data);
+        let (layout, inner_offset) = P::layout(inner);
+        (layout, inner_offset + tail_offset)
+    }
+}
+
+type DstBuf<P, D> = RawBuf<WithDst<P, D>, Item<D>>;
+
+/// Writes the header and the fields of the struct, returning a pointer to the struct.
+///
+/// # Safety
+/// `P` must be the prefix of the smart pointer `header` belongs to.
+unsafe fn finish<P: Prefix, D: RcDst + ?Sized>(
+    buf: DstBuf<P, D>,
+    header: P,
+    fields: D::Fields,
+) -> *mut D {
+    let (alloc, data) = buf.into_raw_parts();
+    let ptr = alloc.as_ptr().add(WithDst::<P, D>::struct_offset());
+
+    ptr::write(alloc.as_ptr() as *mut P, header);
+    D::write_fields(fields, ptr);
+
+    D::from_raw_parts(ptr, data.len())
+}
+
+fn rc_box() -> RcBox<()> {
+    RcBox {
+        strong_count: 1,
+        weak_count: 1,
+        data: (),
+    }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+fn arc_inner() -> ArcInner<()> {
+    ArcInner {
+        strong: AtomicUsize::new(1),
+        weak: AtomicUsize::new(1),
+        data: (),
+    }
+}
+
+/// Builds an `Rc<D>` from its sized fields and the elements of its tail.
+pub fn new_rc<D, T, I>(fields: D::Fields, iter: I) -> Rc<D>
+where
+    D: RcDst<Tail = [T]> + ?Sized,
+    I: Iterator<Item = T>,
+{
+    // SAFETY: The allocation is laid out as an `RcBox<D>`.
+    unsafe { Rc::from_raw(finish(DstBuf::from_iter(iter), rc_box(), fields)) }
+}
+
+/// Builds an `Rc<D>` from its sized fields and the `char`s of its `str` tail.
+pub fn new_rc_from_chars<D, I>(fields: D::Fields, iter: I) -> Rc<D>
+where
+    D: RcDst<Tail = str> + ?Sized,
+    I: Iterator<Item = char>,
+{
+    // SAFETY: The allocation is laid out as an `RcBox<D>` and the tail is valid UTF-8.
+    unsafe { Rc::from_raw(finish(DstBuf::from_chars(iter), rc_box(), fields)) }
+}
+
+/// Builds an `Arc<D>` from its sized fields and the elements of its tail.
+#[cfg(target_has_atomic = "ptr")]
+pub fn new_arc<D, T, I>(fields: D::Fields, iter: I) -> Arc<D>
+where
+    D: RcDst<Tail = [T]> + ?Sized,
+    I: Iterator<Item = T>,
+{
+    // SAFETY: The allocation is laid out as an `ArcInner<D>`.
+    unsafe { Arc::from_raw(finish(DstBuf::from_iter(iter), arc_inner(), fields)) }
+}
+
+/// Builds an `Arc<D>` from its sized fields and the `char`s of its `str` tail.
+#[cfg(target_has_atomic = "ptr")]
+pub fn new_arc_from_chars<D, I>(fields: D::Fields, iter: I) -> Arc<D>
+where
+    D: RcDst<Tail = str> + ?Sized,
+    I: Iterator<Item = char>,
+{
+    // SAFETY: The allocation is laid out as an `ArcInner<D>` and the tail is valid UTF-8.
+    unsafe { Arc::from_raw(finish(DstBuf::from_chars(iter), arc_inner(), fields)) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[repr(C)]
+    struct Token {
+        kind: u8,
+        line: u32,
+        text: str,
+    }
+
+    // SAFETY: This is what `#[derive(RcDst)]` generates for `Token`.
+    unsafe impl RcDst for Token {
+        type Fields = (u8, u32);
+        type Tail = str;
+
+        fn layout(tail: Layout) -> (Layout, usize) {
+            let layout = Layout::new::<()>();
+            let (layout, _) = layout.extend(Layout::new::<u8>()).unwrap();
+            let (layout, _) = layout.extend(Layout::new::<u32>()).unwrap();
+            let (layout, offset) = layout.extend(tail).unwrap();
+            (layout.pad_to_align(), offset)
+        }
+
+        unsafe fn write_fields((kind, line): Self::Fields, ptr: *mut u8) {
+            let layout = Layout::new::<()>();
+            let (layout, offset) = layout.extend(Layout::new::<u8>()).unwrap();
+            ptr.add(offset).cast::<u8>().write(kind);
+            let (_, offset) = layout.extend(Layout::new::<u32>()).unwrap();
+            ptr.add(offset).cast::<u32>().write(line);
+        }
+
+        fn from_raw_parts(ptr: *mut u8, len: usize) -> *mut Self {
+            ptr::slice_from_raw_parts_mut(ptr, len) as *mut Self
+        }
+    }
+
+    #[test]
+    fn test_new_rc_dst() {
+        let token: Rc<Token> = new_rc_from_chars((3, 42), "ident".chars());
+
+        assert_eq!(token.kind, 3);
+        assert_eq!(token.line, 42);
+        assert_eq!(&token.text, "ident");
+        assert_eq!(Rc::strong_count(&token), 1);
+    }
+
+    #[repr(C)]
+    struct Row {
+        schema: u16,
+        cells: [String],
+    }
+
+    // SAFETY: This is what `#[derive(RcDst)]` generates for `Row`.
+    unsafe impl RcDst for Row {
+        type Fields = (u16,);
+        type Tail = [String];
+
+        fn layout(tail: Layout) -> (Layout, usize) {
+            let layout = Layout::new::<()>();
+            let (layout, _) = layout.extend(Layout::new::<u16>()).unwrap();
+            let (layout, offset) = layout.extend(tail).unwrap();
+            (layout.pad_to_align(), offset)
+        }
+
+        unsafe fn write_fields((schema,): Self::Fields, ptr: *mut u8) {
+            let layout = Layout::new::<()>();
+            let (_, offset) = layout.extend(Layout::new::<u16>()).unwrap();
+            ptr.add(offset).cast::<u16>().write(schema);
+        }
+
+        fn from_raw_parts(ptr: *mut u8, len: usize) -> *mut Self {
+            ptr::slice_from_raw_parts_mut(ptr as *mut String, len) as *mut Self
+        }
+    }
+
+    #[test]
+    fn test_new_arc_dst() {
+        let mut iter = ["a", "b", "c"].into_iter().map(String::from);
+        let row: Arc<Row> = new_arc((9,), std::iter::from_fn(move || iter.next()));
+
+        assert_eq!(row.schema, 9);
+        assert_eq!(&row.cells, &["a", "b", "c"]);
+        assert_eq!(Arc::strong_count(&row), 1);
+    }
+}
@@ -0,0 +1,284 @@
+#![cfg(target_has_atomic = "ptr")]
+use crate::arc::{into_arc, ArcBuf};
+use std::{
+    borrow::Borrow,
+    collections::hash_map::RandomState,
+    fmt,
+    hash::{BuildHasher, Hash},
+    sync::Arc,
+};
+
+/// A thread-safe immutable hash map whose buckets and entries are stored inline in a single
+/// `Arc<[Option<(K, V)>]>`.
+///
+/// The table is built once, with linear probing and a load factor of at most 7/8, so lookups are
+/// O(1) without any per-entry allocations, and cloning the map only bumps the reference count.
+/// Use it instead of a [`FrozenRcMap`](crate::FrozenRcMap) when the key set is large enough for
+/// binary searches to show up in profiles.
+///
+/// # Examples
+/// ```rust
+/// use collect_into_rc_slice::*;
+///
+/// let mime = [("html", "text/html"), ("png", "image/png"), ("json", "application/json")]
+///     .into_iter()
+///     .collect_into_frozen_arc_hash_map();
+///
+/// assert_eq!(mime.get("png"), Some(&"image/png"));
+/// assert!(!mime.contains_key("gif"));
+/// assert_eq!(mime.len(), 3);
+/// ```
+pub struct FrozenArcHashMap<K, V, S = RandomState> {
+    buckets: Arc<[Option<(K, V)>]>,
+    len: usize,
+    hasher: S,
+}
+
+impl<K, V, S> FrozenArcHashMap<K, V, S> {
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The number of buckets of the table, which is always a power of two larger than the length.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.buckets.len()
+    }
+
+    #[inline]
+    pub fn hasher(&self) -> &S {
+        &self.hasher
+    }
+
+    /// Iterates over the entries in an arbitrary order.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.buckets.iter().flatten().map(|(k, v)| (k, v))
+    }
+
+    #[inline]
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.iter().map(|(k, _)| k)
+    }
+
+    #[inline]
+    pub fn values(&self) -> impl Iterator<Item = &V> {
+        self.iter().map(|(_, v)| v)
+    }
+
+    /// Returns `true` if the two maps point to the same allocation, like `Arc::ptr_eq`.
+    #[inline]
+    pub fn ptr_eq(this: &Self, other: &Self) -> bool {
+        Arc::ptr_eq(&this.buckets, &other.buckets)
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher> FrozenArcHashMap<K, V, S> {
+    /// Builds the map from `iter`, hashing the keys with `hasher`. If a key occurs more than
+    /// once, the last value for it is kept.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use collect_into_rc_slice::*;
+    /// use std::hash::BuildHasherDefault;
+    /// use std::collections::hash_map::DefaultHasher;
+    ///
+    /// let map = FrozenArcHashMap::from_iter_with_hasher(
+    ///     [(1, 'a'), (2, 'b'), (1, 'c')],
+    ///     BuildHasherDefault::<DefaultHasher>::default(),
+    /// );
+    ///
+    /// assert_eq!(map.len(), 2);
+    /// assert_eq!(map.get(&1), Some(&'c'));
+    /// ```
+    pub fn from_iter_with_hasher<I: IntoIterator<Item = (K, V)>>(iter: I, hasher: S) -> Self {
+        let entries: Vec<_> = iter.into_iter().collect();
+
+        // keep at least one bucket empty so that probing for a missing key always terminates
+        let cap = (entries.len() + entries.len() / 7 + 1).next_power_of_two();
+        let mut buf = ArcBuf::with_capacity(cap);
+        for _ in 0..cap {
+            buf.push(None);
+        }
+
+        let buckets = buf.as_mut_slice();
+        let mut len = 0;
+        for (key, value) in entries {
+            let mut i = bucket(&hasher, &key, cap);
+            loop {
+                match &mut buckets[i] {
+                    Some((k, v)) if *k == key => {
+                        *v = value;
+                        break;
+                    }
+                    Some(_) => i = (i + 1) & (cap - 1),
+                    slot @ None => {
+                        *slot = Some((key, value));
+                        len += 1;
+                        break;
+                    }
+                }
+            }
+        }
+
+        Self {
+            buckets: into_arc(buf),
+            len,
+            hasher,
+        }
+    }
+
+    /// Returns the value for `key`, if there is one.
+    #[inline]
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get_key_value(key).map(|(_, v)| v)
+    }
+
+    pub fn get_key_value<Q>(&self, key: &Q) -> Option<(&K, &V)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let cap = self.buckets.len();
+        let mut i = bucket(&self.hasher, key, cap);
+
+        loop {
+            match &self.buckets[i] {
+                Some((k, v)) if k.borrow() == key => return Some((k, v)),
+                Some(_) => i = (i + 1) & (cap - 1),
+                None => return None,
+            }
+        }
+    }
+
+    #[inline]
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.get_key_value(key).is_some()
+    }
+}
+
+/// The first bucket to probe for `key` in a table of `cap` buckets, `cap` being a power of two.
+#[inline]
+fn bucket<Q: Hash + ?Sized>(hasher: &impl BuildHasher, key: &Q, cap: usize) -> usize {
+    hasher.hash_one(key) as usize & (cap - 1)
+}
+
+impl<K, V, S: Clone> Clone for FrozenArcHashMap<K, V, S> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            buckets: self.buckets.clone(),
+            len: self.len,
+            hasher: self.hasher.clone(),
+        }
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug, S> fmt::Debug for FrozenArcHashMap<K, V, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K, V, S> PartialEq for FrozenArcHashMap<K, V, S>
+where
+    K: Hash + Eq,
+    V: PartialEq,
+    S: BuildHasher,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().all(|(k, v)| other.get(k) == Some(v))
+    }
+}
+
+impl<K: Hash + Eq, V: Eq, S: BuildHasher> Eq for FrozenArcHashMap<K, V, S> {}
+
+impl<K: Hash + Eq, V, S: BuildHasher + Default> FromIterator<(K, V)> for FrozenArcHashMap<K, V, S> {
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        Self::from_iter_with_hasher(iter, S::default())
+    }
+}
+
+pub trait CollectIntoFrozenArcHashMap<K, V> {
+    /// Collects the pairs into a [`FrozenArcHashMap`]. If a key occurs more than once, the last
+    /// value for it is kept, just like when inserting into a map.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use collect_into_rc_slice::*;
+    ///
+    /// let map = (0..1000).map(|i| (i.to_string(), i)).collect_into_frozen_arc_hash_map();
+    ///
+    /// assert_eq!(map.get("512"), Some(&512));
+    /// assert!(map.capacity() >= 1024);
+    /// ```
+    fn collect_into_frozen_arc_hash_map(self) -> FrozenArcHashMap<K, V>;
+}
+
+impl<I, K, V> CollectIntoFrozenArcHashMap<K, V> for I
+where
+    I: IntoIterator<Item = (K, V)>,
+    K: Hash + Eq,
+{
+    #[inline]
+    fn collect_into_frozen_arc_hash_map(self) -> FrozenArcHashMap<K, V> {
+        FrozenArcHashMap::from_iter_with_hasher(self, RandomState::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::hash::BuildHasherDefault;
+
+    /// A hasher sending every key to the same bucket, to exercise the probing.
+    #[derive(Default)]
+    struct Collide;
+
+    impl std::hash::Hasher for Collide {
+        fn finish(&self) -> u64 {
+            0
+        }
+
+        fn write(&mut self, _: &[u8]) {}
+    }
+
+    #[test]
+    fn test_frozen_arc_hash_map_collisions() {
+        let map = FrozenArcHashMap::from_iter_with_hasher(
+            [(3, "c"), (1, "a"), (2, "b"), (1, "z")],
+            BuildHasherDefault::<Collide>::default(),
+        );
+
+        assert_eq!(map.len(), 3);
+        assert_eq!(map.capacity(), 8);
+        assert_eq!(map.get(&1), Some(&"z"));
+        assert_eq!(map.get(&2), Some(&"b"));
+        assert_eq!(map.get(&4), None);
+        assert_eq!(map.values().count(), 3);
+    }
+
+    #[test]
+    fn test_frozen_arc_hash_map_empty() {
+        let map: FrozenArcHashMap<String, u8> = std::iter::empty().collect();
+
+        assert!(map.is_empty());
+        assert!(!map.contains_key("a"));
+        assert_eq!(map, map.clone());
+    }
+}
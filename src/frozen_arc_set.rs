@@ -0,0 +1,304 @@
+#![cfg(target_has_atomic = "ptr")]
+use crate::{
+    arc::{into_arc, ArcBuf},
+    frozen_rc_map::sorted_range,
+};
+use std::{
+    borrow::Borrow,
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+    ops::{Deref, RangeBounds},
+    sync::Arc,
+};
+
+/// A thread-safe immutable set stored as a single sorted `Arc<[T]>` without duplicates.
+///
+/// Membership tests are binary searches, and the set operations merge the sorted elements into a
+/// new set in one pass.
+///
+/// # Examples
+/// ```rust
+/// use collect_into_rc_slice::*;
+///
+/// let primes = [7, 2, 5, 3, 5].into_iter().collect_into_frozen_arc_set();
+/// let odd = (1..10).step_by(2).collect_into_frozen_arc_set();
+///
+/// assert_eq!(&*primes, &[2, 3, 5, 7]);
+/// assert!(primes.contains(&5));
+/// assert_eq!(primes.range(3..7), &[3, 5]);
+/// assert_eq!(&*primes.intersection(&odd), &[3, 5, 7]);
+/// ```
+pub struct FrozenArcSet<T> {
+    items: Arc<[T]>,
+}
+
+impl<T> FrozenArcSet<T> {
+    /// The elements of the set, in order.
+    #[inline]
+    pub fn items(&self) -> &Arc<[T]> {
+        &self.items
+    }
+
+    /// Returns `true` if the two sets point to the same allocation, like `Arc::ptr_eq`.
+    #[inline]
+    pub fn ptr_eq(this: &Self, other: &Self) -> bool {
+        Arc::ptr_eq(&this.items, &other.items)
+    }
+}
+
+impl<T: Ord> FrozenArcSet<T> {
+    fn from_buf(mut buf: ArcBuf<T>) -> Self {
+        buf.as_mut_slice().sort();
+        Self::from_sorted_buf(buf)
+    }
+
+    fn from_sorted_buf(mut buf: ArcBuf<T>) -> Self {
+        buf.dedup_by(|item, prev| item == prev);
+
+        Self {
+            items: into_arc(buf),
+        }
+    }
+
+    #[inline]
+    pub fn contains<Q>(&self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.items
+            .binary_search_by(|item| item.borrow().cmp(value))
+            .is_ok()
+    }
+
+    /// The elements that fall in `range`, in order.
+    pub fn range<Q, R>(&self, range: R) -> &[T]
+    where
+        T: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        &self.items[sorted_range(&self.items, T::borrow, range)]
+    }
+
+    /// Returns `true` if every element of `self` is also in `other`.
+    pub fn is_subset(&self, other: &Self) -> bool {
+        self.len() <= other.len() && self.iter().all(|item| other.contains(item))
+    }
+
+    /// Merges the two sets, keeping the elements for which `keep` returns `true` given whether
+    /// they are in `self` and in `other`.
+    fn merge(&self, other: &Self, keep: impl Fn(bool, bool) -> bool) -> Self
+    where
+        T: Clone,
+    {
+        let mut buf = ArcBuf::with_capacity(self.len() + other.len());
+        let (mut a, mut b) = (self.iter().peekable(), other.iter().peekable());
+
+        loop {
+            let (item, in_a, in_b) = match (a.peek(), b.peek()) {
+                (Some(x), Some(y)) => match x.cmp(y) {
+                    Ordering::Less => (a.next(), true, false),
+                    Ordering::Greater => (b.next(), false, true),
+                    Ordering::Equal => (a.next().and(b.next()), true, true),
+                },
+                (Some(_), None) => (a.next(), true, false),
+                (None, Some(_)) => (b.next(), false, true),
+                (None, None) => break,
+            };
+
+            if keep(in_a, in_b) {
+                buf.push(item.expect("peeked").clone());
+            }
+        }
+
+        // the elements are already sorted and unique
+        Self {
+            items: into_arc(buf),
+        }
+    }
+
+    /// The elements that are in `self` or in `other`.
+    #[inline]
+    pub fn union(&self, other: &Self) -> Self
+    where
+        T: Clone,
+    {
+        self.merge(other, |a, b| a || b)
+    }
+
+    /// The elements that are in both `self` and `other`.
+    #[inline]
+    pub fn intersection(&self, other: &Self) -> Self
+    where
+        T: Clone,
+    {
+        self.merge(other, |a, b| a && b)
+    }
+
+    /// The elements that are in `self` but not in `other`.
+    #[inline]
+    pub fn difference(&self, other: &Self) -> Self
+    where
+        T: Clone,
+    {
+        self.merge(other, |a, b| a && !b)
+    }
+
+    /// The elements that are in exactly one of `self` and `other`.
+    #[inline]
+    pub fn symmetric_difference(&self, other: &Self) -> Self
+    where
+        T: Clone,
+    {
+        self.merge(other, |a, b| a != b)
+    }
+}
+
+impl<T> Deref for FrozenArcSet<T> {
+    type Target = [T];
+
+    #[inline]
+    fn deref(&self) -> &[T] {
+        &self.items
+    }
+}
+
+impl<T> AsRef<[T]> for FrozenArcSet<T> {
+    #[inline]
+    fn as_ref(&self) -> &[T] {
+        self
+    }
+}
+
+impl<T> Clone for FrozenArcSet<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            items: self.items.clone(),
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for FrozenArcSet<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for FrozenArcSet<T> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.items == other.items
+    }
+}
+
+impl<T: Eq> Eq for FrozenArcSet<T> {}
+
+impl<T: Hash> Hash for FrozenArcSet<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.items.hash(state);
+    }
+}
+
+impl<T: Ord> FromIterator<T> for FrozenArcSet<T> {
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::from_buf(ArcBuf::from_iter(iter.into_iter()))
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<T: Ord + Send> rayon::iter::FromParallelIterator<T> for FrozenArcSet<T> {
+    /// Collects the elements in parallel and sorts them with a parallel sort before dropping the
+    /// duplicates.
+    fn from_par_iter<I: rayon::iter::IntoParallelIterator<Item = T>>(iter: I) -> Self {
+        use rayon::slice::ParallelSliceMut;
+
+        let mut buf = crate::par::from_par_iter(iter.into_par_iter());
+        buf.as_mut_slice().par_sort_unstable();
+        Self::from_sorted_buf(buf)
+    }
+}
+
+pub trait CollectIntoFrozenArcSet<T> {
+    /// Collects the iterator into a [`FrozenArcSet`], sorting it and dropping the duplicates.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use collect_into_rc_slice::*;
+    ///
+    /// let set = "mississippi".chars().collect_into_frozen_arc_set();
+    ///
+    /// assert_eq!(&*set, &['i', 'm', 'p', 's']);
+    /// ```
+    fn collect_into_frozen_arc_set(self) -> FrozenArcSet<T>;
+}
+
+impl<I, T> CollectIntoFrozenArcSet<T> for I
+where
+    I: IntoIterator<Item = T>,
+    T: Ord,
+{
+    fn collect_into_frozen_arc_set(self) -> FrozenArcSet<T> {
+        FrozenArcSet::from_buf(ArcBuf::from_iter(self.into_iter()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ops::Bound;
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_frozen_arc_set_from_par_iter() {
+        use rayon::prelude::*;
+
+        let set: FrozenArcSet<u32> = (0..10_000u32)
+            .into_par_iter()
+            .map(|i| i * 7 % 1000)
+            .collect();
+        assert_eq!(set, (0..1000).collect_into_frozen_arc_set());
+    }
+
+    #[test]
+    fn test_frozen_arc_set_ops() {
+        let a = (0..10).collect_into_frozen_arc_set();
+        let b = (5..15).collect_into_frozen_arc_set();
+
+        assert!(a.union(&b).iter().copied().eq(0..15));
+        assert!(a.intersection(&b).iter().copied().eq(5..10));
+        assert!(a.difference(&b).iter().copied().eq(0..5));
+        assert!(a
+            .symmetric_difference(&b)
+            .iter()
+            .copied()
+            .eq((0..5).chain(10..15)));
+        assert!(a.intersection(&b).is_subset(&a));
+        assert!(!a.is_subset(&b));
+    }
+
+    #[test]
+    fn test_frozen_arc_set_range() {
+        let set: FrozenArcSet<String> = ["pear", "apple", "fig", "kiwi", "apple"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+
+        assert_eq!(set.len(), 4);
+        assert_eq!(
+            set.range::<str, _>((Bound::Included("b"), Bound::Excluded("l"))),
+            &["fig", "kiwi"]
+        );
+        assert_eq!(
+            set.range::<str, _>((Bound::Excluded("fig"), Bound::Unbounded)),
+            &["kiwi", "pear"]
+        );
+        assert!(set
+            .range::<str, _>((Bound::Included("z"), Bound::Unbounded))
+            .is_empty());
+        assert!(set.contains("pear"));
+    }
+}
@@ -0,0 +1,384 @@
+use crate::rc::{into_rc, RcBuf};
+use std::{
+    borrow::Borrow,
+    cmp::Ordering,
+    fmt, mem,
+    ops::{Bound, Range, RangeBounds},
+    rc::Rc,
+};
+
+/// An immutable map stored as a single sorted `Rc<[(K, V)]>`.
+///
+/// Lookups are binary searches over the entries, so there are no per-entry allocations and cloning
+/// the map only bumps the reference count. Build it with
+/// [`collect_into_frozen_rc_map`](CollectIntoFrozenRcMap::collect_into_frozen_rc_map).
+///
+/// # Examples
+/// ```rust
+/// use collect_into_rc_slice::*;
+///
+/// let ports = [("https", 443), ("http", 80), ("ssh", 22)]
+///     .into_iter()
+///     .collect_into_frozen_rc_map();
+///
+/// assert_eq!(ports.get("http"), Some(&80));
+/// assert!(!ports.contains_key("ftp"));
+/// assert_eq!(ports.keys().copied().collect::<Vec<_>>(), ["http", "https", "ssh"]);
+/// ```
+pub struct FrozenRcMap<K, V> {
+    entries: Rc<[(K, V)]>,
+}
+
+impl<K, V> FrozenRcMap<K, V> {
+    /// The entries of the map, sorted by key.
+    #[inline]
+    pub fn entries(&self) -> &Rc<[(K, V)]> {
+        &self.entries
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Iterates over the entries in the order of their keys.
+    #[inline]
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (&K, &V)> + ExactSizeIterator {
+        self.entries.iter().map(|(k, v)| (k, v))
+    }
+
+    #[inline]
+    pub fn keys(&self) -> impl DoubleEndedIterator<Item = &K> + ExactSizeIterator {
+        self.entries.iter().map(|(k, _)| k)
+    }
+
+    #[inline]
+    pub fn values(&self) -> impl DoubleEndedIterator<Item = &V> + ExactSizeIterator {
+        self.entries.iter().map(|(_, v)| v)
+    }
+
+    /// The entry with the smallest key.
+    #[inline]
+    pub fn first_key_value(&self) -> Option<(&K, &V)> {
+        self.entries.first().map(|(k, v)| (k, v))
+    }
+
+    /// The entry with the largest key.
+    #[inline]
+    pub fn last_key_value(&self) -> Option<(&K, &V)> {
+        self.entries.last().map(|(k, v)| (k, v))
+    }
+
+    /// Returns `true` if the two maps point to the same allocation, like `Rc::ptr_eq`.
+    #[inline]
+    pub fn ptr_eq(this: &Self, other: &Self) -> bool {
+        Rc::ptr_eq(&this.entries, &other.entries)
+    }
+}
+
+impl<K: Ord, V> FrozenRcMap<K, V> {
+    fn from_buf(mut buf: RcBuf<(K, V)>, dedup: bool) -> Self {
+        buf.as_mut_slice().sort_by(|(a, _), (b, _)| a.cmp(b));
+        Self::from_sorted_buf(buf, dedup)
+    }
+
+    /// Builds the map from entries sorted by key, with a stable sort so the duplicates of a key
+    /// are still in the order they were inserted in.
+    fn from_sorted_buf(mut buf: RcBuf<(K, V)>, dedup: bool) -> Self {
+        if dedup {
+            // like inserting into a `BTreeMap`, the last value for a key wins
+            buf.dedup_by(|(key, value), (prev_key, prev_value)| {
+                let same = key == prev_key;
+                if same {
+                    mem::swap(value, prev_value);
+                }
+                same
+            });
+        }
+
+        Self {
+            entries: into_rc(buf),
+        }
+    }
+
+    fn search<Q>(&self, key: &Q) -> Result<usize, usize>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.entries.binary_search_by(|(k, _)| k.borrow().cmp(key))
+    }
+
+    /// Returns the value for `key`, if there is one. If the map has duplicate keys, any of their
+    /// values may be returned.
+    #[inline]
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.get_key_value(key).map(|(_, v)| v)
+    }
+
+    #[inline]
+    pub fn get_key_value<Q>(&self, key: &Q) -> Option<(&K, &V)>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let (k, v) = &self.entries[self.search(key).ok()?];
+        Some((k, v))
+    }
+
+    #[inline]
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.search(key).is_ok()
+    }
+
+    /// The entries whose keys fall in `range`, in order.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use collect_into_rc_slice::*;
+    ///
+    /// let map = (0..10).map(|i| (i, i * i)).collect_into_frozen_rc_map();
+    /// let squares: Vec<_> = map.range(3..6).iter().map(|(_, v)| *v).collect();
+    ///
+    /// assert_eq!(squares, [9, 16, 25]);
+    /// ```
+    pub fn range<Q, R>(&self, range: R) -> &[(K, V)]
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        &self.entries[sorted_range(&self.entries, |(k, _)| k.borrow(), range)]
+    }
+
+    /// Binary searches the entries with a comparator function, like `slice::binary_search_by`.
+    #[inline]
+    pub fn binary_search_by<F>(&self, f: F) -> Result<usize, usize>
+    where
+        F: FnMut(&(K, V)) -> Ordering,
+    {
+        self.entries.binary_search_by(f)
+    }
+
+    /// Binary searches the entries by a key extracted with `f`, like
+    /// `slice::binary_search_by_key`.
+    #[inline]
+    pub fn binary_search_by_key<B: Ord, F>(&self, b: &B, f: F) -> Result<usize, usize>
+    where
+        F: FnMut(&(K, V)) -> B,
+    {
+        self.entries.binary_search_by_key(b, f)
+    }
+
+    /// Returns all the entries for `key`, in the order they were collected in, for maps built with
+    /// [`collect_into_frozen_rc_multimap`].
+    ///
+    /// [`collect_into_frozen_rc_multimap`]: CollectIntoFrozenRcMap::collect_into_frozen_rc_multimap
+    pub fn get_all<Q>(&self, key: &Q) -> &[(K, V)]
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let start = self
+            .entries
+            .partition_point(|(k, _)| k.borrow().cmp(key) == Ordering::Less);
+        let len = self.entries[start..].partition_point(|(k, _)| k.borrow() == key);
+        &self.entries[start..start + len]
+    }
+}
+
+/// The indices of the elements of the sorted `items` whose keys fall in `range`.
+pub(crate) fn sorted_range<T, Q, R>(items: &[T], key: impl Fn(&T) -> &Q, range: R) -> Range<usize>
+where
+    Q: Ord + ?Sized,
+    R: RangeBounds<Q>,
+{
+    let start = match range.start_bound() {
+        Bound::Included(start) => items.partition_point(|item| key(item) < start),
+        Bound::Excluded(start) => items.partition_point(|item| key(item) <= start),
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(end) => items.partition_point(|item| key(item) <= end),
+        Bound::Excluded(end) => items.partition_point(|item| key(item) < end),
+        Bound::Unbounded => items.len(),
+    };
+
+    start..end.max(start)
+}
+
+impl<K, V> Clone for FrozenRcMap<K, V> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            entries: self.entries.clone(),
+        }
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for FrozenRcMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<K: PartialEq, V: PartialEq> PartialEq for FrozenRcMap<K, V> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.entries == other.entries
+    }
+}
+
+impl<K: Eq, V: Eq> Eq for FrozenRcMap<K, V> {}
+
+impl<K: Ord, V> FromIterator<(K, V)> for FrozenRcMap<K, V> {
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        Self::from_buf(RcBuf::from_iter(iter.into_iter()), true)
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<K: Ord + Send, V: Send> rayon::iter::FromParallelIterator<(K, V)> for FrozenRcMap<K, V> {
+    /// Collects the pairs in parallel and sorts them by key with a parallel sort. If a key occurs
+    /// more than once, the last value for it is kept, just like when collecting sequentially.
+    fn from_par_iter<I: rayon::iter::IntoParallelIterator<Item = (K, V)>>(iter: I) -> Self {
+        use rayon::slice::ParallelSliceMut;
+
+        let mut buf = crate::par::from_par_iter(iter.into_par_iter());
+        buf.as_mut_slice().par_sort_by(|(a, _), (b, _)| a.cmp(b));
+        Self::from_sorted_buf(buf, true)
+    }
+}
+
+pub trait CollectIntoFrozenRcMap<K, V> {
+    /// Collects the pairs into a [`FrozenRcMap`], sorted by key. If a key occurs more than once,
+    /// the last value for it is kept, just like when inserting into a map.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use collect_into_rc_slice::*;
+    ///
+    /// let map = [(2, 'a'), (1, 'b'), (2, 'c')].into_iter().collect_into_frozen_rc_map();
+    ///
+    /// assert_eq!(map.len(), 2);
+    /// assert_eq!(map.get(&2), Some(&'c'));
+    /// ```
+    fn collect_into_frozen_rc_map(self) -> FrozenRcMap<K, V>;
+
+    /// Collects the pairs into a [`FrozenRcMap`], sorted by key, keeping every pair even if the
+    /// keys repeat. Use [`FrozenRcMap::get_all`] to get all of the values for a key.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use collect_into_rc_slice::*;
+    ///
+    /// let map = [(2, 'a'), (1, 'b'), (2, 'c')].into_iter().collect_into_frozen_rc_multimap();
+    ///
+    /// assert_eq!(map.len(), 3);
+    /// assert_eq!(map.get_all(&2), &[(2, 'a'), (2, 'c')]);
+    /// ```
+    fn collect_into_frozen_rc_multimap(self) -> FrozenRcMap<K, V>;
+}
+
+impl<I, K, V> CollectIntoFrozenRcMap<K, V> for I
+where
+    I: IntoIterator<Item = (K, V)>,
+    K: Ord,
+{
+    fn collect_into_frozen_rc_map(self) -> FrozenRcMap<K, V> {
+        FrozenRcMap::from_buf(RcBuf::from_iter(self.into_iter()), true)
+    }
+
+    fn collect_into_frozen_rc_multimap(self) -> FrozenRcMap<K, V> {
+        FrozenRcMap::from_buf(RcBuf::from_iter(self.into_iter()), false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frozen_rc_map_dedup() {
+        let map: FrozenRcMap<String, Vec<u8>> = ["b", "a", "c", "a", "b", "a"]
+            .into_iter()
+            .enumerate()
+            .map(|(i, k)| (k.to_string(), vec![i as u8]))
+            .collect();
+
+        assert_eq!(map.len(), 3);
+        assert_eq!(map.get("a"), Some(&vec![5]));
+        assert_eq!(map.get("b"), Some(&vec![4]));
+        assert_eq!(map.get("c"), Some(&vec![2]));
+        assert_eq!(map.get("d"), None);
+    }
+
+    #[test]
+    fn test_frozen_rc_map_multimap() {
+        let map = (0..20)
+            .map(|i| (i % 4, i))
+            .collect_into_frozen_rc_multimap();
+
+        assert_eq!(map.len(), 20);
+        assert_eq!(map.get_all(&3).len(), 5);
+        assert!(map
+            .get_all(&3)
+            .iter()
+            .map(|(_, v)| v)
+            .eq(&[3, 7, 11, 15, 19]));
+        assert!(map.get_all(&4).is_empty());
+        assert!(FrozenRcMap::ptr_eq(&map, &map.clone()));
+    }
+
+    #[test]
+    fn test_frozen_rc_map_range() {
+        let map: FrozenRcMap<&str, u32> = [("b", 2), ("d", 4), ("a", 1), ("c", 3)]
+            .into_iter()
+            .collect();
+
+        assert_eq!(map.range("b"..="c"), &[("b", 2), ("c", 3)]);
+        assert_eq!(map.range("c"..), &[("c", 3), ("d", 4)]);
+        assert!(map.range("e"..).is_empty());
+        assert_eq!(map.first_key_value(), Some((&"a", &1)));
+        assert_eq!(map.last_key_value(), Some((&"d", &4)));
+        assert_eq!(map.binary_search_by_key(&3, |&(_, v)| v), Ok(2));
+    }
+
+    #[test]
+    fn test_frozen_rc_map_empty() {
+        let map = std::iter::empty::<(u8, u8)>().collect_into_frozen_rc_map();
+
+        assert!(map.is_empty());
+        assert!(!map.contains_key(&0));
+        assert!(map.first_key_value().is_none());
+        assert!(map.range(1..).is_empty());
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_frozen_rc_map_from_par_iter() {
+        use rayon::prelude::*;
+
+        let map: FrozenRcMap<u32, usize> = (0..10_000usize)
+            .into_par_iter()
+            .map(|i| ((i % 100) as u32, i))
+            .collect();
+
+        assert_eq!(map.len(), 100);
+        assert_eq!(map.get(&42), Some(&9942));
+    }
+}
@@ -0,0 +1,205 @@
+use crate::{
+    header_slice::{into_rc_header_slice, HeaderSlice, RcHeaderBuf},
+    into_char::IntoChar,
+};
+use std::{
+    cmp::Ordering,
+    collections::hash_map::DefaultHasher,
+    fmt,
+    hash::{Hash, Hasher},
+    ops::Deref,
+    rc::Rc,
+    str,
+};
+
+/// An `Rc<str>` that stores the hash of the string next to the reference counts.
+///
+/// The hash is computed once when the string is collected. Hashing a `HashedRcStr` only feeds the
+/// cached value to the hasher, and comparing two of them for equality compares the hashes before
+/// the contents, so lookups in hash maps and interners never rehash or rescan the string.
+///
+/// The cached hash is computed with a fixed-key hasher, so it is the same for equal strings
+/// everywhere in the program. Since it is not the hash of the `str` itself, `HashedRcStr` doesn't
+/// implement `Borrow<str>`: maps keyed by it have to be looked up by a `HashedRcStr` too.
+///
+/// # Examples
+/// ```rust
+/// use std::collections::HashSet;
+/// use collect_into_rc_slice::*;
+///
+/// let s = "Hello, world!".chars().collect_into_hashed_rc_str();
+/// let set: HashSet<_> = [s.clone()].into_iter().collect();
+///
+/// assert!(set.contains(&HashedRcStr::from("Hello, world!")));
+/// ```
+#[derive(Clone)]
+pub struct HashedRcStr(Rc<HeaderSlice<u64, str>>);
+
+impl HashedRcStr {
+    fn from_buf(buf: RcHeaderBuf<u64, u8>) -> Self {
+        let mut hasher = DefaultHasher::new();
+        // SAFETY: The bytes were only ever written from `char`s and `str`s.
+        unsafe { str::from_utf8_unchecked(buf.as_slice()) }.hash(&mut hasher);
+
+        let rc = into_rc_header_slice(buf, hasher.finish());
+        // SAFETY: `HeaderSlice<u64, str>` has the same layout as `HeaderSlice<u64, [u8]>`, and the
+        // bytes are valid UTF-8.
+        Self(unsafe { Rc::from_raw(Rc::into_raw(rc) as *const HeaderSlice<u64, str>) })
+    }
+
+    /// The hash that was computed when the string was collected.
+    #[inline]
+    pub fn cached_hash(&self) -> u64 {
+        self.0.header
+    }
+
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        &self.0.slice
+    }
+
+    /// Returns `true` if the two strings point to the same allocation.
+    #[inline]
+    pub fn ptr_eq(this: &Self, other: &Self) -> bool {
+        Rc::ptr_eq(&this.0, &other.0)
+    }
+}
+
+impl Deref for HashedRcStr {
+    type Target = str;
+
+    #[inline]
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl AsRef<str> for HashedRcStr {
+    #[inline]
+    fn as_ref(&self) -> &str {
+        self
+    }
+}
+
+impl From<&str> for HashedRcStr {
+    fn from(s: &str) -> Self {
+        let mut buf = RcHeaderBuf::with_capacity(s.len());
+        buf.extend_from_slice(s.as_bytes());
+        Self::from_buf(buf)
+    }
+}
+
+impl fmt::Debug for HashedRcStr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl fmt::Display for HashedRcStr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self.as_str(), f)
+    }
+}
+
+impl PartialEq for HashedRcStr {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        Self::ptr_eq(self, other)
+            || (self.cached_hash() == other.cached_hash() && self.as_str() == other.as_str())
+    }
+}
+
+impl Eq for HashedRcStr {}
+
+impl PartialOrd for HashedRcStr {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for HashedRcStr {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+impl Hash for HashedRcStr {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(self.cached_hash());
+    }
+}
+
+impl PartialEq<str> for HashedRcStr {
+    #[inline]
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for HashedRcStr {
+    #[inline]
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl FromIterator<char> for HashedRcStr {
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = char>>(iter: I) -> Self {
+        Self::from_buf(RcHeaderBuf::from_chars(iter.into_iter()))
+    }
+}
+
+pub trait CollectIntoHashedRcStr {
+    /// Collects the iterator into a [`HashedRcStr`].
+    ///
+    /// # Examples
+    /// ```rust
+    /// use collect_into_rc_slice::*;
+    ///
+    /// let s = "Hello, world!".chars().collect_into_hashed_rc_str();
+    ///
+    /// assert_eq!(&*s, "Hello, world!");
+    /// ```
+    fn collect_into_hashed_rc_str(self) -> HashedRcStr;
+}
+
+impl<T> CollectIntoHashedRcStr for T
+where
+    T: IntoIterator,
+    T::Item: IntoChar,
+{
+    fn collect_into_hashed_rc_str(self) -> HashedRcStr {
+        self.into_iter().map(IntoChar::into_char).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_collect_into_hashed_rc_str() {
+        let a = "Hello, world!".chars().collect_into_hashed_rc_str();
+        let b = HashedRcStr::from("Hello, world!");
+
+        assert_eq!(&*a, "Hello, world!");
+        assert_eq!(a.cached_hash(), b.cached_hash());
+        assert_eq!(a, b);
+        assert_ne!(a, HashedRcStr::from("Hello, world?"));
+    }
+
+    #[test]
+    fn test_hashed_rc_str_map() {
+        let mut map = HashMap::new();
+        map.insert(HashedRcStr::from("foo"), 1);
+        map.insert("bar".chars().collect_into_hashed_rc_str(), 2);
+
+        assert_eq!(map[&HashedRcStr::from("foo")], 1);
+        assert_eq!(map[&HashedRcStr::from("bar")], 2);
+    }
+}
@@ -0,0 +1,263 @@
+#[cfg(target_has_atomic = "ptr")]
+use crate::arc::ArcInner;
+use crate::{
+    raw::{RawBuf, WithHeader},
+    rc::RcBox,
+};
+#[cfg(target_has_atomic = "ptr")]
+use std::sync::{atomic::AtomicUsize, Arc};
+use std::{ptr, rc::Rc};
+
+/// A header value followed by a slice, stored together in a single allocation.
+///
+/// This is meant to be used unsized, as `HeaderSlice<H, [T]>` behind an `Rc` or `Arc`, so the
+/// header and the elements share one reference-counted allocation.
+///
+/// # Examples
+/// ```rust
+/// use collect_into_rc_slice::*;
+///
+/// let rc = (1..=3).collect_into_rc_header_slice("numbers");
+///
+/// assert_eq!(rc.header, "numbers");
+/// assert_eq!(&rc.slice, &[1, 2, 3]);
+/// ```
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(C)]
+pub struct HeaderSlice<H, T: ?Sized> {
+    pub header: H,
+    pub slice: T,
+}
+
+pub(crate) type RcHeaderBuf<H, T> = RawBuf<WithHeader<RcBox<()>, H>, T>;
+
+/// Writes the reference counts and the header, and hands the buffer over to an
+/// `Rc<HeaderSlice<H, [T]>>`.
+pub(crate) fn into_rc_header_slice<H, T>(
+    buf: RcHeaderBuf<H, T>,
+    header: H,
+) -> Rc<HeaderSlice<H, [T]>> {
+    let (alloc, data) = buf.into_raw_parts();
+    let header_offset = WithHeader::<RcBox<()>, H>::header_offset::<T>();
+
+    // SAFETY:
+    // - The prefix of the allocation is reserved for the `RcBox` header.
+    // - The `HeaderSlice` starts at `header_offset`, with the header as its first field.
+    // - The pointer to the `HeaderSlice` carries the length of the elements as its metadata.
+    unsafe {
+        ptr::write(
+            alloc.as_ptr() as *mut RcBox<()>,
+            RcBox {
+                strong_count: 1,
+                weak_count: 1,
+                data: (),
+            },
+        );
+
+        let header_ptr = alloc.as_ptr().add(header_offset);
+        ptr::write(header_ptr as *mut H, header);
+
+        let data = ptr::slice_from_raw_parts(header_ptr, data.len());
+        Rc::from_raw(data as *const HeaderSlice<H, [T]>)
+    }
+}
+
+pub trait CollectIntoRcHeaderSlice<T> {
+    /// Collects the iterator into an `Rc<HeaderSlice<H, [T]>>`, with `header` stored in the same
+    /// allocation before the elements.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use std::rc::Rc;
+    /// use collect_into_rc_slice::*;
+    ///
+    /// struct Schema {
+    ///     id: u32,
+    /// }
+    ///
+    /// let row: Rc<HeaderSlice<Schema, [&str]>> =
+    ///     ["a", "b", "c"].into_iter().collect_into_rc_header_slice(Schema { id: 7 });
+    ///
+    /// assert_eq!(row.header.id, 7);
+    /// assert_eq!(&row.slice, &["a", "b", "c"]);
+    /// ```
+    fn collect_into_rc_header_slice<H>(self, header: H) -> Rc<HeaderSlice<H, [T]>>;
+
+    /// Collects the iterator into an `Rc<HeaderSlice<H, [T]>>`, with the header computed from the
+    /// collected elements by `f` before the allocation is frozen.
+    ///
+    /// This avoids a second pass over the iterator for headers that summarize the elements, like
+    /// a checksum or the minimum and maximum.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use collect_into_rc_slice::*;
+    ///
+    /// let rc = [3, 1, 4, 1, 5]
+    ///     .into_iter()
+    ///     .collect_into_rc_header_slice_with(|items| items.iter().max().copied());
+    ///
+    /// assert_eq!(rc.header, Some(5));
+    /// assert_eq!(&rc.slice, &[3, 1, 4, 1, 5]);
+    /// ```
+    fn collect_into_rc_header_slice_with<H, F>(self, f: F) -> Rc<HeaderSlice<H, [T]>>
+    where
+        F: FnOnce(&[T]) -> H;
+}
+
+impl<I, T> CollectIntoRcHeaderSlice<T> for I
+where
+    I: IntoIterator<Item = T>,
+{
+    fn collect_into_rc_header_slice<H>(self, header: H) -> Rc<HeaderSlice<H, [T]>> {
+        into_rc_header_slice(RawBuf::from_iter(self.into_iter()), header)
+    }
+
+    fn collect_into_rc_header_slice_with<H, F>(self, f: F) -> Rc<HeaderSlice<H, [T]>>
+    where
+        F: FnOnce(&[T]) -> H,
+    {
+        let buf = RawBuf::from_iter(self.into_iter());
+        let header = f(buf.as_slice());
+        into_rc_header_slice(buf, header)
+    }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+type ArcHeaderBuf<H, T> = RawBuf<WithHeader<ArcInner<()>, H>, T>;
+
+/// Writes the reference counts and the header, and hands the buffer over to an
+/// `Arc<HeaderSlice<H, [T]>>`.
+#[cfg(target_has_atomic = "ptr")]
+fn into_arc_header_slice<H, T>(buf: ArcHeaderBuf<H, T>, header: H) -> Arc<HeaderSlice<H, [T]>> {
+    let (alloc, data) = buf.into_raw_parts();
+    let header_offset = WithHeader::<ArcInner<()>, H>::header_offset::<T>();
+
+    // SAFETY:
+    // - The prefix of the allocation is reserved for the `ArcInner` header.
+    // - The `HeaderSlice` starts at `header_offset`, with the header as its first field.
+    // - The pointer to the `HeaderSlice` carries the length of the elements as its metadata.
+    unsafe {
+        ptr::write(
+            alloc.as_ptr() as *mut ArcInner<()>,
+            ArcInner {
+                strong: AtomicUsize::new(1),
+                weak: AtomicUsize::new(1),
+                data: (),
+            },
+        );
+
+        let header_ptr = alloc.as_ptr().add(header_offset);
+        ptr::write(header_ptr as *mut H, header);
+
+        let data = ptr::slice_from_raw_parts(header_ptr, data.len());
+        Arc::from_raw(data as *const HeaderSlice<H, [T]>)
+    }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+pub trait CollectIntoArcHeaderSlice<T> {
+    /// Collects the iterator into an `Arc<HeaderSlice<H, [T]>>`, with `header` stored in the same
+    /// allocation before the elements.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use std::sync::Arc;
+    /// use collect_into_rc_slice::*;
+    ///
+    /// struct Layout {
+    ///     width: f32,
+    /// }
+    ///
+    /// let run: Arc<HeaderSlice<Layout, [u16]>> =
+    ///     [72, 105].into_iter().collect_into_arc_header_slice(Layout { width: 12.5 });
+    ///
+    /// assert_eq!(run.header.width, 12.5);
+    /// assert_eq!(&run.slice, &[72, 105]);
+    /// ```
+    fn collect_into_arc_header_slice<H>(self, header: H) -> Arc<HeaderSlice<H, [T]>>;
+
+    /// Collects the iterator into an `Arc<HeaderSlice<H, [T]>>`, with the header computed from the
+    /// collected elements by `f` before the allocation is frozen.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use collect_into_rc_slice::*;
+    ///
+    /// let arc = b"hello"
+    ///     .iter()
+    ///     .copied()
+    ///     .collect_into_arc_header_slice_with(|bytes| bytes.iter().fold(0u8, |a, b| a ^ b));
+    ///
+    /// assert_eq!(arc.header, 0x62);
+    /// assert_eq!(&arc.slice, b"hello");
+    /// ```
+    fn collect_into_arc_header_slice_with<H, F>(self, f: F) -> Arc<HeaderSlice<H, [T]>>
+    where
+        F: FnOnce(&[T]) -> H;
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl<I, T> CollectIntoArcHeaderSlice<T> for I
+where
+    I: IntoIterator<Item = T>,
+{
+    fn collect_into_arc_header_slice<H>(self, header: H) -> Arc<HeaderSlice<H, [T]>> {
+        into_arc_header_slice(RawBuf::from_iter(self.into_iter()), header)
+    }
+
+    fn collect_into_arc_header_slice_with<H, F>(self, f: F) -> Arc<HeaderSlice<H, [T]>>
+    where
+        F: FnOnce(&[T]) -> H,
+    {
+        let buf = RawBuf::from_iter(self.into_iter());
+        let header = f(buf.as_slice());
+        into_arc_header_slice(buf, header)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rc_header_slice() {
+        let mut iter = (0..10u8).map(|i| i * 2);
+        let rc = std::iter::from_fn(move || iter.next()).collect_into_rc_header_slice(u64::MAX);
+
+        assert_eq!(rc.header, u64::MAX);
+        assert_eq!(rc.slice, [0, 2, 4, 6, 8, 10, 12, 14, 16, 18]);
+        assert_eq!(Rc::strong_count(&rc), 1);
+        assert_eq!(Rc::weak_count(&rc), 0);
+    }
+
+    #[test]
+    fn test_rc_header_slice_drop() {
+        let rc = vec![String::from("a"), String::from("b")]
+            .into_iter()
+            .collect_into_rc_header_slice(String::from("header"));
+
+        assert_eq!(rc.header, "header");
+        assert_eq!(&rc.slice, &["a", "b"]);
+    }
+
+    #[test]
+    fn test_rc_header_slice_with() {
+        let rc = (1..=4)
+            .map(|i| i.to_string())
+            .collect_into_rc_header_slice_with(|items| items.len());
+
+        assert_eq!(rc.header, 4);
+        assert_eq!(&rc.slice, &["1", "2", "3", "4"]);
+    }
+
+    #[test]
+    fn test_arc_header_slice() {
+        let arc = "abc".bytes().collect_into_arc_header_slice([1u128, 2]);
+
+        assert_eq!(arc.header, [1, 2]);
+        assert_eq!(&arc.slice, b"abc");
+        assert_eq!(Arc::strong_count(&arc), 1);
+        assert_eq!(Arc::weak_count(&arc), 0);
+    }
+}
@@ -0,0 +1,244 @@
+#![cfg(feature = "hybrid-rc")]
+use crate::into_char::IntoChar;
+use hybrid_rc::{Arc, Rc};
+use std::{
+    mem::{self, MaybeUninit},
+    ptr,
+};
+
+/// The items written so far to the front of a new slice, which are dropped unless the slice is
+/// filled.
+struct Filled<'a, T> {
+    slots: &'a mut [MaybeUninit<T>],
+    len: usize,
+}
+
+impl<T> Filled<'_, T> {
+    /// Moves the written items out into a `Vec`, leaving the slice empty.
+    fn take_vec(&mut self, additional: usize) -> Vec<T> {
+        let mut vec = Vec::with_capacity(self.len.saturating_add(additional));
+        // SAFETY: The first `len` slots were written, and forgetting them right after moves them.
+        vec.extend(
+            self.slots[..self.len]
+                .iter()
+                .map(|slot| unsafe { slot.assume_init_read() }),
+        );
+        self.len = 0;
+        vec
+    }
+}
+
+impl<T> Drop for Filled<'_, T> {
+    fn drop(&mut self) {
+        // SAFETY: The first `len` slots were written.
+        unsafe {
+            ptr::drop_in_place(ptr::slice_from_raw_parts_mut(
+                self.slots.as_mut_ptr() as *mut T,
+                self.len,
+            ))
+        };
+    }
+}
+
+/// Collects `iter` into a new shared `hybrid_rc::Arc<[T]>`.
+///
+/// `hybrid_rc` only allocates slices of a length that is known upfront, so when the iterator
+/// reports its exact length the slice is allocated once and filled in place. Otherwise, or if the
+/// iterator turns out to have a different length, the items are gathered in a `Vec` first, which
+/// is then copied into the slice.
+fn collect_hybrid_arc<T, I: Iterator<Item = T>>(mut iter: I) -> Arc<[T]> {
+    let len = match iter.size_hint() {
+        (lower, Some(upper)) if lower == upper => lower,
+        _ => return Arc::from(iter.collect::<Vec<_>>()),
+    };
+
+    let mut uninit = Arc::<[T]>::new_uninit_slice(len);
+    let mut filled = Filled {
+        slots: Arc::get_mut(&mut uninit).expect("a new allocation is unique"),
+        len: 0,
+    };
+    for item in iter.by_ref().take(len) {
+        filled.slots[filled.len].write(item);
+        filled.len += 1;
+    }
+
+    match iter.next() {
+        None if filled.len == len => {
+            mem::forget(filled);
+            // SAFETY: Every slot was written.
+            unsafe { uninit.assume_init() }
+        }
+        extra => {
+            let mut vec = filled.take_vec(1);
+            vec.extend(extra);
+            vec.extend(iter);
+            Arc::from(vec)
+        }
+    }
+}
+
+/// Collects `iter` into a new thread-local `hybrid_rc::Rc<[T]>`.
+fn collect_hybrid_rc<T, I: Iterator<Item = T>>(iter: I) -> Rc<[T]> {
+    // A new shared allocation isn't owned by any thread yet, so it can always become local.
+    Arc::to_local(&collect_hybrid_arc(iter)).expect("allocation is not owned by another thread")
+}
+
+/// Collects an iterator into a `hybrid_rc` slice.
+///
+/// The slice is allocated once and filled in place when the iterator reports its exact length.
+/// Otherwise the items are gathered in a `Vec` first and copied into the slice, allocating twice
+/// just like `std`'s `collect::<Vec<_>>().into()`.
+pub trait CollectIntoHybridSlice<T> {
+    /// Collects the iterator into a thread-local `hybrid_rc::Rc<[T]>`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use collect_into_rc_slice::*;
+    ///
+    /// let arr = [1, 2, 3, 4, 5];
+    /// let rc: hybrid_rc::Rc<[i32]> = arr.into_iter().collect_into_hybrid_rc_slice();
+    ///
+    /// assert_eq!(&*rc, &[1, 2, 3, 4, 5]);
+    /// ```
+    fn collect_into_hybrid_rc_slice(self) -> Rc<[T]>;
+
+    /// Collects the iterator into a shared `hybrid_rc::Arc<[T]>`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use collect_into_rc_slice::*;
+    ///
+    /// let arr = [1, 2, 3, 4, 5];
+    /// let arc: hybrid_rc::Arc<[i32]> = arr.into_iter().collect_into_hybrid_arc_slice();
+    ///
+    /// assert_eq!(&*arc, &[1, 2, 3, 4, 5]);
+    /// ```
+    fn collect_into_hybrid_arc_slice(self) -> Arc<[T]>;
+}
+
+impl<I, T> CollectIntoHybridSlice<T> for I
+where
+    I: IntoIterator<Item = T>,
+{
+    fn collect_into_hybrid_rc_slice(self) -> Rc<[T]> {
+        collect_hybrid_rc(self.into_iter())
+    }
+
+    fn collect_into_hybrid_arc_slice(self) -> Arc<[T]> {
+        collect_hybrid_arc(self.into_iter())
+    }
+}
+
+/// Collects an iterator of `char`s into a `hybrid_rc` string.
+///
+/// `hybrid_rc` can't allocate a string before its length is known, so the `char`s are always
+/// gathered in a `String` first and copied into the string, allocating twice. This is the same as
+/// `std`'s `collect::<String>().into()`.
+pub trait CollectIntoHybridStr {
+    /// Collects the iterator into a thread-local `hybrid_rc::Rc<str>`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use collect_into_rc_slice::*;
+    ///
+    /// let s: hybrid_rc::Rc<str> = "Hello, world!".chars().collect_into_hybrid_rc_str();
+    ///
+    /// assert!(s.as_ref() == "Hello, world!");
+    /// ```
+    fn collect_into_hybrid_rc_str(self) -> Rc<str>;
+
+    /// Collects the iterator into a shared `hybrid_rc::Arc<str>`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use collect_into_rc_slice::*;
+    ///
+    /// let s: hybrid_rc::Arc<str> = "Hello, world!".chars().collect_into_hybrid_arc_str();
+    ///
+    /// assert!(s.as_ref() == "Hello, world!");
+    /// ```
+    fn collect_into_hybrid_arc_str(self) -> Arc<str>;
+}
+
+impl<T> CollectIntoHybridStr for T
+where
+    T: IntoIterator,
+    T::Item: IntoChar,
+{
+    fn collect_into_hybrid_rc_str(self) -> Rc<str> {
+        // see `collect_into_hybrid_arc_str`
+        Arc::to_local(&self.collect_into_hybrid_arc_str())
+            .expect("allocation is not owned by another thread")
+    }
+
+    fn collect_into_hybrid_arc_str(self) -> Arc<str> {
+        // the length of the UTF-8 data isn't known until every `char` is encoded, so it is
+        // gathered in a `String` before `hybrid_rc` copies it into the allocation
+        Arc::from(
+            self.into_iter()
+                .map(IntoChar::into_char)
+                .collect::<String>(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collect_into_hybrid_rc_slice() {
+        let rc = vec![1, 2, 3, 4, 5]
+            .into_iter()
+            .collect_into_hybrid_rc_slice();
+
+        assert_eq!(&*rc, &[1, 2, 3, 4, 5]);
+        assert_eq!(Rc::strong_count(&rc), 1);
+        assert_eq!(Rc::weak_count(&rc), 0);
+
+        let arc = Rc::to_shared(&rc);
+        assert_eq!(Arc::strong_count(&arc), 2);
+    }
+
+    #[test]
+    fn test_collect_into_hybrid_arc_str() {
+        let mut str = "Hello, world!".chars();
+        let s = std::iter::from_fn(move || str.next()).collect_into_hybrid_arc_str();
+
+        assert!(s.as_ref() == "Hello, world!");
+        assert_eq!(Arc::strong_count(&s), 1);
+        assert_eq!(Arc::weak_count(&s), 0);
+        assert!(Arc::to_local(&s).is_some());
+    }
+
+    #[test]
+    fn test_collect_into_hybrid_arc_slice_wrong_size_hint() {
+        struct Exact<I>(I, usize);
+
+        impl<I: Iterator> Iterator for Exact<I> {
+            type Item = I::Item;
+
+            fn next(&mut self) -> Option<I::Item> {
+                self.0.next()
+            }
+
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                (self.1, Some(self.1))
+            }
+        }
+
+        let strings = || ["a", "b", "c"].into_iter().map(String::from);
+        assert_eq!(
+            &*Exact(strings(), 3).collect_into_hybrid_arc_slice(),
+            ["a", "b", "c"]
+        );
+        assert_eq!(
+            &*Exact(strings(), 5).collect_into_hybrid_arc_slice(),
+            ["a", "b", "c"]
+        );
+        assert_eq!(
+            &*Exact(strings(), 1).collect_into_hybrid_rc_slice(),
+            ["a", "b", "c"]
+        );
+    }
+}
@@ -0,0 +1,65 @@
+/// An item that the string collectors accept: a `char`, or a reference to one.
+///
+/// This lets `.iter()` over a slice of `char`s be collected into a string pointer without a
+/// `.copied()` first. The trait is sealed, so the set of item types can grow without breaking
+/// anything that relies on it.
+///
+/// # Examples
+/// ```rust
+/// use collect_into_rc_slice::*;
+///
+/// let chars = ['a', 'b', 'c'];
+/// let mut upper = ['X', 'Y'];
+///
+/// assert_eq!(&*chars.iter().collect_into_rc_str(), "abc");
+/// assert_eq!(&*upper.iter_mut().collect_into_arc_str(), "XY");
+/// ```
+pub trait IntoChar: sealed::Sealed {
+    fn into_char(self) -> char;
+}
+
+impl IntoChar for char {
+    #[inline]
+    fn into_char(self) -> char {
+        self
+    }
+}
+
+impl IntoChar for &char {
+    #[inline]
+    fn into_char(self) -> char {
+        *self
+    }
+}
+
+impl IntoChar for &mut char {
+    #[inline]
+    fn into_char(self) -> char {
+        *self
+    }
+}
+
+mod sealed {
+    pub trait Sealed {}
+
+    impl Sealed for char {}
+    impl Sealed for &char {}
+    impl Sealed for &mut char {}
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn test_collect_char_refs_into_strs() {
+        let chars: Vec<char> = "héllo".chars().collect();
+
+        assert_eq!(&*chars.iter().collect_into_rc_str(), "héllo");
+        assert_eq!(&*chars.iter().rev().collect_into_thin_rc_str(), "olléh");
+        assert_eq!(
+            chars.iter().collect_into_hashed_rc_str(),
+            HashedRcStr::from("héllo")
+        );
+    }
+}
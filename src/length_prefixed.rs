@@ -0,0 +1,109 @@
+#![cfg(target_has_atomic = "ptr")]
+use crate::arc::{into_arc, into_arc_str, ArcBuf};
+use std::{
+    io::{self, Read},
+    str,
+    sync::Arc,
+};
+
+/// Reads frames made of a big-endian `u32` length followed by that many bytes of payload.
+///
+/// The payload is read straight into the `Arc` allocation, which is allocated exactly once for the
+/// length of the frame, without going through a `Vec<u8>`. Frames longer than `max_len` are
+/// rejected before anything is allocated, so a corrupted or malicious length can't make the
+/// reader allocate gigabytes.
+///
+/// A `bytes::Buf` can be decoded from through `Buf::reader`.
+///
+/// # Examples
+/// ```rust
+/// use collect_into_rc_slice::*;
+///
+/// let mut wire: &[u8] = b"\0\0\0\x05hello\0\0\0\x02\xff\xfe";
+///
+/// assert_eq!(&*wire.read_length_prefixed_arc_str(1024).unwrap(), "hello");
+/// assert_eq!(&*wire.read_length_prefixed_arc_slice(1024).unwrap(), b"\xff\xfe");
+/// assert!(wire.is_empty());
+/// ```
+pub trait ReadLengthPrefixed: Read {
+    /// Reads a frame into an `Arc<[u8]>`.
+    ///
+    /// # Errors
+    /// Fails with `InvalidData` if the length is larger than `max_len` and with `UnexpectedEof`
+    /// if the reader ends before the frame does.
+    fn read_length_prefixed_arc_slice(&mut self, max_len: usize) -> io::Result<Arc<[u8]>> {
+        read_frame(self, max_len).map(into_arc)
+    }
+
+    /// Reads a frame of UTF-8 data into an `Arc<str>`.
+    ///
+    /// # Errors
+    /// Fails like [`read_length_prefixed_arc_slice`](Self::read_length_prefixed_arc_slice),
+    /// and with `InvalidData` if the payload is not valid UTF-8.
+    fn read_length_prefixed_arc_str(&mut self, max_len: usize) -> io::Result<Arc<str>> {
+        let buf = read_frame(self, max_len)?;
+
+        if let Err(err) = str::from_utf8(buf.as_slice()) {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, err));
+        }
+
+        // SAFETY: The buffer was just checked to be valid UTF-8.
+        Ok(unsafe { into_arc_str(buf) })
+    }
+}
+
+impl<R: Read + ?Sized> ReadLengthPrefixed for R {}
+
+fn read_frame<R: Read + ?Sized>(reader: &mut R, max_len: usize) -> io::Result<ArcBuf<u8>> {
+    let mut prefix = [0; 4];
+    reader.read_exact(&mut prefix)?;
+    let len = u32::from_be_bytes(prefix) as usize;
+
+    if len > max_len {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("frame of {len} bytes is longer than the maximum of {max_len} bytes"),
+        ));
+    }
+
+    let mut buf = ArcBuf::with_capacity(len);
+    buf.extend_zeroed(len);
+    reader.read_exact(buf.as_mut_slice())?;
+    Ok(buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_length_prefixed_errors() {
+        let mut too_long: &[u8] = b"\0\0\x01\0";
+        let mut truncated: &[u8] = b"\0\0\0\x04abc";
+        let mut invalid: &[u8] = b"\0\0\0\x01\xff";
+
+        let err = too_long.read_length_prefixed_arc_slice(255).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        let err = truncated.read_length_prefixed_arc_slice(255).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+        let err = invalid.read_length_prefixed_arc_str(255).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[cfg(feature = "bytes")]
+    #[test]
+    fn test_read_length_prefixed_buf() {
+        use bytes::Buf;
+
+        let mut buf = bytes::Bytes::from_static(b"\0\0\0\0\0\0\0\x03abc!");
+        let empty = (&mut buf).reader().read_length_prefixed_arc_str(8).unwrap();
+        let abc = (&mut buf)
+            .reader()
+            .read_length_prefixed_arc_slice(8)
+            .unwrap();
+
+        assert_eq!(&*empty, "");
+        assert_eq!(&*abc, b"abc");
+        assert_eq!(buf.remaining(), 1);
+    }
+}
@@ -1,17 +1,209 @@
 #![doc = include_str!("../README.md")]
+#![cfg_attr(feature = "allocator_api", feature(allocator_api))]
 
+mod allocated_size;
+mod allocator;
 #[cfg(target_has_atomic = "ptr")]
 mod arc;
 #[cfg(target_has_atomic = "ptr")]
+mod arc_bytes;
+#[cfg(target_has_atomic = "ptr")]
+mod arc_bytes_arena;
+#[cfg(target_has_atomic = "ptr")]
+mod arc_cstr;
+#[cfg(target_has_atomic = "ptr")]
 mod arc_slice;
 #[cfg(target_has_atomic = "ptr")]
+mod arc_slice_builder;
+#[cfg(target_has_atomic = "ptr")]
+mod arc_slice_view;
+#[cfg(target_has_atomic = "ptr")]
 mod arc_str;
+#[cfg(target_has_atomic = "ptr")]
+mod arc_str_interner;
+#[cfg(target_has_atomic = "ptr")]
+mod arc_substr;
+#[cfg(feature = "archery")]
+mod archery_kind;
+mod bits;
+#[cfg(target_has_atomic = "ptr")]
+mod channel;
+mod concat;
+mod dst;
+#[cfg(target_has_atomic = "ptr")]
+mod frozen_arc_hash_map;
+#[cfg(target_has_atomic = "ptr")]
+mod frozen_arc_set;
+mod frozen_rc_map;
+mod hashed_rc_str;
+mod header_slice;
+#[cfg(feature = "hybrid-rc")]
+mod hybrid;
+mod into_char;
+#[cfg(target_has_atomic = "ptr")]
+mod length_prefixed;
+mod map;
+#[cfg(feature = "memmap2")]
+mod mmap;
+#[cfg(any(feature = "smallvec", feature = "arrayvec"))]
+mod move_into;
+mod options;
+#[cfg(feature = "rayon")]
+mod par;
+#[cfg(feature = "rayon")]
+mod parallel_arc_slice_builder;
+mod partition;
+mod pattern;
+mod raw;
 mod rc;
+mod rc_bytes;
+mod rc_cell_slice;
+mod rc_cow_slice;
+mod rc_cursor;
+mod rc_cyclic;
+mod rc_path;
 mod rc_slice;
+mod rc_slice_view;
 mod rc_str;
+mod rc_str_arena;
+mod rc_str_interner;
+mod rc_substr;
+mod read;
+#[cfg(feature = "regex")]
+mod regex_split;
+mod rev;
+#[cfg(target_has_atomic = "ptr")]
+mod scoped_fill;
+mod sorted;
+#[cfg(target_has_atomic = "ptr")]
+mod static_arc_str;
+mod static_rc_str;
+#[cfg(feature = "stats")]
+mod stats;
+#[cfg(feature = "futures")]
+mod stream_collect;
+#[cfg(feature = "string-interner")]
+mod string_interner_backend;
+#[cfg(any(test, feature = "test-util"))]
+mod test_util;
+#[cfg(target_has_atomic = "ptr")]
+mod thin_arc_slice;
+#[cfg(target_has_atomic = "ptr")]
+mod thin_arc_str;
+mod thin_rc_slice;
+mod thin_rc_str;
+#[cfg(feature = "tokio")]
+mod tokio_read;
+#[cfg(feature = "triomphe")]
+mod triomphe_arc;
+mod unique;
+mod unzip;
+#[cfg(feature = "wasm")]
+mod wasm;
+pub use allocated_size::*;
+#[cfg(target_has_atomic = "ptr")]
+pub use arc_bytes::*;
+#[cfg(target_has_atomic = "ptr")]
+pub use arc_bytes_arena::*;
+#[cfg(target_has_atomic = "ptr")]
+pub use arc_cstr::*;
 #[cfg(target_has_atomic = "ptr")]
 pub use arc_slice::*;
 #[cfg(target_has_atomic = "ptr")]
+pub use arc_slice_builder::*;
+#[cfg(target_has_atomic = "ptr")]
+pub use arc_slice_view::*;
+#[cfg(target_has_atomic = "ptr")]
 pub use arc_str::*;
+#[cfg(target_has_atomic = "ptr")]
+pub use arc_str_interner::*;
+#[cfg(target_has_atomic = "ptr")]
+pub use arc_substr::*;
+#[cfg(feature = "archery")]
+pub use archery_kind::*;
+pub use bits::*;
+#[cfg(target_has_atomic = "ptr")]
+pub use channel::*;
+pub use concat::*;
+pub use dst::{DstTail, RcDst};
+#[cfg(target_has_atomic = "ptr")]
+pub use frozen_arc_hash_map::*;
+#[cfg(target_has_atomic = "ptr")]
+pub use frozen_arc_set::*;
+pub use frozen_rc_map::*;
+pub use hashed_rc_str::*;
+pub use header_slice::*;
+#[cfg(feature = "hybrid-rc")]
+pub use hybrid::*;
+pub use into_char::IntoChar;
+#[cfg(target_has_atomic = "ptr")]
+pub use length_prefixed::*;
+pub use map::*;
+#[cfg(feature = "memmap2")]
+pub use mmap::*;
+#[cfg(any(feature = "smallvec", feature = "arrayvec"))]
+pub use move_into::*;
+pub use options::*;
+#[cfg(feature = "rayon")]
+pub use par::*;
+#[cfg(feature = "rayon")]
+pub use parallel_arc_slice_builder::*;
+pub use partition::*;
+pub use pattern::{SplitIntoSubstrs, SplitPattern};
+pub use rc_bytes::*;
+pub use rc_cell_slice::*;
+pub use rc_cow_slice::*;
+pub use rc_cursor::*;
+pub use rc_cyclic::*;
+pub use rc_path::*;
 pub use rc_slice::*;
+pub use rc_slice_view::*;
 pub use rc_str::*;
+pub use rc_str_arena::*;
+pub use rc_str_interner::*;
+pub use rc_substr::*;
+pub use read::*;
+#[cfg(feature = "regex")]
+pub use regex_split::*;
+pub use rev::*;
+#[cfg(target_has_atomic = "ptr")]
+pub use scoped_fill::*;
+pub use sorted::*;
+#[cfg(target_has_atomic = "ptr")]
+pub use static_arc_str::*;
+pub use static_rc_str::*;
+#[cfg(feature = "stats")]
+pub use stats::*;
+#[cfg(feature = "futures")]
+pub use stream_collect::*;
+#[cfg(feature = "string-interner")]
+pub use string_interner_backend::ArcStrBackend;
+#[cfg(feature = "test-util")]
+pub use test_util::*;
+#[cfg(target_has_atomic = "ptr")]
+pub use thin_arc_slice::*;
+#[cfg(target_has_atomic = "ptr")]
+pub use thin_arc_str::*;
+pub use thin_rc_slice::*;
+pub use thin_rc_str::*;
+#[cfg(feature = "tokio")]
+pub use tokio_read::*;
+#[cfg(feature = "triomphe")]
+pub use triomphe_arc::*;
+pub use unique::*;
+pub use unzip::*;
+#[cfg(feature = "wasm")]
+pub use wasm::*;
+
+#[cfg(feature = "derive")]
+pub use collect_into_rc_slice_derive::RcDst;
+
+#[doc(hidden)]
+pub mod __private {
+    #[cfg(target_has_atomic = "ptr")]
+    pub use crate::dst::{new_arc, new_arc_from_chars};
+    pub use crate::dst::{new_rc, new_rc_from_chars};
+    #[cfg(target_has_atomic = "ptr")]
+    pub use std::sync::Arc;
+    pub use std::{alloc::Layout, ptr, rc::Rc};
+}
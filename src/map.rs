@@ -0,0 +1,194 @@
+#[cfg(target_has_atomic = "ptr")]
+use crate::arc::into_arc_str;
+use crate::{
+    raw::{Prefix, RawBuf},
+    rc::{into_rc, into_rc_str, RcBuf},
+};
+use std::rc::Rc;
+#[cfg(target_has_atomic = "ptr")]
+use std::sync::Arc;
+
+/// Maps every element of `slice` into a new `Rc<[U]>`, allocated once with room for exactly as
+/// many elements, like `slice.iter().map(f).collect::<Vec<_>>().into()` without the `Vec`.
+///
+/// # Examples
+/// ```rust
+/// use collect_into_rc_slice::*;
+///
+/// let names = ["ada", "grace"].into_iter().collect_into_rc_slice();
+/// let lengths = map_rc_slice(&names, |name| name.len());
+///
+/// assert_eq!(&*lengths, &[3, 5]);
+/// ```
+pub fn map_rc_slice<T, U, F>(slice: &[T], f: F) -> Rc<[U]>
+where
+    F: FnMut(&T) -> U,
+{
+    into_rc(RcBuf::from_iter(slice.iter().map(f)))
+}
+
+/// Maps every character of `s` into a new `Rc<str>`.
+///
+/// The allocation starts with room for as many bytes as `s` has, and only grows if the mapped
+/// characters take more bytes to encode than the original ones.
+///
+/// # Examples
+/// ```rust
+/// use collect_into_rc_slice::*;
+///
+/// let s = map_rc_str("snake_case_name", |c| if c == '_' { '-' } else { c });
+///
+/// assert_eq!(&*s, "snake-case-name");
+/// ```
+pub fn map_rc_str<F>(s: &str, mut f: F) -> Rc<str>
+where
+    F: FnMut(char) -> char,
+{
+    let mut buf = RcBuf::with_capacity(s.len());
+    for c in s.chars() {
+        buf.push_char(f(c));
+    }
+
+    // SAFETY: The buffer is made up of UTF-8 encoded `char`s.
+    unsafe { into_rc_str(buf) }
+}
+
+/// Converts `s` to lowercase into a new `Rc<str>`, like `str::to_lowercase` without the `String`.
+///
+/// ASCII strings are copied as is and lowercased in place. Strings with a `'Σ'`, whose lowercase
+/// form depends on where it is in a word, go through `str::to_lowercase` first.
+///
+/// # Examples
+/// ```rust
+/// use collect_into_rc_slice::*;
+///
+/// assert_eq!(&*to_lowercase_rc("Hello, World!"), "hello, world!");
+/// assert_eq!(&*to_lowercase_rc("ὈΔΥΣΣΕΎΣ"), "ὀδυσσεύς");
+/// ```
+pub fn to_lowercase_rc(s: &str) -> Rc<str> {
+    // SAFETY: The buffer is valid UTF-8.
+    unsafe { into_rc_str(lowercase(s)) }
+}
+
+/// Converts `s` to uppercase into a new `Rc<str>`, like `str::to_uppercase` without the `String`.
+///
+/// ASCII strings are copied as is and uppercased in place.
+///
+/// # Examples
+/// ```rust
+/// use collect_into_rc_slice::*;
+///
+/// assert_eq!(&*to_uppercase_rc("Hello, World!"), "HELLO, WORLD!");
+/// assert_eq!(&*to_uppercase_rc("straße"), "STRASSE");
+/// ```
+pub fn to_uppercase_rc(s: &str) -> Rc<str> {
+    // SAFETY: The buffer is valid UTF-8.
+    unsafe { into_rc_str(uppercase(s)) }
+}
+
+/// Converts `s` to lowercase into a new `Arc<str>`, like [`to_lowercase_rc`].
+///
+/// # Examples
+/// ```rust
+/// use collect_into_rc_slice::*;
+///
+/// assert_eq!(&*to_lowercase_arc("Hello, World!"), "hello, world!");
+/// ```
+#[cfg(target_has_atomic = "ptr")]
+pub fn to_lowercase_arc(s: &str) -> Arc<str> {
+    // SAFETY: The buffer is valid UTF-8.
+    unsafe { into_arc_str(lowercase(s)) }
+}
+
+/// Converts `s` to uppercase into a new `Arc<str>`, like [`to_uppercase_rc`].
+///
+/// # Examples
+/// ```rust
+/// use collect_into_rc_slice::*;
+///
+/// assert_eq!(&*to_uppercase_arc("Hello, World!"), "HELLO, WORLD!");
+/// ```
+#[cfg(target_has_atomic = "ptr")]
+pub fn to_uppercase_arc(s: &str) -> Arc<str> {
+    // SAFETY: The buffer is valid UTF-8.
+    unsafe { into_arc_str(uppercase(s)) }
+}
+
+fn lowercase<P: Prefix>(s: &str) -> RawBuf<P, u8> {
+    if s.is_ascii() {
+        let mut buf = RawBuf::with_capacity(s.len());
+        buf.extend_from_slice(s.as_bytes());
+        buf.as_mut_slice().make_ascii_lowercase();
+        return buf;
+    }
+
+    // the final sigma rule needs the context of each character
+    if s.contains('Σ') {
+        let lower = s.to_lowercase();
+        let mut buf = RawBuf::with_capacity(lower.len());
+        buf.extend_from_slice(lower.as_bytes());
+        return buf;
+    }
+
+    let mut buf = RawBuf::with_capacity(s.len());
+    for c in s.chars().flat_map(char::to_lowercase) {
+        buf.push_char(c);
+    }
+    buf
+}
+
+fn uppercase<P: Prefix>(s: &str) -> RawBuf<P, u8> {
+    let mut buf = RawBuf::with_capacity(s.len());
+    if s.is_ascii() {
+        buf.extend_from_slice(s.as_bytes());
+        buf.as_mut_slice().make_ascii_uppercase();
+        return buf;
+    }
+
+    for c in s.chars().flat_map(char::to_uppercase) {
+        buf.push_char(c);
+    }
+    buf
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_map_rc_slice() {
+        let rc: Rc<[u16]> = Rc::from([1, 2, 3]);
+        let mapped = map_rc_slice(&rc, |&n| n.to_string());
+
+        assert_eq!(&*mapped, ["1", "2", "3"]);
+        assert!(map_rc_slice(&[] as &[u8], |&b| b).is_empty());
+    }
+
+    #[test]
+    fn test_map_rc_str_longer_encoding() {
+        // 'a' takes 1 byte and 'α' takes 2
+        let s = map_rc_str("abab", |c| if c == 'a' { 'α' } else { c });
+        assert_eq!(&*s, "αbαb");
+
+        let s = map_rc_str("αβ", |c| {
+            char::from_u32(c as u32 - 0x3b1 + 'a' as u32).unwrap()
+        });
+        assert_eq!(&*s, "ab");
+    }
+
+    #[test]
+    fn test_case_conversions_match_std() {
+        for s in [
+            "",
+            "ASCII only",
+            "ÀÉÎõü",
+            "İstanbul",
+            "ΣΑΣ ΟΔΟΣ",
+            "ﬁ ß ǅ",
+            "日本語",
+        ] {
+            assert_eq!(&*to_lowercase_rc(s), s.to_lowercase());
+            assert_eq!(&*to_uppercase_rc(s), s.to_uppercase());
+        }
+    }
+}
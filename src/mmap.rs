@@ -0,0 +1,97 @@
+#![cfg(target_has_atomic = "ptr")]
+use crate::arc::{into_arc, ArcBuf};
+use memmap2::MmapOptions;
+use std::{
+    fs::File,
+    io::{self, ErrorKind},
+    ops::Range,
+    path::Path,
+    sync::Arc,
+};
+
+/// Copies the bytes in `range` of the file at `path` into an `Arc<[u8]>`, through a memory map of
+/// just that region.
+///
+/// The allocation is made once at the length of the range and the region is copied into it in one
+/// go, so it is a snapshot of the file that can be shared after the map is gone.
+///
+/// [`read_exact_at_to_arc_bytes`](crate::read_exact_at_to_arc_bytes) makes the same copy with
+/// positioned reads, which is safe whatever happens to the file meanwhile.
+///
+/// # Safety
+/// The file must not be truncated or modified, by this process or another one, until the function
+/// returns. Reading a page of the map that was truncated away raises `SIGBUS` on Unix, and the
+/// mapped bytes changing while they are copied is a data race.
+///
+/// # Errors
+/// Fails with an error of kind `InvalidInput` if the range starts after it ends, with one of kind
+/// `UnexpectedEof` if it ends past the end of the file, and with the error of the file if opening
+/// or mapping it fails.
+///
+/// # Examples
+/// ```rust,no_run
+/// use collect_into_rc_slice::*;
+///
+/// // the header of a database page
+/// // SAFETY: Nothing writes to the database file while it is open.
+/// let header = unsafe { mmap_region_to_arc_bytes("pages.db", 4096..4096 + 100) }.unwrap();
+///
+/// assert_eq!(header.len(), 100);
+/// ```
+pub unsafe fn mmap_region_to_arc_bytes<P: AsRef<Path>>(
+    path: P,
+    range: Range<u64>,
+) -> io::Result<Arc<[u8]>> {
+    let len = range
+        .end
+        .checked_sub(range.start)
+        .and_then(|len| usize::try_from(len).ok())
+        .ok_or_else(|| io::Error::new(ErrorKind::InvalidInput, "invalid file range"))?;
+
+    let file = File::open(path)?;
+    if range.end > file.metadata()?.len() {
+        return Err(ErrorKind::UnexpectedEof.into());
+    }
+    // an empty region can't be mapped
+    if len == 0 {
+        return Ok(Arc::from([]));
+    }
+
+    // SAFETY: The caller guarantees that the file isn't truncated or modified while the map is
+    // read from, and the map is dropped before returning.
+    let map = unsafe { MmapOptions::new().offset(range.start).len(len).map(&file)? };
+
+    let mut buf = ArcBuf::with_capacity(len);
+    buf.extend_from_slice(&map);
+    Ok(into_arc(buf))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg_attr(miri, ignore = "miri can't map files")]
+    #[test]
+    fn test_mmap_region_to_arc_bytes() {
+        // SAFETY: The file is only written by this test, before it is mapped.
+        let mmap_region_to_arc_bytes =
+            |path, range| unsafe { mmap_region_to_arc_bytes(path, range) };
+
+        let path = std::env::temp_dir().join(format!("mmap_test_{}", std::process::id()));
+        let data: Vec<u8> = (0..10_000).map(|i| i as u8).collect();
+        std::fs::write(&path, &data).unwrap();
+
+        // an offset that isn't page aligned
+        let region = mmap_region_to_arc_bytes(&path, 4097..9000).unwrap();
+        assert_eq!(&*region, &data[4097..9000]);
+        assert!(mmap_region_to_arc_bytes(&path, 10..10).unwrap().is_empty());
+
+        let past_end = mmap_region_to_arc_bytes(&path, 9000..10_001).unwrap_err();
+        assert_eq!(past_end.kind(), ErrorKind::UnexpectedEof);
+        #[allow(clippy::reversed_empty_ranges)]
+        let reversed = mmap_region_to_arc_bytes(&path, 10..5).unwrap_err();
+        assert_eq!(reversed.kind(), ErrorKind::InvalidInput);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
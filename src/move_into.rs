@@ -0,0 +1,142 @@
+#[cfg(target_has_atomic = "ptr")]
+use crate::arc::into_arc;
+use crate::{
+    raw::{Prefix, RawBuf},
+    rc::into_rc,
+};
+#[cfg(target_has_atomic = "ptr")]
+use std::sync::Arc;
+use std::{ptr, rc::Rc};
+
+/// Copies `len` elements from `src` into a new buffer of exactly that length.
+///
+/// # Safety
+/// `src` must point to `len` initialized elements, which the caller has to forget once they are
+/// moved.
+#[inline]
+unsafe fn move_slice<P: Prefix, T>(src: *const T, len: usize) -> RawBuf<P, T> {
+    let mut buf = RawBuf::with_capacity(len);
+    ptr::copy_nonoverlapping(src, buf.data_ptr(), len);
+    buf.set_len(len);
+    buf
+}
+
+mod sealed {
+    /// A container that owns its elements in one contiguous slice.
+    pub trait Contiguous<T> {
+        /// Calls `take` with a pointer to the elements and their number, after which the
+        /// container forgets them, so `take` has to move them out.
+        fn take_slice<R>(self, take: impl FnOnce(*const T, usize) -> R) -> R;
+    }
+}
+
+use sealed::Contiguous;
+
+#[cfg(feature = "smallvec")]
+impl<A: smallvec::Array> Contiguous<A::Item> for smallvec::SmallVec<A> {
+    fn take_slice<R>(mut self, take: impl FnOnce(*const A::Item, usize) -> R) -> R {
+        let taken = take(self.as_ptr(), self.len());
+        // SAFETY: The elements were moved out, and the `SmallVec` only frees its allocation when
+        // it is dropped.
+        unsafe { self.set_len(0) };
+        taken
+    }
+}
+
+#[cfg(feature = "arrayvec")]
+impl<T, const CAP: usize> Contiguous<T> for arrayvec::ArrayVec<T, CAP> {
+    fn take_slice<R>(mut self, take: impl FnOnce(*const T, usize) -> R) -> R {
+        let taken = take(self.as_ptr(), self.len());
+        // SAFETY: The elements were moved out, and the `ArrayVec` doesn't own anything else.
+        unsafe { self.set_len(0) };
+        taken
+    }
+}
+
+/// Moves the elements of a contiguous container into an `Rc<[T]>` with a single copy.
+///
+/// `collect_into_rc_slice` moves the elements one by one through the iterator of the container,
+/// which the compiler can't always turn into one copy, e.g. for the iterator of a `SmallVec` that
+/// checks whether it is inline on every step. This takes the elements out of the container as one
+/// slice instead. It is implemented for `SmallVec` and `ArrayVec` with the `smallvec` and
+/// `arrayvec` features. A `Vec<T>` or `Box<[T]>` is already turned into an `Rc<[T]>` with one copy
+/// by `Rc::from`.
+pub trait MoveIntoRcSlice<T>: Contiguous<T> {
+    /// # Examples
+    /// ```rust
+    /// # #[cfg(feature = "smallvec")] {
+    /// use collect_into_rc_slice::*;
+    /// use smallvec::SmallVec;
+    ///
+    /// let names: SmallVec<[String; 2]> = ["a", "b"].into_iter().map(String::from).collect();
+    /// let rc = names.move_into_rc_slice();
+    ///
+    /// assert_eq!(&*rc, ["a", "b"]);
+    /// # }
+    /// ```
+    fn move_into_rc_slice(self) -> Rc<[T]>;
+}
+
+impl<T, C: Contiguous<T>> MoveIntoRcSlice<T> for C {
+    #[inline]
+    fn move_into_rc_slice(self) -> Rc<[T]> {
+        // SAFETY: `take_slice` passes the elements of the container, which forgets them after.
+        into_rc(self.take_slice(|src, len| unsafe { move_slice(src, len) }))
+    }
+}
+
+/// Moves the elements of a contiguous container into an `Arc<[T]>` with a single copy, see
+/// [`MoveIntoRcSlice`].
+#[cfg(target_has_atomic = "ptr")]
+pub trait MoveIntoArcSlice<T>: Contiguous<T> {
+    /// # Examples
+    /// ```rust
+    /// # #[cfg(feature = "arrayvec")] {
+    /// use arrayvec::ArrayVec;
+    /// use collect_into_rc_slice::*;
+    ///
+    /// let arc = ArrayVec::from([1, 2, 3]).move_into_arc_slice();
+    ///
+    /// assert_eq!(&*arc, [1, 2, 3]);
+    /// # }
+    /// ```
+    fn move_into_arc_slice(self) -> Arc<[T]>;
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl<T, C: Contiguous<T>> MoveIntoArcSlice<T> for C {
+    #[inline]
+    fn move_into_arc_slice(self) -> Arc<[T]> {
+        // SAFETY: `take_slice` passes the elements of the container, which forgets them after.
+        into_arc(self.take_slice(|src, len| unsafe { move_slice(src, len) }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(all(feature = "smallvec", target_has_atomic = "ptr"))]
+    #[test]
+    fn test_move_into_arc_slice_smallvec() {
+        let mut inline = smallvec::SmallVec::<[String; 4]>::new();
+        inline.push("inline".to_string());
+        let spilled: smallvec::SmallVec<[String; 1]> =
+            ["x", "y", "z"].into_iter().map(String::from).collect();
+        assert!(spilled.spilled());
+
+        assert_eq!(&*inline.move_into_arc_slice(), ["inline"]);
+        assert_eq!(&*spilled.move_into_arc_slice(), ["x", "y", "z"]);
+    }
+
+    #[cfg(feature = "arrayvec")]
+    #[test]
+    fn test_move_into_rc_slice_arrayvec() {
+        let mut array = arrayvec::ArrayVec::<String, 8>::new();
+        array.push("array".to_string());
+        assert_eq!(&*array.move_into_rc_slice(), ["array"]);
+        assert!(arrayvec::ArrayVec::<String, 8>::new()
+            .move_into_rc_slice()
+            .is_empty());
+    }
+}
@@ -0,0 +1,341 @@
+#[cfg(target_has_atomic = "ptr")]
+use crate::arc::{into_arc, into_arc_str, try_into_arc, try_into_arc_str, ArcBuf};
+use crate::{
+    raw::{capacity_overflow, default_min_capacity},
+    rc::{into_rc, into_rc_str, try_into_rc, try_into_rc_str, RcBuf},
+    IntoChar,
+};
+#[cfg(target_has_atomic = "ptr")]
+use std::sync::Arc;
+use std::{
+    alloc::{handle_alloc_error, Layout},
+    error::Error,
+    fmt,
+    rc::Rc,
+};
+
+/// What a collection does when the memory for it can't be allocated.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum OomPolicy {
+    /// Calls `handle_alloc_error`, which aborts the process, like the plain collectors do.
+    #[default]
+    Abort,
+    /// Drops the elements that were collected so far and returns an [`AllocError`], e.g. for
+    /// loading data of an untrusted size that the process should survive running out of memory on.
+    Error,
+}
+
+/// Options for the `collect_*_with` collectors of slices and strings.
+///
+/// The builders, like [`ArcSliceBuilder`](crate::ArcSliceBuilder), always abort when they run out
+/// of memory.
+///
+/// # Examples
+/// ```rust
+/// use collect_into_rc_slice::*;
+///
+/// let options = CollectOptions::new().on_oom(OomPolicy::Error);
+/// let rc = (0..10).collect_into_rc_slice_with(options);
+///
+/// assert_eq!(rc.as_deref(), Ok(&[0, 1, 2, 3, 4, 5, 6, 7, 8, 9][..]));
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct CollectOptions {
+    on_oom: OomPolicy,
+    min_capacity: Option<usize>,
+}
+
+impl CollectOptions {
+    /// The options the plain collectors use.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            on_oom: OomPolicy::Abort,
+            min_capacity: None,
+        }
+    }
+
+    #[inline]
+    pub const fn on_oom(mut self, policy: OomPolicy) -> Self {
+        self.on_oom = policy;
+        self
+    }
+
+    #[inline]
+    pub const fn oom_policy(&self) -> OomPolicy {
+        self.on_oom
+    }
+
+    /// Sets the number of elements the allocation makes room for at least once it has to grow, or
+    /// when the size hint of the iterator has no upper bound.
+    ///
+    /// By default, it is as many elements as fit in 64 bytes, or 1 if they are bigger than that.
+    /// Raising it trades memory that is trimmed off at the end for fewer reallocations early on,
+    /// and setting it to 1 makes the allocation grow one element at a time at first, like it
+    /// would without a minimum.
+    #[inline]
+    pub const fn min_capacity(mut self, elements: usize) -> Self {
+        self.min_capacity = Some(elements);
+        self
+    }
+
+    /// The minimum capacity for a buffer of `T`s.
+    fn min_capacity_for<T>(&self) -> usize {
+        self.min_capacity.unwrap_or_else(default_min_capacity::<T>)
+    }
+}
+
+/// The error returned by a collection with [`OomPolicy::Error`] when its memory can't be
+/// allocated.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AllocError {
+    layout: Option<Layout>,
+}
+
+impl AllocError {
+    #[inline]
+    pub(crate) fn new(layout: Layout) -> Self {
+        Self {
+            layout: Some(layout),
+        }
+    }
+
+    #[inline]
+    pub(crate) fn capacity_overflow() -> Self {
+        Self { layout: None }
+    }
+
+    /// The layout of the allocation that failed, or `None` if its size would have overflowed.
+    #[inline]
+    pub fn layout(&self) -> Option<Layout> {
+        self.layout
+    }
+
+    /// Does what the infallible collectors do instead of returning the error.
+    #[cold]
+    pub(crate) fn handle(self) -> ! {
+        match self.layout {
+            Some(layout) => handle_alloc_error(layout),
+            None => capacity_overflow(),
+        }
+    }
+}
+
+impl fmt::Display for AllocError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.layout {
+            Some(layout) => write!(f, "memory allocation of {} bytes failed", layout.size()),
+            None => f.write_str("capacity overflow"),
+        }
+    }
+}
+
+impl Error for AllocError {}
+
+pub trait CollectIntoRcSliceWith<T> {
+    /// Collects the iterator into an `Rc<[T]>` like `collect_into_rc_slice`, handling a failed
+    /// allocation as `options` say.
+    ///
+    /// With [`OomPolicy::Abort`], the result is always `Ok`.
+    fn collect_into_rc_slice_with(self, options: CollectOptions) -> Result<Rc<[T]>, AllocError>;
+}
+
+impl<T, I> CollectIntoRcSliceWith<T> for I
+where
+    I: IntoIterator<Item = T>,
+{
+    fn collect_into_rc_slice_with(self, options: CollectOptions) -> Result<Rc<[T]>, AllocError> {
+        let buf = RcBuf::try_from_iter_in(
+            self.into_iter(),
+            Default::default(),
+            options.min_capacity_for::<T>(),
+        );
+        match options.on_oom {
+            OomPolicy::Abort => Ok(into_rc(buf.unwrap_or_else(|err| err.handle()))),
+            OomPolicy::Error => try_into_rc(buf?),
+        }
+    }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+pub trait CollectIntoArcSliceWith<T> {
+    /// Collects the iterator into an `Arc<[T]>` like `collect_into_arc_slice`, handling a failed
+    /// allocation as `options` say.
+    ///
+    /// With [`OomPolicy::Abort`], the result is always `Ok`.
+    fn collect_into_arc_slice_with(self, options: CollectOptions) -> Result<Arc<[T]>, AllocError>;
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl<T, I> CollectIntoArcSliceWith<T> for I
+where
+    I: IntoIterator<Item = T>,
+{
+    fn collect_into_arc_slice_with(self, options: CollectOptions) -> Result<Arc<[T]>, AllocError> {
+        let buf = ArcBuf::try_from_iter_in(
+            self.into_iter(),
+            Default::default(),
+            options.min_capacity_for::<T>(),
+        );
+        match options.on_oom {
+            OomPolicy::Abort => Ok(into_arc(buf.unwrap_or_else(|err| err.handle()))),
+            OomPolicy::Error => try_into_arc(buf?),
+        }
+    }
+}
+
+pub trait CollectIntoRcStrWith {
+    /// Collects the iterator into an `Rc<str>` like `collect_into_rc_str`, handling a failed
+    /// allocation as `options` say, with the minimum capacity counted in bytes.
+    ///
+    /// With [`OomPolicy::Abort`], the result is always `Ok`.
+    fn collect_into_rc_str_with(self, options: CollectOptions) -> Result<Rc<str>, AllocError>;
+}
+
+impl<I> CollectIntoRcStrWith for I
+where
+    I: IntoIterator,
+    I::Item: IntoChar,
+{
+    fn collect_into_rc_str_with(self, options: CollectOptions) -> Result<Rc<str>, AllocError> {
+        let buf = RcBuf::try_from_chars(
+            self.into_iter().map(IntoChar::into_char),
+            options.min_capacity_for::<u8>(),
+        );
+        // SAFETY: The buffer is made up of UTF-8 encoded `char`s.
+        unsafe {
+            match options.on_oom {
+                OomPolicy::Abort => Ok(into_rc_str(buf.unwrap_or_else(|err| err.handle()))),
+                OomPolicy::Error => try_into_rc_str(buf?),
+            }
+        }
+    }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+pub trait CollectIntoArcStrWith {
+    /// Collects the iterator into an `Arc<str>` like `collect_into_arc_str`, handling a failed
+    /// allocation as `options` say, with the minimum capacity counted in bytes.
+    ///
+    /// With [`OomPolicy::Abort`], the result is always `Ok`.
+    fn collect_into_arc_str_with(self, options: CollectOptions) -> Result<Arc<str>, AllocError>;
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl<I> CollectIntoArcStrWith for I
+where
+    I: IntoIterator,
+    I::Item: IntoChar,
+{
+    fn collect_into_arc_str_with(self, options: CollectOptions) -> Result<Arc<str>, AllocError> {
+        let buf = ArcBuf::try_from_chars(
+            self.into_iter().map(IntoChar::into_char),
+            options.min_capacity_for::<u8>(),
+        );
+        // SAFETY: The buffer is made up of UTF-8 encoded `char`s.
+        unsafe {
+            match options.on_oom {
+                OomPolicy::Abort => Ok(into_arc_str(buf.unwrap_or_else(|err| err.handle()))),
+                OomPolicy::Error => try_into_arc_str(buf?),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::fail_allocs_after;
+    use std::cell::Cell;
+
+    const ERROR: CollectOptions = CollectOptions::new().on_oom(OomPolicy::Error);
+
+    #[test]
+    fn test_collect_with_error_policy() {
+        let _guard = fail_allocs_after(0);
+        let err = [1u64, 2, 3].collect_into_rc_slice_with(ERROR).unwrap_err();
+
+        assert_eq!(err.layout(), Some(RcBuf::<u64>::layout(3)));
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "memory allocation of {} bytes failed",
+                2 * size_of::<usize>() + 24
+            )
+        );
+    }
+
+    #[test]
+    fn test_collect_with_error_policy_drops_collected() {
+        struct Counted<'a>(&'a Cell<usize>);
+
+        impl Drop for Counted<'_> {
+            fn drop(&mut self) {
+                self.0.set(self.0.get() + 1);
+            }
+        }
+
+        let drops = Cell::new(0);
+        let mut n = 0;
+        let items = std::iter::from_fn(|| {
+            n += 1;
+            (n <= 5).then(|| Counted(&drops))
+        });
+
+        // the buffer is allocated with room for 1 element and grows to 2 and 4, then fails to grow
+        // to 8
+        let guard = fail_allocs_after(3);
+        assert!(items
+            .collect_into_rc_slice_with(ERROR.min_capacity(1))
+            .is_err());
+        drop(guard);
+        // the fifth element was dropped along with the four that were collected
+        assert_eq!(drops.get(), 5);
+
+        let too_many = std::iter::repeat_n(0u64, usize::MAX / 4);
+        let err = too_many.collect_into_rc_slice_with(ERROR).unwrap_err();
+        assert_eq!(err.layout(), None);
+    }
+
+    #[test]
+    fn test_min_capacity() {
+        let mut buf = RcBuf::<u8>::with_capacity(0);
+        buf.push(1);
+        assert_eq!(buf.capacity(), 64);
+
+        let mut buf = RcBuf::<[u8; 100]>::with_capacity(0);
+        buf.push([0; 100]);
+        assert_eq!(buf.capacity(), 1);
+
+        // the upper bound is known, so the minimum doesn't matter until the buffer grows
+        let options = CollectOptions::new().min_capacity(1000);
+        let rc = (0..10).collect_into_rc_slice_with(options).unwrap();
+        assert_eq!(rc.len(), 10);
+    }
+
+    #[test]
+    fn test_collect_into_rc_str_with_error_policy() {
+        let rc = "héllo".chars().collect_into_rc_str_with(ERROR);
+        assert_eq!(rc.as_deref(), Ok("héllo"));
+
+        // the 40 characters make room for 40 bytes, and growing for the 21st one fails
+        let _guard = fail_allocs_after(1);
+        let err = std::iter::repeat_n('é', 40)
+            .collect_into_rc_str_with(ERROR)
+            .unwrap_err();
+        assert_eq!(err.layout(), Some(RcBuf::<u8>::layout(80)));
+    }
+
+    #[cfg(target_has_atomic = "ptr")]
+    #[test]
+    fn test_collect_into_arc_slice_with_failed_shrink() {
+        // `filter` makes room for every element, so trimming the allocation is what fails
+        let _guard = fail_allocs_after(1);
+        let err = (0..10u8)
+            .filter(|i| i % 2 == 0)
+            .collect_into_arc_slice_with(ERROR)
+            .unwrap_err();
+
+        assert_eq!(err.layout(), Some(ArcBuf::<u8>::layout(5)));
+    }
+}
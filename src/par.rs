@@ -0,0 +1,391 @@
+#[cfg(target_has_atomic = "ptr")]
+use crate::arc::{into_arc, into_arc_str, ArcBuf};
+use crate::raw::{Prefix, RawBuf};
+use rayon::prelude::*;
+#[cfg(target_has_atomic = "ptr")]
+use std::sync::Arc;
+use std::{
+    collections::LinkedList,
+    ptr,
+    str::{self, Utf8Error},
+};
+
+/// The size of the buffers past which they are validated as UTF-8 in parallel.
+const PARALLEL_UTF8_THRESHOLD: usize = 1024 * 1024;
+/// The size of the chunks validated as UTF-8 by each worker.
+const UTF8_CHUNK_SIZE: usize = 256 * 1024;
+
+/// A pointer into a buffer that the workers write disjoint ranges of.
+pub(crate) struct SendPtr<T>(*mut T);
+
+impl<T> SendPtr<T> {
+    #[inline]
+    pub(crate) fn new(ptr: *mut T) -> Self {
+        Self(ptr)
+    }
+
+    /// The pointer, taken through a method so closures capture the whole `SendPtr`.
+    #[inline]
+    pub(crate) fn get(&self) -> *mut T {
+        self.0
+    }
+}
+
+// SAFETY: The workers only ever write to disjoint ranges through the pointer.
+unsafe impl<T: Send> Send for SendPtr<T> {}
+unsafe impl<T: Send> Sync for SendPtr<T> {}
+
+/// Collects a parallel iterator into a new buffer.
+///
+/// Each worker collects its part of the iterator into a `Vec<T>` of its own, and once they are
+/// all done the segments are moved into a single buffer, each by a worker of its own.
+pub(crate) fn from_par_iter<P, T, I>(iter: I) -> RawBuf<P, T>
+where
+    P: Prefix,
+    T: Send,
+    I: ParallelIterator<Item = T>,
+{
+    let segments = iter
+        .fold(Vec::new, |mut segment, item| {
+            segment.push(item);
+            segment
+        })
+        .map(|segment| LinkedList::from([segment]))
+        .reduce(LinkedList::new, |mut a, mut b| {
+            a.append(&mut b);
+            a
+        });
+
+    concat_segments(segments)
+}
+
+/// Moves `segments` into a new buffer, one after the other, each segment being moved by a worker
+/// of its own.
+pub(crate) fn concat_segments<P, T, S>(segments: S) -> RawBuf<P, T>
+where
+    P: Prefix,
+    T: Send,
+    S: IntoIterator<Item = Vec<T>>,
+{
+    let mut offset = 0;
+    let segments: Vec<_> = segments
+        .into_iter()
+        .map(|segment| {
+            let start = offset;
+            offset += segment.len();
+            (segment, start)
+        })
+        .collect();
+
+    let mut buf = RawBuf::<P, T>::with_capacity(offset);
+    let dst = SendPtr::new(buf.data_ptr());
+    segments.into_par_iter().for_each(|(mut segment, start)| {
+        // SAFETY:
+        // - The segments are moved into disjoint ranges of the buffer, which has room for all of
+        //   them.
+        // - The elements are forgotten by the segment once they are moved out of it.
+        unsafe {
+            ptr::copy_nonoverlapping(segment.as_ptr(), dst.get().add(start), segment.len());
+            segment.set_len(0);
+        }
+    });
+
+    // SAFETY: All the segments were moved into the buffer.
+    unsafe { buf.set_len(offset) };
+    buf
+}
+
+/// Concatenates the strings of a parallel iterator into an `Arc<str>`, copying them into the
+/// allocation in parallel.
+///
+/// The strings are collected first, along with their lengths, which gives the offset of each
+/// string in the result, and then every string is copied to its offset by a worker of its own.
+///
+/// # Examples
+/// ```rust
+/// use collect_into_rc_slice::*;
+/// use rayon::prelude::*;
+///
+/// let csv = par_concat_into_arc_str((0..5).into_par_iter().map(|i| format!("{i},")));
+///
+/// assert_eq!(&*csv, "0,1,2,3,4,");
+/// ```
+#[cfg(target_has_atomic = "ptr")]
+pub fn par_concat_into_arc_str<I>(iter: I) -> Arc<str>
+where
+    I: IntoParallelIterator,
+    I::Item: AsRef<str>,
+{
+    let chunks: Vec<_> = iter
+        .into_par_iter()
+        .map(|chunk| (chunk.as_ref().len(), chunk))
+        .collect();
+
+    let mut len = 0;
+    let offsets: Vec<_> = chunks
+        .iter()
+        .map(|&(chunk_len, _)| {
+            let offset = len;
+            len += chunk_len;
+            offset
+        })
+        .collect();
+
+    let mut buf = ArcBuf::<u8>::with_capacity(len);
+    let dst = SendPtr::new(buf.data_ptr());
+    chunks
+        .into_par_iter()
+        .zip(offsets)
+        .for_each(|((chunk_len, chunk), offset)| {
+            let chunk = chunk.as_ref();
+            assert_eq!(chunk.len(), chunk_len, "the string changed length");
+            // SAFETY: The strings are copied into disjoint ranges of the buffer, which has room
+            // for all of them.
+            unsafe { ptr::copy_nonoverlapping(chunk.as_ptr(), dst.get().add(offset), chunk_len) };
+        });
+
+    // SAFETY: The buffer is a concatenation of `str`s, which were all copied into it.
+    unsafe {
+        buf.set_len(len);
+        into_arc_str(buf)
+    }
+}
+
+/// Concatenates `chunks` into an `Arc<[T]>`, copying them into the allocation in parallel.
+///
+/// The allocation is made once with the total length of the chunks, and each chunk is then copied
+/// to its offset by a worker of its own, which makes this the fastest way to merge the shards of
+/// a result into a single shared buffer.
+///
+/// # Examples
+/// ```rust
+/// use collect_into_rc_slice::*;
+///
+/// let shards = [vec![1, 2], vec![], vec![3, 4, 5]];
+/// let merged = par_concat_into_arc_slice(&shards);
+///
+/// assert_eq!(&*merged, &[1, 2, 3, 4, 5]);
+/// ```
+#[cfg(target_has_atomic = "ptr")]
+pub fn par_concat_into_arc_slice<T, S>(chunks: &[S]) -> Arc<[T]>
+where
+    T: Copy + Send + Sync,
+    S: AsRef<[T]> + Sync,
+{
+    let mut len = 0;
+    let spans: Vec<_> = chunks
+        .iter()
+        .map(|chunk| {
+            let offset = len;
+            len += chunk.as_ref().len();
+            (offset, len - offset)
+        })
+        .collect();
+
+    let mut buf = ArcBuf::<T>::with_capacity(len);
+    let dst = SendPtr::new(buf.data_ptr());
+    chunks
+        .par_iter()
+        .zip(spans)
+        .for_each(|(chunk, (offset, chunk_len))| {
+            let chunk = chunk.as_ref();
+            assert_eq!(chunk.len(), chunk_len, "the chunk changed length");
+            // SAFETY: The chunks are copied into disjoint ranges of the buffer, which has room for
+            // all of them.
+            unsafe { ptr::copy_nonoverlapping(chunk.as_ptr(), dst.get().add(offset), chunk_len) };
+        });
+
+    // SAFETY: All the chunks were copied into the buffer.
+    unsafe { buf.set_len(len) };
+    into_arc(buf)
+}
+
+#[cfg(target_has_atomic = "ptr")]
+pub trait ParCollectIntoArcSlice<T> {
+    /// Collects the parallel iterator into an `Arc<[T]>`, with the workers writing the items
+    /// straight to their index in the allocation.
+    ///
+    /// Since the length of the iterator is known upfront, the allocation is made once with that
+    /// length, and unlike collecting an [`ArcSlice`](crate::ArcSlice) no segments are collected
+    /// on the side. If a worker panics, the items that were already written are leaked.
+    ///
+    /// # Panics
+    /// Panics if the iterator produces fewer items than its length, leaking the items it produced.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use collect_into_rc_slice::*;
+    /// use rayon::prelude::*;
+    ///
+    /// let squares = (0..1000u32).into_par_iter().map(|i| i * i).par_collect_into_arc_slice();
+    ///
+    /// assert_eq!(squares.len(), 1000);
+    /// assert_eq!(squares[999], 998_001);
+    /// ```
+    fn par_collect_into_arc_slice(self) -> Arc<[T]>;
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl<T, I> ParCollectIntoArcSlice<T> for I
+where
+    T: Send,
+    I: IndexedParallelIterator<Item = T>,
+{
+    fn par_collect_into_arc_slice(self) -> Arc<[T]> {
+        let len = self.len();
+        let mut buf = ArcBuf::<T>::with_capacity(len);
+
+        // the indices of an `IndexedParallelIterator` can't be trusted, since it is a safe trait,
+        // so every item is paired with a slot of its own instead
+        let written = buf.spare_capacity_mut()[..len]
+            .par_iter_mut()
+            .zip(self)
+            .map(|(slot, item)| {
+                slot.write(item);
+            })
+            .count();
+        assert_eq!(
+            written, len,
+            "the iterator produced fewer items than its length"
+        );
+
+        // SAFETY: Each of the `len` slots was written once.
+        unsafe { buf.set_len(len) };
+        into_arc(buf)
+    }
+}
+
+/// Validates `bytes` as UTF-8, in parallel if they are large enough.
+pub(crate) fn validate_utf8(bytes: &[u8]) -> Result<(), Utf8Error> {
+    if bytes.len() < PARALLEL_UTF8_THRESHOLD {
+        return str::from_utf8(bytes).map(drop);
+    }
+
+    validate_utf8_chunks(bytes, UTF8_CHUNK_SIZE)
+}
+
+/// Validates `bytes` as UTF-8 in chunks of about `chunk_size` bytes.
+///
+/// The chunks are split before a byte that starts a code point, so a valid buffer is made up of
+/// valid chunks. The position of an error is only known relative to its chunk though, so if any of
+/// them is invalid, the whole buffer is validated again to get the error.
+fn validate_utf8_chunks(bytes: &[u8], chunk_size: usize) -> Result<(), Utf8Error> {
+    let mut chunks = Vec::with_capacity(bytes.len() / chunk_size + 1);
+    let mut rest = bytes;
+    while !rest.is_empty() {
+        let mut mid = rest.len().min(chunk_size);
+        // a code point is at most 4 bytes long, so it has at most 3 continuation bytes
+        for _ in 0..3 {
+            if mid < rest.len() && mid > 1 && is_continuation(rest[mid]) {
+                mid -= 1;
+            }
+        }
+
+        let (chunk, tail) = rest.split_at(mid);
+        chunks.push(chunk);
+        rest = tail;
+    }
+
+    if chunks.par_iter().all(|chunk| str::from_utf8(chunk).is_ok()) {
+        Ok(())
+    } else {
+        str::from_utf8(bytes).map(drop)
+    }
+}
+
+#[inline]
+fn is_continuation(byte: u8) -> bool {
+    byte & 0b1100_0000 == 0b1000_0000
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(target_has_atomic = "ptr")]
+    #[test]
+    fn test_par_concat_into_arc_str() {
+        let words: Vec<String> = (0..1000).map(|i| format!("{i}✓")).collect();
+        let concat = par_concat_into_arc_str(words.par_iter());
+        assert_eq!(*concat, words.concat());
+
+        let empty = par_concat_into_arc_str(rayon::iter::empty::<&str>());
+        assert_eq!(&*empty, "");
+    }
+
+    #[cfg(target_has_atomic = "ptr")]
+    #[test]
+    fn test_par_concat_into_arc_slice() {
+        let shards: Vec<Vec<u16>> = (0..100).map(|i| (0..i).collect()).collect();
+        let merged = par_concat_into_arc_slice(&shards);
+        assert_eq!(*merged, shards.concat());
+
+        let slices: [&[[u8; 3]]; 2] = [&[[1; 3]], &[]];
+        assert_eq!(&*par_concat_into_arc_slice(&slices), &[[1; 3]]);
+    }
+
+    #[cfg(target_has_atomic = "ptr")]
+    #[test]
+    fn test_par_collect_into_arc_slice_in_place() {
+        let strings: Vec<String> = (0..1000).map(|i| i.to_string()).collect();
+        let arc = strings
+            .par_iter()
+            .cloned()
+            .rev()
+            .par_collect_into_arc_slice();
+
+        assert_eq!(arc.len(), 1000);
+        assert!(arc.iter().rev().eq(strings.iter()));
+        assert!(rayon::iter::empty::<u8>()
+            .par_collect_into_arc_slice()
+            .is_empty());
+    }
+
+    #[cfg(target_has_atomic = "ptr")]
+    #[test]
+    #[should_panic(expected = "fewer items than its length")]
+    fn test_par_collect_into_arc_slice_wrong_len() {
+        use rayon::iter::plumbing::{bridge, Consumer, ProducerCallback, UnindexedConsumer};
+
+        // claims one item more than it produces
+        struct Lying<I>(I);
+
+        impl<I: IndexedParallelIterator> ParallelIterator for Lying<I> {
+            type Item = I::Item;
+
+            fn drive_unindexed<C: UnindexedConsumer<I::Item>>(self, consumer: C) -> C::Result {
+                bridge(self, consumer)
+            }
+        }
+
+        impl<I: IndexedParallelIterator> IndexedParallelIterator for Lying<I> {
+            fn len(&self) -> usize {
+                self.0.len() + 1
+            }
+
+            fn drive<C: Consumer<I::Item>>(self, consumer: C) -> C::Result {
+                bridge(self, consumer)
+            }
+
+            fn with_producer<CB: ProducerCallback<I::Item>>(self, callback: CB) -> CB::Output {
+                self.0.with_producer(callback)
+            }
+        }
+
+        let strings: Vec<String> = (0..100).map(|i| i.to_string()).collect();
+        Lying(strings.into_par_iter()).par_collect_into_arc_slice();
+    }
+
+    #[test]
+    fn test_validate_utf8_chunks() {
+        let text = "ascii, ünïcödé, ✓✓✓ and 🦀🦀🦀 ".repeat(10);
+        for chunk_size in 1..8 {
+            assert_eq!(validate_utf8_chunks(text.as_bytes(), chunk_size), Ok(()));
+        }
+
+        let mut invalid = text.clone().into_bytes();
+        invalid[100] = 0xff;
+        let err = validate_utf8_chunks(&invalid, 7).unwrap_err();
+        assert_eq!(err, str::from_utf8(&invalid).unwrap_err());
+    }
+}
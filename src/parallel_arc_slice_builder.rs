@@ -0,0 +1,187 @@
+#![cfg(target_has_atomic = "ptr")]
+use crate::{arc::into_arc, par::concat_segments};
+use std::{
+    fmt,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+/// A builder for an `Arc<[T]>` shared by a pool of workers, each of them appending to a segment
+/// of its own.
+///
+/// Workers get a [`SegmentWriter`] from [`writer`](ParallelArcSliceBuilder::writer) and push
+/// their items into it without any synchronization. The segment is handed back to the builder
+/// when the writer is dropped, and [`finish`](ParallelArcSliceBuilder::finish) then moves all of
+/// the segments into a single `Arc<[T]>` in parallel. The segments are concatenated in the order
+/// their writers were dropped in, the items of each segment staying in the order they were pushed
+/// in.
+///
+/// # Examples
+/// ```rust
+/// use collect_into_rc_slice::*;
+/// use rayon::prelude::*;
+///
+/// let builder = ParallelArcSliceBuilder::new();
+/// (0..1000).into_par_iter().for_each_init(
+///     || builder.writer(),
+///     |writer, i| {
+///         if i % 3 == 0 {
+///             writer.push(i);
+///         }
+///     },
+/// );
+///
+/// let mut multiples = builder.finish().to_vec();
+/// multiples.sort();
+/// assert_eq!(multiples, (0..1000).step_by(3).collect::<Vec<_>>());
+/// ```
+pub struct ParallelArcSliceBuilder<T> {
+    segments: Mutex<Vec<Vec<T>>>,
+}
+
+impl<T> ParallelArcSliceBuilder<T> {
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            segments: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Starts a new segment, which is added to the builder when the writer is dropped.
+    #[inline]
+    pub fn writer(&self) -> SegmentWriter<'_, T> {
+        SegmentWriter {
+            builder: self,
+            segment: Vec::new(),
+        }
+    }
+
+    /// The number of items in the segments handed back so far.
+    pub fn len(&self) -> usize {
+        self.segments
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .map(Vec::len)
+            .sum()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T: Send> ParallelArcSliceBuilder<T> {
+    /// Moves all of the segments into a single `Arc<[T]>`, each segment being moved by a rayon
+    /// worker of its own.
+    pub fn finish(self) -> Arc<[T]> {
+        let segments = self
+            .segments
+            .into_inner()
+            .unwrap_or_else(PoisonError::into_inner);
+
+        into_arc(concat_segments(segments))
+    }
+}
+
+impl<T> Default for ParallelArcSliceBuilder<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> fmt::Debug for ParallelArcSliceBuilder<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ParallelArcSliceBuilder")
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+/// A segment of a [`ParallelArcSliceBuilder`] that a single worker appends to.
+pub struct SegmentWriter<'a, T> {
+    builder: &'a ParallelArcSliceBuilder<T>,
+    segment: Vec<T>,
+}
+
+impl<T> SegmentWriter<'_, T> {
+    #[inline]
+    pub fn push(&mut self, item: T) {
+        self.segment.push(item);
+    }
+
+    /// The number of items pushed into this segment.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.segment.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.segment.is_empty()
+    }
+}
+
+impl<T> Extend<T> for SegmentWriter<'_, T> {
+    #[inline]
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.segment.extend(iter);
+    }
+}
+
+impl<T> Drop for SegmentWriter<'_, T> {
+    fn drop(&mut self) {
+        if !self.segment.is_empty() {
+            self.builder
+                .segments
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .push(std::mem::take(&mut self.segment));
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for SegmentWriter<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.segment, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn test_parallel_arc_slice_builder_segments() {
+        let builder = ParallelArcSliceBuilder::new();
+        thread::scope(|scope| {
+            for t in 0..4 {
+                let builder = &builder;
+                scope.spawn(move || {
+                    let mut writer = builder.writer();
+                    writer.extend((0..t * 10).map(|i| format!("{t}:{i}")));
+                });
+            }
+        });
+        assert_eq!(builder.len(), 60);
+
+        let arc = builder.finish();
+        assert_eq!(arc.len(), 60);
+        for t in 1..4 {
+            // each segment stays contiguous and in order
+            let start = arc.iter().position(|s| *s == format!("{t}:0")).unwrap();
+            assert!((0..t * 10).all(|i| arc[start + i] == format!("{t}:{i}")));
+        }
+    }
+
+    #[test]
+    fn test_parallel_arc_slice_builder_empty() {
+        let builder = ParallelArcSliceBuilder::<u8>::default();
+        drop(builder.writer());
+
+        assert!(builder.is_empty());
+        assert!(builder.finish().is_empty());
+    }
+}
@@ -0,0 +1,66 @@
+use crate::rc::{into_rc, RcBuf};
+use std::rc::Rc;
+
+pub trait PartitionIntoRcSlices<T> {
+    /// Splits the iterator into an `Rc<[T]>` of the elements `f` returns `true` for and one of the
+    /// others, like `Iterator::partition` into two `Vec`s without the `Vec`s.
+    ///
+    /// The iterator is consumed once, and both allocations grow as the elements are sorted into
+    /// them.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use collect_into_rc_slice::*;
+    ///
+    /// let records = ["ok:1", "err:bad", "ok:2"];
+    /// let (ok, err) = records
+    ///     .into_iter()
+    ///     .partition_into_rc_slices(|record| record.starts_with("ok"));
+    ///
+    /// assert_eq!(&*ok, ["ok:1", "ok:2"]);
+    /// assert_eq!(&*err, ["err:bad"]);
+    /// ```
+    fn partition_into_rc_slices<F>(self, f: F) -> (Rc<[T]>, Rc<[T]>)
+    where
+        F: FnMut(&T) -> bool;
+}
+
+impl<T, I> PartitionIntoRcSlices<T> for I
+where
+    I: IntoIterator<Item = T>,
+{
+    fn partition_into_rc_slices<F>(self, mut f: F) -> (Rc<[T]>, Rc<[T]>)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        let mut matching = RcBuf::with_capacity(0);
+        let mut rest = RcBuf::with_capacity(0);
+
+        for item in self {
+            if f(&item) {
+                matching.push(item);
+            } else {
+                rest.push(item);
+            }
+        }
+
+        (into_rc(matching), into_rc(rest))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partition_into_rc_slices_order() {
+        let (even, odd) = (0..20).partition_into_rc_slices(|i| i % 2 == 0);
+
+        assert_eq!(&*even, (0..20).step_by(2).collect::<Vec<_>>());
+        assert_eq!(&*odd, (1..20).step_by(2).collect::<Vec<_>>());
+
+        let (all, none) = ["a"].into_iter().partition_into_rc_slices(|_| true);
+        assert_eq!(&*all, ["a"]);
+        assert!(none.is_empty());
+    }
+}
@@ -0,0 +1,101 @@
+/// A pattern that substring views can be split by.
+///
+/// `std::str::pattern::Pattern` can't be named on stable Rust, so this covers the same kinds of
+/// patterns: a `char`, a string, a set of `char`s, or a `char` predicate.
+pub trait SplitPattern: sealed::Sealed {
+    #[doc(hidden)]
+    fn split(self, s: &str) -> impl Iterator<Item = &str>;
+
+    #[doc(hidden)]
+    fn splitn(self, s: &str, n: usize) -> impl Iterator<Item = &str>;
+}
+
+macro_rules! impl_split_pattern {
+    ($(impl$([$($generics:tt)*])? for $ty:ty;)*) => {
+        $(
+            impl$(<$($generics)*>)? sealed::Sealed for $ty {}
+
+            impl$(<$($generics)*>)? SplitPattern for $ty {
+                #[inline]
+                fn split(self, s: &str) -> impl Iterator<Item = &str> {
+                    s.split(self)
+                }
+
+                #[inline]
+                fn splitn(self, s: &str, n: usize) -> impl Iterator<Item = &str> {
+                    s.splitn(n, self)
+                }
+            }
+        )*
+    };
+}
+
+impl_split_pattern! {
+    impl for char;
+    impl['a] for &'a str;
+    impl['a] for &'a String;
+    impl['a] for &'a [char];
+    impl[const N: usize] for [char; N];
+    impl[F: FnMut(char) -> bool] for F;
+}
+
+/// Splits a whole `Rc<str>` or `Arc<str>`, or its lines, into substring views of it, without
+/// wrapping it in an [`RcSubstr`](crate::RcSubstr) or [`ArcSubstr`](crate::ArcSubstr) first.
+///
+/// The methods are named apart from the `str` methods they mirror, so that `rc.split(',')` still
+/// borrows `&str`s.
+pub trait SplitIntoSubstrs {
+    /// [`RcSubstr`](crate::RcSubstr) for an `Rc<str>` and [`ArcSubstr`](crate::ArcSubstr) for an
+    /// `Arc<str>`.
+    type Substr;
+
+    /// Splits the string by `pat`, like `str::split`, returning substrings that share its
+    /// allocation.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use collect_into_rc_slice::*;
+    ///
+    /// let csv = "a,b,,c".chars().collect_into_rc_str();
+    /// let fields: Vec<RcSubstr> = csv.split_substrs(',').collect();
+    ///
+    /// drop(csv);
+    /// assert_eq!(fields, ["a", "b", "", "c"]);
+    /// ```
+    fn split_substrs<'a, P>(&'a self, pat: P) -> impl Iterator<Item = Self::Substr> + 'a
+    where
+        P: SplitPattern + 'a;
+
+    /// Splits the string by `pat` into at most `n` substrings, like `str::splitn`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use collect_into_rc_slice::*;
+    ///
+    /// let header = "Host: example.com:80".chars().collect_into_arc_str();
+    /// let parts: Vec<ArcSubstr> = header.splitn_substrs(2, ':').collect();
+    ///
+    /// assert_eq!(parts, ["Host", " example.com:80"]);
+    /// ```
+    fn splitn_substrs<'a, P>(&'a self, n: usize, pat: P) -> impl Iterator<Item = Self::Substr> + 'a
+    where
+        P: SplitPattern + 'a;
+
+    /// Iterates over the lines of the string, like `str::lines`, as substrings sharing its
+    /// allocation. Lines end with `\n` or `\r\n`, and the line endings are not included.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use collect_into_rc_slice::*;
+    ///
+    /// let text = "first\r\nsecond\nthird\n".chars().collect_into_rc_str();
+    /// let lines: Vec<RcSubstr> = text.lines_substrs().collect();
+    ///
+    /// assert_eq!(lines, ["first", "second", "third"]);
+    /// ```
+    fn lines_substrs(&self) -> impl Iterator<Item = Self::Substr> + '_;
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
@@ -0,0 +1,612 @@
+use crate::{
+    allocator::{Global, RawAlloc},
+    AllocError,
+};
+use std::{
+    alloc::Layout,
+    cmp,
+    marker::PhantomData,
+    mem::{self, ManuallyDrop, MaybeUninit},
+    ptr::{self, NonNull},
+    slice,
+};
+
+/// The part of a smart pointer allocation that comes before the data, e.g. the reference counts.
+///
+/// # Safety
+/// `layout` must return exactly the layout the smart pointer deallocates with when its data has
+/// the layout `data`, along with the offset of the data from the start of the allocation.
+pub(crate) unsafe trait Prefix {
+    fn layout(data: Layout) -> (Layout, usize)
+    where
+        Self: Sized,
+    {
+        extend(Layout::new::<Self>(), data)
+    }
+}
+
+/// A prefix `P` followed by a header `H`, for allocations that hold a `HeaderSlice<H, [T]>`.
+pub(crate) struct WithHeader<P, H>(PhantomData<(P, H)>);
+
+impl<P: Prefix, H> WithHeader<P, H> {
+    /// The offset of the header from the start of the allocation when the elements are `T`s.
+    pub(crate) fn header_offset<T>() -> usize {
+        let (inner, _) = extend(Layout::new::<H>(), Layout::new::<[T; 0]>());
+        P::layout(inner).1
+    }
+}
+
+// SAFETY: The header and the elements are laid out like a `#[repr(C)]` struct, which is then laid
+// out after `P` as a whole.
+unsafe impl<P: Prefix, H> Prefix for WithHeader<P, H> {
+    fn layout(data: Layout) -> (Layout, usize) {
+        let (inner, data_offset) = extend(Layout::new::<H>(), data);
+        let (layout, inner_offset) = P::layout(inner);
+        (layout, inner_offset + data_offset)
+    }
+}
+
+/// Appends `data` to `header` the same way a `#[repr(C)]` struct would lay out its last field.
+#[inline]
+pub(crate) fn extend(header: Layout, data: Layout) -> (Layout, usize) {
+    let (layout, offset) = header.extend(data).unwrap_or_else(|_| capacity_overflow());
+    (layout.pad_to_align(), offset)
+}
+
+/// The number of bytes of elements a growing buffer makes room for at least by default, so the
+/// first few elements don't each make it reallocate.
+const MIN_CAPACITY_BYTES: usize = 64;
+
+/// The minimum capacity of a growing buffer of `T`s, unless it is set to something else.
+#[inline]
+pub(crate) fn default_min_capacity<T>() -> usize {
+    cmp::max(MIN_CAPACITY_BYTES / cmp::max(mem::size_of::<T>(), 1), 1)
+}
+
+#[cold]
+pub(crate) fn capacity_overflow() -> ! {
+    panic!("capacity overflow");
+}
+
+/// Collects the elements of a serde sequence into a new buffer.
+///
+/// The size hint of the sequence is trusted up to 1 MiB worth of elements, past that the buffer
+/// grows as the elements arrive, so a malicious hint can't make it allocate arbitrary amounts of
+/// memory upfront.
+#[cfg(feature = "serde")]
+pub(crate) fn from_seq<'de, P, T, S>(mut seq: S) -> Result<RawBuf<P, T>, S::Error>
+where
+    P: Prefix,
+    T: serde::Deserialize<'de>,
+    S: serde::de::SeqAccess<'de>,
+{
+    let max = 1024 * 1024 / cmp::max(mem::size_of::<T>(), 1);
+    let mut buf = RawBuf::with_capacity(cmp::min(seq.size_hint().unwrap_or(0), max));
+
+    while let Some(item) = seq.next_element()? {
+        buf.push(item);
+    }
+
+    Ok(buf)
+}
+
+/// A growable buffer of `T` that lives inside a smart pointer allocation with the prefix `P`,
+/// allocated with `A`.
+///
+/// The prefix itself is left uninitialized, it is up to the owner to write it once the buffer is
+/// finished. If the buffer is dropped before that, the initialized elements are dropped and the
+/// allocation is freed.
+pub(crate) struct RawBuf<P: Prefix, T, A: RawAlloc = Global> {
+    ptr: NonNull<u8>,
+    cap: usize,
+    len: usize,
+    /// The capacity the buffer grows to at least when it has to grow.
+    min_cap: usize,
+    alloc: A,
+    _marker: PhantomData<(fn() -> P, T)>,
+}
+
+// SAFETY: `RawBuf` uniquely owns its elements, just like `Vec<T>`.
+unsafe impl<P: Prefix, T: Send, A: RawAlloc + Send> Send for RawBuf<P, T, A> {}
+unsafe impl<P: Prefix, T: Sync, A: RawAlloc + Sync> Sync for RawBuf<P, T, A> {}
+
+impl<P: Prefix, T> RawBuf<P, T> {
+    /// Allocates a buffer with room for at least `cap` elements.
+    #[inline]
+    pub(crate) fn with_capacity(cap: usize) -> Self {
+        Self::with_capacity_in(cap, Global)
+    }
+
+    /// Collects `iter` into a new buffer, using its size hint for the initial capacity.
+    #[inline]
+    pub(crate) fn from_iter<I: Iterator<Item = T>>(iter: I) -> Self {
+        Self::from_iter_in(iter, Global)
+    }
+
+    /// Takes back ownership of an allocation made for `len` elements, e.g. by a smart pointer that
+    /// is uniquely owned.
+    ///
+    /// # Safety
+    /// - `ptr` must have been allocated by the global allocator with `Self::layout(len)`.
+    /// - The `len` elements must be initialized, and not be used or dropped by anything else.
+    pub(crate) unsafe fn from_raw_parts(ptr: NonNull<u8>, len: usize) -> Self {
+        let cap = if mem::size_of::<T>() == 0 {
+            usize::MAX
+        } else {
+            len
+        };
+
+        Self {
+            ptr,
+            cap,
+            len,
+            min_cap: default_min_capacity::<T>(),
+            alloc: Global,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Trims the allocation and gives up ownership of it, returning a pointer to the start of the
+    /// allocation and a pointer to the elements.
+    #[inline]
+    pub(crate) fn into_raw_parts(self) -> (NonNull<u8>, *mut [T]) {
+        let (ptr, data, _) = self.into_raw_parts_with_alloc();
+        (ptr, data)
+    }
+
+    /// Like `into_raw_parts`, but returns an error instead of aborting if the allocation can't be
+    /// trimmed, dropping the buffer.
+    #[inline]
+    pub(crate) fn try_into_raw_parts(self) -> Result<(NonNull<u8>, *mut [T]), AllocError> {
+        let (ptr, data, _) = self.try_into_raw_parts_with_alloc()?;
+        Ok((ptr, data))
+    }
+}
+
+impl<P: Prefix, T, A: RawAlloc> RawBuf<P, T, A> {
+    /// Allocates a buffer with room for at least `cap` elements in `alloc`.
+    #[inline]
+    pub(crate) fn with_capacity_in(cap: usize, alloc: A) -> Self {
+        Self::try_with_capacity_in(cap, alloc).unwrap_or_else(|err| err.handle())
+    }
+
+    /// Like `with_capacity_in`, but returns an error instead of aborting if the allocation fails.
+    pub(crate) fn try_with_capacity_in(cap: usize, alloc: A) -> Result<Self, AllocError> {
+        let cap = if mem::size_of::<T>() == 0 {
+            usize::MAX
+        } else {
+            cap
+        };
+
+        let layout = Self::try_layout(cap)?;
+        let ptr = NonNull::new(alloc.alloc(layout)).ok_or(AllocError::new(layout))?;
+        #[cfg(feature = "stats")]
+        crate::stats::record_alloc();
+        #[cfg(feature = "tracing")]
+        tracing::trace!(
+            element = std::any::type_name::<T>(),
+            capacity = cap,
+            bytes = layout.size(),
+            "allocated a buffer"
+        );
+
+        Ok(Self {
+            ptr,
+            cap,
+            len: 0,
+            min_cap: default_min_capacity::<T>(),
+            alloc,
+            _marker: PhantomData,
+        })
+    }
+
+    /// Collects `iter` into a new buffer in `alloc`, using its size hint for the initial capacity.
+    pub(crate) fn from_iter_in<I: Iterator<Item = T>>(iter: I, alloc: A) -> Self {
+        let cap = Self::initial_capacity(iter.size_hint(), default_min_capacity::<T>());
+        let mut buf = Self::with_capacity_in(cap, alloc);
+
+        for item in iter {
+            buf.push(item);
+        }
+
+        buf
+    }
+
+    /// Like `from_iter_in`, but returns an error instead of aborting if an allocation fails, and
+    /// grows to at least `min_cap` elements.
+    pub(crate) fn try_from_iter_in<I: Iterator<Item = T>>(
+        iter: I,
+        alloc: A,
+        min_cap: usize,
+    ) -> Result<Self, AllocError> {
+        let cap = Self::initial_capacity(iter.size_hint(), min_cap);
+        let mut buf = Self::try_with_capacity_in(cap, alloc)?;
+        buf.min_cap = min_cap;
+
+        for item in iter {
+            buf.try_push(item)?;
+        }
+
+        Ok(buf)
+    }
+
+    /// The capacity to collect an iterator with the size hint `(lower_bound, upper_bound)` into.
+    fn initial_capacity(
+        (lower_bound, upper_bound): (usize, Option<usize>),
+        min_cap: usize,
+    ) -> usize {
+        // the capacity should be at least the lower bound
+        // but if the upper bound is known, it should be the upper bound
+        // and if it isn't, the buffer is likely to grow, so it might as well start at the minimum
+        upper_bound.unwrap_or(cmp::max(lower_bound, min_cap))
+    }
+
+    #[inline]
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    pub(crate) fn capacity(&self) -> usize {
+        self.cap
+    }
+
+    /// The layout of the whole allocation when it has room for `cap` elements.
+    pub(crate) fn layout(cap: usize) -> Layout {
+        let data = Layout::array::<T>(cap).unwrap_or_else(|_| capacity_overflow());
+        P::layout(data).0
+    }
+
+    /// Like `layout`, but returns an error instead of panicking if the size overflows.
+    fn try_layout(cap: usize) -> Result<Layout, AllocError> {
+        let data = Layout::array::<T>(cap).map_err(|_| AllocError::capacity_overflow())?;
+
+        // the elements start at the same offset whatever their number, so the size only overflows
+        // if the offset plus the elements do
+        let (empty, offset) = P::layout(Layout::new::<[T; 0]>());
+        offset
+            .checked_add(data.size())
+            .and_then(|size| Layout::from_size_align(size, empty.align()).ok())
+            .ok_or(AllocError::capacity_overflow())?;
+
+        Ok(Self::layout(cap))
+    }
+
+    /// The offset of the first element from the start of the allocation.
+    #[inline]
+    pub(crate) fn data_offset() -> usize {
+        P::layout(Layout::new::<[T; 0]>()).1
+    }
+
+    /// A pointer to the first element.
+    #[inline]
+    pub(crate) fn data_ptr(&self) -> *mut T {
+        // SAFETY: The data offset is always within the allocation.
+        unsafe { self.ptr.as_ptr().add(Self::data_offset()) as *mut T }
+    }
+
+    #[inline]
+    pub(crate) fn as_slice(&self) -> &[T] {
+        // SAFETY: The first `len` elements are initialized.
+        unsafe { slice::from_raw_parts(self.data_ptr(), self.len) }
+    }
+
+    #[inline]
+    pub(crate) fn as_mut_slice(&mut self) -> &mut [T] {
+        // SAFETY: The first `len` elements are initialized.
+        unsafe { slice::from_raw_parts_mut(self.data_ptr(), self.len) }
+    }
+
+    /// The room past the elements, which may or may not be initialized.
+    #[inline]
+    pub(crate) fn spare_capacity_mut(&mut self) -> &mut [MaybeUninit<T>] {
+        // SAFETY: The allocation has room for `cap` elements.
+        unsafe {
+            slice::from_raw_parts_mut(
+                self.data_ptr().add(self.len) as *mut MaybeUninit<T>,
+                self.cap - self.len,
+            )
+        }
+    }
+
+    /// # Safety
+    /// `len` must be at most the capacity and the first `len` elements must be initialized.
+    #[inline]
+    pub(crate) unsafe fn set_len(&mut self, len: usize) {
+        self.len = len;
+    }
+
+    /// Makes sure there is room for at least `additional` more elements.
+    #[inline]
+    pub(crate) fn reserve(&mut self, additional: usize) {
+        self.try_reserve(additional)
+            .unwrap_or_else(|err| err.handle());
+    }
+
+    /// Like `reserve`, but returns an error instead of aborting if the allocation fails, leaving
+    /// the buffer as it was.
+    pub(crate) fn try_reserve(&mut self, additional: usize) -> Result<(), AllocError> {
+        let required = self
+            .len
+            .checked_add(additional)
+            .ok_or(AllocError::capacity_overflow())?;
+
+        if required > self.cap {
+            let cap = cmp::max(self.cap.saturating_mul(2), required);
+            self.try_realloc(cmp::max(cap, self.min_cap))?;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    pub(crate) fn push(&mut self, item: T) {
+        if self.len == self.cap {
+            self.reserve(1);
+        }
+
+        // SAFETY: There is room for at least one more element.
+        unsafe { self.data_ptr().add(self.len).write(item) };
+        self.len += 1;
+    }
+
+    /// Like `push`, but returns an error instead of aborting if the buffer can't grow, dropping
+    /// `item`.
+    #[inline]
+    pub(crate) fn try_push(&mut self, item: T) -> Result<(), AllocError> {
+        if self.len == self.cap {
+            self.try_reserve(1)?;
+        }
+
+        // SAFETY: There is room for at least one more element.
+        unsafe { self.data_ptr().add(self.len).write(item) };
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Drops the elements past `len`, if there are any.
+    pub(crate) fn truncate(&mut self, len: usize) {
+        if len < self.len {
+            // SAFETY: The elements in `len..self.len` are initialized and forgotten by the buffer
+            // before they are dropped, so a panicking destructor can only leak them.
+            unsafe {
+                let tail = ptr::slice_from_raw_parts_mut(self.data_ptr().add(len), self.len - len);
+                self.len = len;
+                ptr::drop_in_place(tail);
+            }
+        }
+    }
+
+    /// Removes consecutive elements for which `same_bucket(element, previous)` returns `true`,
+    /// like `Vec::dedup_by`.
+    pub(crate) fn dedup_by<F: FnMut(&mut T, &mut T) -> bool>(&mut self, mut same_bucket: F) {
+        let items = self.as_mut_slice();
+        let mut kept = 1;
+
+        for i in 1..items.len() {
+            let (head, tail) = items.split_at_mut(i);
+            if !same_bucket(&mut tail[0], &mut head[kept - 1]) {
+                items.swap(i, kept);
+                kept += 1;
+            }
+        }
+
+        self.truncate(kept);
+    }
+
+    /// Keeps only the elements for which `f` returns `true`, in order, like `Vec::retain`.
+    pub(crate) fn retain<F: FnMut(&T) -> bool>(&mut self, mut f: F) {
+        let items = self.as_mut_slice();
+        let mut kept = 0;
+
+        for i in 0..items.len() {
+            if f(&items[i]) {
+                items.swap(i, kept);
+                kept += 1;
+            }
+        }
+
+        self.truncate(kept);
+    }
+
+    /// Trims the allocation down to the length of the buffer.
+    fn try_shrink_to_fit(&mut self) -> Result<(), AllocError> {
+        if self.cap > self.len && mem::size_of::<T>() != 0 {
+            self.try_realloc(self.len)?;
+        }
+        Ok(())
+    }
+
+    fn try_realloc(&mut self, cap: usize) -> Result<(), AllocError> {
+        let old_layout = Self::layout(self.cap);
+        let new_layout = Self::try_layout(cap)?;
+        #[cfg(feature = "tracing")]
+        tracing::trace!(
+            element = std::any::type_name::<T>(),
+            len = self.len,
+            from = self.cap,
+            to = cap,
+            bytes = new_layout.size(),
+            "reallocating a buffer"
+        );
+
+        // SAFETY:
+        // - `ptr` was allocated with `old_layout`.
+        // - Both layouts have the same alignment and a non-zero size.
+        let ptr = unsafe {
+            self.alloc
+                .realloc(self.ptr.as_ptr(), old_layout, new_layout)
+        };
+
+        self.ptr = NonNull::new(ptr).ok_or(AllocError::new(new_layout))?;
+        self.cap = cap;
+        #[cfg(feature = "stats")]
+        crate::stats::record_realloc(self.len * mem::size_of::<T>());
+        Ok(())
+    }
+
+    /// Like `into_raw_parts`, but also returns the allocator.
+    #[inline]
+    pub(crate) fn into_raw_parts_with_alloc(self) -> (NonNull<u8>, *mut [T], A) {
+        self.try_into_raw_parts_with_alloc()
+            .unwrap_or_else(|err| err.handle())
+    }
+
+    /// Like `into_raw_parts_with_alloc`, but returns an error instead of aborting if the allocation
+    /// can't be trimmed, dropping the buffer.
+    pub(crate) fn try_into_raw_parts_with_alloc(
+        mut self,
+    ) -> Result<(NonNull<u8>, *mut [T], A), AllocError> {
+        #[cfg(feature = "stats")]
+        crate::stats::record_collection((self.cap - self.len).saturating_mul(mem::size_of::<T>()));
+        #[cfg(feature = "tracing")]
+        tracing::trace!(
+            element = std::any::type_name::<T>(),
+            len = self.len,
+            capacity = self.cap,
+            "finishing a buffer"
+        );
+        self.try_shrink_to_fit()?;
+
+        let this = ManuallyDrop::new(self);
+        // SAFETY: `this` is never used or dropped again after the allocator is moved out.
+        let alloc = unsafe { ptr::read(&this.alloc) };
+        Ok((
+            this.ptr,
+            ptr::slice_from_raw_parts_mut(this.data_ptr(), this.len),
+            alloc,
+        ))
+    }
+}
+
+impl<P: Prefix, T: Copy, A: RawAlloc> RawBuf<P, T, A> {
+    pub(crate) fn extend_from_slice(&mut self, items: &[T]) {
+        self.reserve(items.len());
+
+        // SAFETY: There is room for `items.len()` more elements and the buffers can't overlap.
+        unsafe {
+            ptr::copy_nonoverlapping(items.as_ptr(), self.data_ptr().add(self.len), items.len());
+        }
+        self.len += items.len();
+    }
+}
+
+impl<P: Prefix> RawBuf<P, u8> {
+    /// Appends `n` zero bytes, to be overwritten by a reader.
+    pub(crate) fn extend_zeroed(&mut self, n: usize) {
+        self.reserve(n);
+
+        // SAFETY: There is room for `n` more bytes.
+        unsafe { self.data_ptr().add(self.len).write_bytes(0, n) };
+        self.len += n;
+    }
+
+    /// Collects an iterator of `char`s into a new buffer of UTF-8 data.
+    pub(crate) fn from_chars<I: Iterator<Item = char>>(iter: I) -> Self {
+        // the capacity should be at least one byte per known character
+        let (lower_bound, upper_bound) = iter.size_hint();
+        let mut buf = Self::with_capacity(upper_bound.unwrap_or(lower_bound));
+
+        for c in iter {
+            buf.push_char(c);
+        }
+
+        buf
+    }
+
+    /// Like `from_chars`, but returns an error instead of aborting if an allocation fails, and
+    /// grows to at least `min_cap` bytes.
+    pub(crate) fn try_from_chars<I: Iterator<Item = char>>(
+        iter: I,
+        min_cap: usize,
+    ) -> Result<Self, AllocError> {
+        let (lower_bound, upper_bound) = iter.size_hint();
+        let mut buf = Self::try_with_capacity_in(upper_bound.unwrap_or(lower_bound), Global)?;
+        buf.min_cap = min_cap;
+
+        for c in iter {
+            buf.try_reserve(c.len_utf8())?;
+            buf.push_char(c);
+        }
+
+        Ok(buf)
+    }
+
+    #[inline]
+    pub(crate) fn push_char(&mut self, c: char) {
+        self.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+    }
+}
+
+impl<P: Prefix, T, A: RawAlloc> Drop for RawBuf<P, T, A> {
+    fn drop(&mut self) {
+        // SAFETY:
+        // - The first `len` elements are initialized.
+        // - `ptr` was allocated with the layout for `cap` elements.
+        unsafe {
+            ptr::drop_in_place(self.as_mut_slice());
+            self.alloc
+                .dealloc(self.ptr.as_ptr(), Self::layout(self.cap));
+        }
+    }
+}
+
+#[cfg(all(test, feature = "tracing"))]
+mod tests {
+    use crate::*;
+    use std::sync::{Arc, Mutex};
+    use tracing::{
+        field::{Field, Visit},
+        span, Event, Metadata, Subscriber,
+    };
+
+    /// Records the messages of the events.
+    #[derive(Clone, Default)]
+    struct Messages(Arc<Mutex<Vec<String>>>);
+
+    impl Visit for Messages {
+        fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+            if field.name() == "message" {
+                self.0.lock().unwrap().push(format!("{value:?}"));
+            }
+        }
+    }
+
+    impl Subscriber for Messages {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, _: &span::Attributes<'_>) -> span::Id {
+            span::Id::from_u64(1)
+        }
+
+        fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+
+        fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            event.record(&mut self.clone());
+        }
+
+        fn enter(&self, _: &span::Id) {}
+
+        fn exit(&self, _: &span::Id) {}
+    }
+
+    #[test]
+    fn test_tracing_events() {
+        let messages = Messages::default();
+        tracing::subscriber::with_default(messages.clone(), || {
+            (0..10).filter(|i| i % 2 == 0).collect_into_rc_slice()
+        });
+
+        assert_eq!(
+            *messages.0.lock().unwrap(),
+            [
+                "allocated a buffer",
+                "finishing a buffer",
+                "reallocating a buffer"
+            ]
+        );
+    }
+}
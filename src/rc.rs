@@ -1,5 +1,8 @@
-use std::alloc::Layout;
-use std::mem;
+use crate::{
+    raw::{Prefix, RawBuf},
+    AllocError,
+};
+use std::{ptr, rc::Rc};
 
 #[repr(C)]
 pub(crate) struct RcBox<T: ?Sized> {
@@ -8,14 +11,75 @@ pub(crate) struct RcBox<T: ?Sized> {
     pub(crate) data: T,
 }
 
-pub(crate) fn data_offset<T>() -> usize {
-    let layout = Layout::new::<RcBox<()>>();
-    layout.size() + padding_needed(layout.size(), mem::align_of::<T>())
-}
+// SAFETY: `Rc` lays out its allocation as an `RcBox`.
+unsafe impl Prefix for RcBox<()> {}
+
+pub(crate) type RcBuf<T> = RawBuf<RcBox<()>, T>;
 
+/// Writes the reference counts and hands the buffer over to an `Rc<[T]>`.
 #[inline]
-pub(crate) fn padding_needed(len: usize, align: usize) -> usize {
-    // Math for computing padding is taken from `Layout::padding_needed_for`.
-    let padding = len.wrapping_add(align).wrapping_sub(1) & !align.wrapping_sub(1);
-    padding.wrapping_sub(len)
+pub(crate) fn into_rc<T>(buf: RcBuf<T>) -> Rc<[T]> {
+    try_into_rc(buf).unwrap_or_else(|err| err.handle())
+}
+
+/// Like `into_rc`, but returns an error instead of aborting if the allocation can't be trimmed.
+pub(crate) fn try_into_rc<T>(buf: RcBuf<T>) -> Result<Rc<[T]>, AllocError> {
+    let (alloc, data) = buf.try_into_raw_parts()?;
+
+    // SAFETY:
+    // - The prefix of the allocation is reserved for the `RcBox` header.
+    // - `data` is a valid pointer to a `[T]` located at the heap
+    // - `data` is part of an RcBox with proper metadata.
+    unsafe {
+        ptr::write(
+            alloc.as_ptr() as *mut RcBox<()>,
+            RcBox {
+                strong_count: 1,
+                weak_count: 1,
+                data: (),
+            },
+        );
+
+        Ok(Rc::from_raw(data))
+    }
+}
+
+/// Writes the reference counts and hands the buffer over to an `Rc<[T], A>`.
+#[cfg(feature = "allocator_api")]
+pub(crate) fn into_rc_in<T, A: std::alloc::Allocator>(buf: RawBuf<RcBox<()>, T, A>) -> Rc<[T], A> {
+    let (alloc, data, allocator) = buf.into_raw_parts_with_alloc();
+
+    // SAFETY:
+    // - The prefix of the allocation is reserved for the `RcBox` header.
+    // - `data` is a valid pointer to a `[T]` allocated with `allocator`
+    // - `data` is part of an RcBox with proper metadata.
+    unsafe {
+        ptr::write(
+            alloc.as_ptr() as *mut RcBox<()>,
+            RcBox {
+                strong_count: 1,
+                weak_count: 1,
+                data: (),
+            },
+        );
+
+        Rc::from_raw_in(data, allocator)
+    }
+}
+
+/// Hands a buffer of UTF-8 data over to an `Rc<str>`.
+///
+/// # Safety
+/// The buffer must contain valid UTF-8.
+pub(crate) unsafe fn into_rc_str(buf: RcBuf<u8>) -> Rc<str> {
+    Rc::from_raw(Rc::into_raw(into_rc(buf)) as *const str)
+}
+
+/// Like `into_rc_str`, but returns an error instead of aborting if the allocation can't be
+/// trimmed.
+///
+/// # Safety
+/// The buffer must contain valid UTF-8.
+pub(crate) unsafe fn try_into_rc_str(buf: RcBuf<u8>) -> Result<Rc<str>, AllocError> {
+    try_into_rc(buf).map(|rc| Rc::from_raw(Rc::into_raw(rc) as *const str))
 }
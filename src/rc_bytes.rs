@@ -0,0 +1,331 @@
+use crate::rc::{into_rc, RcBuf};
+use std::{
+    borrow::Borrow,
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+    ops::{Bound, Deref, Index, Range, RangeBounds},
+    rc::Rc,
+    slice::{self, SliceIndex},
+};
+
+/// A cheaply cloneable view of a range of an `Rc<[u8]>`, similar to `bytes::Bytes` but
+/// single-threaded.
+///
+/// Slicing and splitting an `RcBytes` only bumps the reference count, so a protocol parser can
+/// collect its input into one buffer and carve it up into frames and fields without copying.
+///
+/// # Examples
+/// ```rust
+/// use collect_into_rc_slice::*;
+///
+/// let mut buf = b"\x03abcrest".iter().copied().collect_into_rc_bytes();
+/// let len = buf[0] as usize;
+/// buf.advance(1);
+/// let field = buf.split_to(len);
+///
+/// assert_eq!(&*field, b"abc");
+/// assert_eq!(&*buf, b"rest");
+/// ```
+#[derive(Clone)]
+pub struct RcBytes {
+    parent: Rc<[u8]>,
+    start: usize,
+    end: usize,
+}
+
+impl RcBytes {
+    /// A view covering all of `parent`.
+    #[inline]
+    pub fn new(parent: Rc<[u8]>) -> Self {
+        let end = parent.len();
+        Self {
+            parent,
+            start: 0,
+            end,
+        }
+    }
+
+    #[inline]
+    pub fn as_slice(&self) -> &[u8] {
+        // SAFETY: The range is always in bounds of the parent.
+        unsafe { self.parent.get_unchecked(self.start..self.end) }
+    }
+
+    /// The whole buffer this is a view of.
+    #[inline]
+    pub fn parent(&self) -> &Rc<[u8]> {
+        &self.parent
+    }
+
+    /// The range of this view within [`parent`](RcBytes::parent).
+    #[inline]
+    pub fn range(&self) -> Range<usize> {
+        self.start..self.end
+    }
+
+    /// Returns a view of a range of this view, sharing the same allocation.
+    ///
+    /// # Panics
+    /// Panics if the range is out of bounds.
+    pub fn slice(&self, range: impl RangeBounds<usize>) -> Self {
+        let bounds: (Bound<usize>, Bound<usize>) =
+            (range.start_bound().cloned(), range.end_bound().cloned());
+        let sub = &self.as_slice()[bounds];
+        self.slice_ref(sub)
+    }
+
+    /// Returns the view for `sub`, which has to be a part of this view.
+    ///
+    /// # Panics
+    /// Panics if `sub` is not contained in this view.
+    pub fn slice_ref(&self, sub: &[u8]) -> Self {
+        let base = self.as_slice().as_ptr() as usize;
+        let offset = (sub.as_ptr() as usize).wrapping_sub(base);
+
+        assert!(
+            offset <= self.len() && sub.len() <= self.len() - offset,
+            "the slice is not a part of this view"
+        );
+
+        Self {
+            parent: self.parent.clone(),
+            start: self.start + offset,
+            end: self.start + offset + sub.len(),
+        }
+    }
+
+    /// Splits the view in two at `at`, keeping `[at, len)` and returning `[0, at)`.
+    ///
+    /// # Panics
+    /// Panics if `at > len`.
+    pub fn split_to(&mut self, at: usize) -> Self {
+        assert!(
+            at <= self.len(),
+            "split_to out of bounds: {at} > {}",
+            self.len()
+        );
+
+        let head = Self {
+            parent: self.parent.clone(),
+            start: self.start,
+            end: self.start + at,
+        };
+        self.start += at;
+        head
+    }
+
+    /// Splits the view in two at `at`, keeping `[0, at)` and returning `[at, len)`.
+    ///
+    /// # Panics
+    /// Panics if `at > len`.
+    pub fn split_off(&mut self, at: usize) -> Self {
+        assert!(
+            at <= self.len(),
+            "split_off out of bounds: {at} > {}",
+            self.len()
+        );
+
+        let tail = Self {
+            parent: self.parent.clone(),
+            start: self.start + at,
+            end: self.end,
+        };
+        self.end = self.start + at;
+        tail
+    }
+
+    /// Drops the first `cnt` bytes from the view.
+    ///
+    /// # Panics
+    /// Panics if `cnt > len`.
+    #[inline]
+    pub fn advance(&mut self, cnt: usize) {
+        assert!(
+            cnt <= self.len(),
+            "advance out of bounds: {cnt} > {}",
+            self.len()
+        );
+        self.start += cnt;
+    }
+}
+
+impl From<Rc<[u8]>> for RcBytes {
+    #[inline]
+    fn from(parent: Rc<[u8]>) -> Self {
+        Self::new(parent)
+    }
+}
+
+impl Deref for RcBytes {
+    type Target = [u8];
+
+    #[inline]
+    fn deref(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+
+impl AsRef<[u8]> for RcBytes {
+    #[inline]
+    fn as_ref(&self) -> &[u8] {
+        self
+    }
+}
+
+impl<I: SliceIndex<[u8]>> Index<I> for RcBytes {
+    type Output = I::Output;
+
+    #[inline]
+    fn index(&self, index: I) -> &I::Output {
+        &(**self)[index]
+    }
+}
+
+/// An empty view.
+impl Default for RcBytes {
+    #[inline]
+    fn default() -> Self {
+        Self::new(Rc::default())
+    }
+}
+
+impl<'a> IntoIterator for &'a RcBytes {
+    type Item = &'a u8;
+    type IntoIter = slice::Iter<'a, u8>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl Borrow<[u8]> for RcBytes {
+    #[inline]
+    fn borrow(&self) -> &[u8] {
+        self
+    }
+}
+
+impl fmt::Debug for RcBytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_slice(), f)
+    }
+}
+
+impl PartialEq for RcBytes {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl Eq for RcBytes {}
+
+impl PartialEq<[u8]> for RcBytes {
+    #[inline]
+    fn eq(&self, other: &[u8]) -> bool {
+        self.as_slice() == other
+    }
+}
+
+impl PartialEq<&[u8]> for RcBytes {
+    #[inline]
+    fn eq(&self, other: &&[u8]) -> bool {
+        self.as_slice() == *other
+    }
+}
+
+impl PartialOrd for RcBytes {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for RcBytes {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_slice().cmp(other.as_slice())
+    }
+}
+
+impl Hash for RcBytes {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_slice().hash(state);
+    }
+}
+
+impl FromIterator<u8> for RcBytes {
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = u8>>(iter: I) -> Self {
+        Self::new(into_rc(RcBuf::from_iter(iter.into_iter())))
+    }
+}
+
+pub trait CollectIntoRcBytes {
+    /// Collects the iterator into an [`RcBytes`] covering the whole buffer.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use collect_into_rc_slice::*;
+    ///
+    /// let bytes = "abc".bytes().collect_into_rc_bytes();
+    ///
+    /// assert_eq!(&*bytes, b"abc");
+    /// ```
+    fn collect_into_rc_bytes(self) -> RcBytes;
+}
+
+impl<T> CollectIntoRcBytes for T
+where
+    T: IntoIterator<Item = u8>,
+{
+    fn collect_into_rc_bytes(self) -> RcBytes {
+        self.into_iter().collect()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for RcBytes {
+    #[inline]
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rc_bytes_split() {
+        let mut bytes = (0..10).collect_into_rc_bytes();
+        let tail = bytes.split_off(6);
+        let head = bytes.split_to(2);
+
+        assert_eq!(&*head, &[0, 1]);
+        assert_eq!(&*bytes, &[2, 3, 4, 5]);
+        assert_eq!(&*tail, &[6, 7, 8, 9]);
+        assert_eq!(bytes.range(), 2..6);
+        assert_eq!(Rc::strong_count(bytes.parent()), 3);
+    }
+
+    #[test]
+    fn test_rc_bytes_slice() {
+        let mut bytes = (0..10).collect_into_rc_bytes();
+        bytes.advance(3);
+        let slice = bytes.slice(1..=2);
+
+        assert_eq!(&*slice, &[4, 5]);
+        assert_eq!(slice.range(), 4..6);
+        assert!(bytes.slice(7..).is_empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_rc_bytes_advance_out_of_bounds() {
+        let mut bytes = (0..2).collect_into_rc_bytes();
+        bytes.advance(3);
+    }
+}
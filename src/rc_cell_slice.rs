@@ -0,0 +1,96 @@
+use crate::rc::{into_rc, RcBuf};
+use std::{cell::Cell, rc::Rc};
+
+pub trait CollectIntoRcCellSlice<T> {
+    /// Collects the iterator into an `Rc<[Cell<T>]>`, a slice whose elements can be modified by
+    /// any of the pointers to it.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use collect_into_rc_slice::*;
+    ///
+    /// let registers = [0u64; 4].into_iter().collect_into_rc_cell_slice();
+    /// let shared = registers.clone();
+    ///
+    /// shared[2].set(42);
+    /// assert_eq!(registers[2].get(), 42);
+    /// ```
+    fn collect_into_rc_cell_slice(self) -> Rc<[Cell<T>]>;
+}
+
+impl<I, T> CollectIntoRcCellSlice<T> for I
+where
+    I: IntoIterator<Item = T>,
+{
+    fn collect_into_rc_cell_slice(self) -> Rc<[Cell<T>]> {
+        into_rc(RcBuf::from_iter(self.into_iter().map(Cell::new)))
+    }
+}
+
+/// Turns `this` into an `Rc<[Cell<T>]>` without copying the elements if it is the only pointer to
+/// them, with no other `Rc`s or `Weak`s that would see them change. Otherwise `this` is returned
+/// as is.
+///
+/// # Examples
+/// ```rust
+/// use collect_into_rc_slice::*;
+///
+/// let rc = (0..4).collect_into_rc_slice();
+/// let cells = rc_slice_into_cells(rc).unwrap();
+///
+/// cells[0].set(10);
+/// assert_eq!(&*rc_cells_into_slice(cells).unwrap(), &[10, 1, 2, 3]);
+/// ```
+pub fn rc_slice_into_cells<T>(mut this: Rc<[T]>) -> Result<Rc<[Cell<T>]>, Rc<[T]>> {
+    if Rc::get_mut(&mut this).is_none() {
+        return Err(this);
+    }
+
+    // SAFETY: `Cell<T>` has the same layout as `T`, and no other pointer can read the elements.
+    Ok(unsafe { Rc::from_raw(Rc::into_raw(this) as *const [Cell<T>]) })
+}
+
+/// Turns `this` back into an `Rc<[T]>` without copying the elements if it is the only pointer to
+/// them, with no other `Rc`s or `Weak`s that could still modify them. Otherwise `this` is returned
+/// as is.
+pub fn rc_cells_into_slice<T>(mut this: Rc<[Cell<T>]>) -> Result<Rc<[T]>, Rc<[Cell<T>]>> {
+    if Rc::get_mut(&mut this).is_none() {
+        return Err(this);
+    }
+
+    // SAFETY: `Cell<T>` has the same layout as `T`, and no other pointer can modify the elements.
+    Ok(unsafe { Rc::from_raw(Rc::into_raw(this) as *const [T]) })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collect_into_rc_cell_slice_shared() {
+        let cells = (1..=3).collect_into_rc_cell_slice();
+        let shared = cells.clone();
+        shared.iter().for_each(|cell| cell.set(cell.get() * 2));
+
+        let cells = rc_cells_into_slice(cells).unwrap_err();
+        drop(shared);
+        assert_eq!(&*rc_cells_into_slice(cells).unwrap(), &[2, 4, 6]);
+    }
+
+    #[test]
+    fn test_rc_slice_into_cells_in_place() {
+        let rc: Rc<[u8]> = Rc::from(&b"abc"[..]);
+        let ptr = rc.as_ptr();
+        let weak = Rc::downgrade(&rc);
+
+        let rc = rc_slice_into_cells(rc).unwrap_err();
+        drop(weak);
+
+        let cells = rc_slice_into_cells(rc).unwrap();
+        cells[0].swap(&cells[2]);
+        assert_eq!(cells.as_ptr() as *const u8, ptr);
+
+        let rc = rc_cells_into_slice(cells).unwrap();
+        assert_eq!(&*rc, b"cba");
+    }
+}
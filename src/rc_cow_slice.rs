@@ -0,0 +1,194 @@
+use crate::{
+    rc::{into_rc, RcBuf},
+    unique::make_mut_slice,
+};
+use std::{
+    borrow::Borrow,
+    fmt,
+    hash::{Hash, Hasher},
+    ops::Deref,
+    rc::Rc,
+};
+
+/// Either a `&'static [T]` or an `Rc<[T]>`, for slices that are usually static but may be patched
+/// at runtime, e.g. lookup tables.
+///
+/// The static variant costs no allocation and is cloned by copying the reference.
+/// [`to_mut`](RcCowSlice::to_mut) collects the elements into a new `Rc<[T]>` the first time the
+/// slice is modified, and clones them again only if that `Rc` is shared by then.
+///
+/// # Examples
+/// ```rust
+/// use collect_into_rc_slice::*;
+///
+/// static DEFAULT_WEIGHTS: [u32; 3] = [1, 1, 1];
+///
+/// let defaults = RcCowSlice::from(&DEFAULT_WEIGHTS[..]);
+/// let mut weights = defaults.clone();
+/// weights.to_mut()[1] = 5;
+///
+/// assert!(defaults.is_static());
+/// assert!(!weights.is_static());
+/// assert_eq!(&*weights, &[1, 5, 1]);
+/// ```
+pub enum RcCowSlice<T: 'static> {
+    Static(&'static [T]),
+    Rc(Rc<[T]>),
+}
+
+impl<T: 'static> RcCowSlice<T> {
+    #[inline]
+    pub fn as_slice(&self) -> &[T] {
+        match self {
+            Self::Static(s) => s,
+            Self::Rc(rc) => rc,
+        }
+    }
+
+    /// Returns `true` if the slice is still the static one.
+    #[inline]
+    pub fn is_static(&self) -> bool {
+        matches!(self, Self::Static(_))
+    }
+
+    /// Returns a mutable reference to the elements, collecting them into a new unique `Rc<[T]>`
+    /// first if they are static or shared.
+    pub fn to_mut(&mut self) -> &mut [T]
+    where
+        T: Clone,
+    {
+        if let Self::Static(s) = *self {
+            *self = Self::Rc(into_rc(RcBuf::from_iter(s.iter().cloned())));
+        }
+
+        match self {
+            Self::Rc(rc) => make_mut_slice(rc),
+            Self::Static(_) => unreachable!("the slice was just collected into an `Rc`"),
+        }
+    }
+
+    /// Returns the elements as an `Rc<[T]>`, collecting them into one if they are static.
+    pub fn into_rc(self) -> Rc<[T]>
+    where
+        T: Clone,
+    {
+        match self {
+            Self::Static(s) => into_rc(RcBuf::from_iter(s.iter().cloned())),
+            Self::Rc(rc) => rc,
+        }
+    }
+}
+
+impl<T: 'static> Clone for RcCowSlice<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        match self {
+            Self::Static(s) => Self::Static(s),
+            Self::Rc(rc) => Self::Rc(rc.clone()),
+        }
+    }
+}
+
+impl<T: 'static> Deref for RcCowSlice<T> {
+    type Target = [T];
+
+    #[inline]
+    fn deref(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+impl<T: 'static> AsRef<[T]> for RcCowSlice<T> {
+    #[inline]
+    fn as_ref(&self) -> &[T] {
+        self
+    }
+}
+
+impl<T: 'static> Borrow<[T]> for RcCowSlice<T> {
+    #[inline]
+    fn borrow(&self) -> &[T] {
+        self
+    }
+}
+
+impl<T: 'static> Default for RcCowSlice<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::Static(&[])
+    }
+}
+
+impl<T: 'static> From<&'static [T]> for RcCowSlice<T> {
+    #[inline]
+    fn from(s: &'static [T]) -> Self {
+        Self::Static(s)
+    }
+}
+
+impl<T: 'static> From<Rc<[T]>> for RcCowSlice<T> {
+    #[inline]
+    fn from(rc: Rc<[T]>) -> Self {
+        Self::Rc(rc)
+    }
+}
+
+impl<T: 'static> FromIterator<T> for RcCowSlice<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::Rc(into_rc(RcBuf::from_iter(iter.into_iter())))
+    }
+}
+
+impl<T: fmt::Debug + 'static> fmt::Debug for RcCowSlice<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_slice(), f)
+    }
+}
+
+impl<T: PartialEq + 'static> PartialEq for RcCowSlice<T> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl<T: Eq + 'static> Eq for RcCowSlice<T> {}
+
+impl<T: Hash + 'static> Hash for RcCowSlice<T> {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_slice().hash(state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rc_cow_slice_promotes_once() {
+        static TABLE: [&str; 2] = ["a", "b"];
+
+        let mut cow = RcCowSlice::from(&TABLE[..]);
+        cow.to_mut()[0] = "x";
+        let ptr = cow.as_ptr();
+
+        // unique, so it is modified in place from now on
+        cow.to_mut()[1] = "y";
+        assert_eq!(cow.as_ptr(), ptr);
+        assert_eq!(&*cow, ["x", "y"]);
+        assert_eq!(TABLE, ["a", "b"]);
+    }
+
+    #[test]
+    fn test_rc_cow_slice_shared_rc() {
+        let mut cow: RcCowSlice<String> = ["a", "b"].into_iter().map(String::from).collect();
+        let other = cow.clone();
+
+        cow.to_mut()[0].push('!');
+        assert_eq!(&*cow, ["a!", "b"]);
+        assert_eq!(&*other, ["a", "b"]);
+        assert_eq!(other.into_rc().len(), 2);
+        assert!(RcCowSlice::<u8>::default().is_static());
+    }
+}
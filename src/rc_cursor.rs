@@ -0,0 +1,160 @@
+use std::{
+    cmp,
+    io::{self, BufRead, Read, Seek, SeekFrom},
+    rc::Rc,
+};
+
+/// A reader over a shared byte buffer, like `io::Cursor` but made for the buffers of this crate.
+///
+/// The buffer can be an `Rc<[u8]>`, an `Arc<[u8]>`, an [`RcBytes`](crate::RcBytes), an
+/// [`ArcBytes`](crate::ArcBytes) or anything else that derefs to bytes, so a collected buffer can
+/// be passed to APIs expecting a reader without copying it into a `Vec<u8>`.
+///
+/// # Examples
+/// ```rust
+/// use collect_into_rc_slice::*;
+/// use std::io::{BufRead, Read};
+///
+/// let mut cursor = RcCursor::new("hello\nworld".bytes().collect_into_rc_slice());
+/// let mut line = String::new();
+/// cursor.read_line(&mut line).unwrap();
+/// let mut rest = String::new();
+/// cursor.read_to_string(&mut rest).unwrap();
+///
+/// assert_eq!(line, "hello\n");
+/// assert_eq!(rest, "world");
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct RcCursor<B = Rc<[u8]>> {
+    inner: B,
+    pos: u64,
+}
+
+impl<B> RcCursor<B> {
+    /// A cursor at the start of `inner`.
+    #[inline]
+    pub fn new(inner: B) -> Self {
+        Self { inner, pos: 0 }
+    }
+
+    #[inline]
+    pub fn get_ref(&self) -> &B {
+        &self.inner
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> B {
+        self.inner
+    }
+
+    /// The current position of the cursor, which can be past the end of the buffer.
+    #[inline]
+    pub fn position(&self) -> u64 {
+        self.pos
+    }
+
+    #[inline]
+    pub fn set_position(&mut self, pos: u64) {
+        self.pos = pos;
+    }
+}
+
+impl<B: AsRef<[u8]>> RcCursor<B> {
+    /// The bytes from the current position to the end of the buffer.
+    #[inline]
+    pub fn remaining_slice(&self) -> &[u8] {
+        let buf = self.inner.as_ref();
+        let start = cmp::min(self.pos, buf.len() as u64) as usize;
+        &buf[start..]
+    }
+
+    /// Returns `true` if the cursor is at or past the end of the buffer.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.remaining_slice().is_empty()
+    }
+}
+
+impl<B: AsRef<[u8]>> Read for RcCursor<B> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = Read::read(&mut self.remaining_slice(), buf)?;
+        self.pos += n as u64;
+        Ok(n)
+    }
+
+    fn read_exact(&mut self, buf: &mut [u8]) -> io::Result<()> {
+        Read::read_exact(&mut self.remaining_slice(), buf)?;
+        self.pos += buf.len() as u64;
+        Ok(())
+    }
+}
+
+impl<B: AsRef<[u8]>> BufRead for RcCursor<B> {
+    #[inline]
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        Ok(self.remaining_slice())
+    }
+
+    #[inline]
+    fn consume(&mut self, amt: usize) {
+        self.pos += amt as u64;
+    }
+}
+
+impl<B: AsRef<[u8]>> Seek for RcCursor<B> {
+    fn seek(&mut self, style: SeekFrom) -> io::Result<u64> {
+        let (base, offset) = match style {
+            SeekFrom::Start(n) => {
+                self.pos = n;
+                return Ok(n);
+            }
+            SeekFrom::End(n) => (self.inner.as_ref().len() as u64, n),
+            SeekFrom::Current(n) => (self.pos, n),
+        };
+
+        match base.checked_add_signed(offset) {
+            Some(n) => {
+                self.pos = n;
+                Ok(n)
+            }
+            None => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            )),
+        }
+    }
+
+    #[inline]
+    fn stream_position(&mut self) -> io::Result<u64> {
+        Ok(self.pos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CollectIntoRcBytes, CollectIntoRcSlice};
+
+    #[test]
+    fn test_rc_cursor_seek() {
+        let mut cursor = RcCursor::new((0..10).collect_into_rc_slice());
+        let mut buf = [0; 3];
+
+        assert_eq!(cursor.seek(SeekFrom::End(-4)).unwrap(), 6);
+        cursor.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, [6, 7, 8]);
+        assert_eq!(cursor.seek(SeekFrom::Current(-9)).unwrap(), 0);
+        assert!(cursor.seek(SeekFrom::Current(-1)).is_err());
+        assert_eq!(cursor.position(), 0);
+    }
+
+    #[test]
+    fn test_rc_cursor_past_end() {
+        let mut cursor = RcCursor::new((0..4).collect_into_rc_bytes().slice(1..));
+        cursor.set_position(10);
+
+        assert_eq!(cursor.read(&mut [0; 4]).unwrap(), 0);
+        assert!(cursor.read_exact(&mut [0; 1]).is_err());
+        assert!(cursor.is_empty());
+    }
+}
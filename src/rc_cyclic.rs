@@ -0,0 +1,235 @@
+use crate::rc::{RcBox, RcBuf};
+use std::{
+    fmt,
+    mem::ManuallyDrop,
+    ptr,
+    rc::{Rc, Weak},
+};
+
+/// Builds an `Rc<[T]>` of `len` elements whose elements can point back to the slice itself, like
+/// `Rc::new_cyclic` does for sized values.
+///
+/// `f` is given a `Weak<[T]>` to the slice before it exists, which can be cloned into the
+/// elements, and a [`CyclicSliceBuilder`] to push exactly `len` elements into. The `Weak` can't be
+/// upgraded until `rc_slice_new_cyclic` returns.
+///
+/// # Panics
+/// Panics if `f` doesn't push exactly `len` elements, or swaps the builder with another one.
+///
+/// # Examples
+/// ```rust
+/// use collect_into_rc_slice::*;
+/// use std::rc::Weak;
+///
+/// struct Node {
+///     graph: Weak<[Node]>,
+///     next: usize,
+/// }
+///
+/// let graph = rc_slice_new_cyclic(3, |weak, builder| {
+///     for i in 0..3 {
+///         assert!(weak.upgrade().is_none());
+///         builder.push(Node {
+///             graph: weak.clone(),
+///             next: (i + 1) % 3,
+///         });
+///     }
+/// });
+///
+/// let node = &graph[2];
+/// let next = &node.graph.upgrade().unwrap()[node.next];
+/// assert_eq!(next.next, 1);
+/// ```
+pub fn rc_slice_new_cyclic<T, F>(len: usize, f: F) -> Rc<[T]>
+where
+    F: FnOnce(&Weak<[T]>, &mut CyclicSliceBuilder<T>),
+{
+    let buf = ManuallyDrop::new(RcBuf::<T>::with_capacity(len));
+    let data = buf.data_ptr();
+
+    // SAFETY:
+    // - The prefix of the allocation is reserved for the `RcBox` header, and the allocation has
+    //   the layout of an `Rc<[T]>` of `len` elements.
+    // - The `Weak` and the builder own the allocation from now on, with no strong references until
+    //   the elements are initialized, so it is freed if `f` panics.
+    let weak = unsafe {
+        ptr::write(
+            (data as *mut u8).sub(RcBuf::<T>::data_offset()) as *mut RcBox<()>,
+            RcBox {
+                strong_count: 0,
+                weak_count: 1,
+                data: (),
+            },
+        );
+        Weak::from_raw(ptr::slice_from_raw_parts(data, len))
+    };
+
+    let mut builder = CyclicSliceBuilder {
+        data,
+        len: 0,
+        capacity: len,
+        weak: weak.clone(),
+    };
+    f(&weak, &mut builder);
+    // `f` could have swapped the builder with the one of a nested call, which writes to another
+    // allocation, and each builder still frees the allocation it holds if this panics
+    assert!(
+        ptr::eq(builder.data, data) && builder.capacity == len,
+        "the builder was swapped with another one"
+    );
+    assert_eq!(
+        builder.len, len,
+        "the builder wasn't filled with exactly {len} elements"
+    );
+    // the elements are owned by the `Rc` now
+    let builder = ManuallyDrop::new(builder);
+
+    // SAFETY:
+    // - All `len` elements are initialized.
+    // - The allocation is still alive, the builder has kept a weak reference to it.
+    // - The weak reference of the builder becomes the one shared by the strong references, and
+    //   the one of `weak` is dropped.
+    unsafe {
+        let header = (data as *mut u8).sub(RcBuf::<T>::data_offset()) as *mut RcBox<()>;
+        (*header).strong_count = 1;
+        Rc::from_raw(Weak::into_raw(ptr::read(&builder.weak)))
+    }
+}
+
+/// The elements of a slice being built by [`rc_slice_new_cyclic`], which are dropped if it isn't
+/// filled.
+pub struct CyclicSliceBuilder<T> {
+    data: *mut T,
+    len: usize,
+    capacity: usize,
+    weak: Weak<[T]>,
+}
+
+impl<T> CyclicSliceBuilder<T> {
+    /// # Panics
+    /// Panics if the slice is already full.
+    #[inline]
+    pub fn push(&mut self, item: T) {
+        assert!(self.len < self.capacity, "the slice is already full");
+
+        // SAFETY: There is room for one more element.
+        unsafe { self.data.add(self.len).write(item) };
+        self.len += 1;
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The number of elements the slice will have.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    #[inline]
+    pub fn as_slice(&self) -> &[T] {
+        // SAFETY: The first `len` elements are initialized.
+        unsafe { &*ptr::slice_from_raw_parts(self.data, self.len) }
+    }
+}
+
+impl<T> Extend<T> for CyclicSliceBuilder<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.push(item);
+        }
+    }
+}
+
+impl<T> Drop for CyclicSliceBuilder<T> {
+    fn drop(&mut self) {
+        // SAFETY: The first `len` elements are initialized, and the allocation is freed by
+        // `self.weak` afterwards.
+        unsafe { ptr::drop_in_place(ptr::slice_from_raw_parts_mut(self.data, self.len)) };
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for CyclicSliceBuilder<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_slice(), f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        mem,
+        panic::{self, AssertUnwindSafe},
+    };
+
+    struct Node {
+        _live: Rc<()>,
+        graph: Weak<[Node]>,
+    }
+
+    #[test]
+    fn test_rc_slice_new_cyclic_counts() {
+        let live = Rc::new(());
+        let rc = rc_slice_new_cyclic(4, |weak, builder| {
+            builder.extend((0..4).map(|_| Node {
+                _live: live.clone(),
+                graph: weak.clone(),
+            }));
+        });
+
+        assert_eq!(Rc::strong_count(&rc), 1);
+        assert_eq!(Rc::weak_count(&rc), 4);
+        assert!(rc
+            .iter()
+            .all(|node| ptr::addr_eq(node.graph.as_ptr(), Rc::as_ptr(&rc))));
+
+        drop(rc);
+        assert_eq!(Rc::strong_count(&live), 1);
+    }
+
+    #[test]
+    fn test_rc_slice_new_cyclic_too_short() {
+        let live = Rc::new(());
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            rc_slice_new_cyclic(3, |weak, builder| {
+                builder.push(Node {
+                    _live: live.clone(),
+                    graph: weak.clone(),
+                });
+            })
+        }));
+
+        assert!(result.is_err());
+        assert_eq!(Rc::strong_count(&live), 1);
+    }
+
+    #[test]
+    fn test_rc_slice_new_cyclic_swapped_builders() {
+        let live = Rc::new(());
+        let node = |weak: &Weak<[Node]>| Node {
+            _live: live.clone(),
+            graph: weak.clone(),
+        };
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            rc_slice_new_cyclic(2, |outer_weak, outer| {
+                outer.push(node(outer_weak));
+                rc_slice_new_cyclic(2, |inner_weak, inner| {
+                    inner.push(node(inner_weak));
+                    mem::swap(outer, inner);
+                    inner.push(node(inner_weak));
+                });
+            })
+        }));
+
+        assert!(result.is_err());
+        assert_eq!(Rc::strong_count(&live), 1);
+    }
+}
@@ -0,0 +1,175 @@
+use std::{
+    borrow::Borrow,
+    ffi::OsStr,
+    fmt,
+    ops::Deref,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+/// A newtype around an `Rc<Path>`, for implementing traits that can't be implemented on
+/// `Rc<Path>` itself.
+///
+/// With the `serde` feature, it deserializes by copying the path straight into a new `Rc<Path>`
+/// allocation, without going through a `PathBuf` first.
+///
+/// # Examples
+/// ```rust
+/// use collect_into_rc_slice::*;
+/// use std::path::Path;
+///
+/// let path = RcPath::from(Path::new("/etc/hosts"));
+///
+/// assert_eq!(path.file_name().unwrap(), "hosts");
+/// assert_eq!(path.clone(), path);
+/// ```
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct RcPath(pub Rc<Path>);
+
+impl RcPath {
+    #[inline]
+    pub fn as_path(&self) -> &Path {
+        &self.0
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> Rc<Path> {
+        self.0
+    }
+}
+
+impl From<Rc<Path>> for RcPath {
+    #[inline]
+    fn from(path: Rc<Path>) -> Self {
+        Self(path)
+    }
+}
+
+impl From<&Path> for RcPath {
+    #[inline]
+    fn from(path: &Path) -> Self {
+        Self(Rc::from(path))
+    }
+}
+
+impl From<PathBuf> for RcPath {
+    #[inline]
+    fn from(path: PathBuf) -> Self {
+        Self(Rc::from(path))
+    }
+}
+
+impl From<RcPath> for Rc<Path> {
+    #[inline]
+    fn from(path: RcPath) -> Self {
+        path.0
+    }
+}
+
+impl Deref for RcPath {
+    type Target = Path;
+
+    #[inline]
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl AsRef<Path> for RcPath {
+    #[inline]
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl AsRef<OsStr> for RcPath {
+    #[inline]
+    fn as_ref(&self) -> &OsStr {
+        self.0.as_os_str()
+    }
+}
+
+impl Borrow<Path> for RcPath {
+    #[inline]
+    fn borrow(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl fmt::Debug for RcPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_path(), f)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for RcPath {
+    #[inline]
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.as_path().serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for RcPath {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Visitor;
+
+        impl serde::de::Visitor<'_> for Visitor {
+            type Value = RcPath;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a path string")
+            }
+
+            fn visit_str<E: serde::de::Error>(self, s: &str) -> Result<RcPath, E> {
+                Ok(RcPath::from(Path::new(s)))
+            }
+
+            fn visit_bytes<E: serde::de::Error>(self, bytes: &[u8]) -> Result<RcPath, E> {
+                #[cfg(unix)]
+                {
+                    use std::os::unix::ffi::OsStrExt;
+                    Ok(RcPath::from(Path::new(OsStr::from_bytes(bytes))))
+                }
+
+                #[cfg(not(unix))]
+                match std::str::from_utf8(bytes) {
+                    Ok(s) => self.visit_str(s),
+                    Err(_) => Err(E::invalid_value(serde::de::Unexpected::Bytes(bytes), &self)),
+                }
+            }
+        }
+
+        deserializer.deserialize_str(Visitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rc_path() {
+        let path = RcPath::from(PathBuf::from("dir/file.txt"));
+        let inner: Rc<Path> = path.clone().into();
+
+        assert_eq!(path.parent(), Some(Path::new("dir")));
+        assert_eq!(format!("{path:?}"), r#""dir/file.txt""#);
+        assert_eq!(Rc::strong_count(&inner), 2);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_rc_path_serde() {
+        let paths: Vec<RcPath> = serde_json::from_str(r#"["a/b.toml", "/"]"#).unwrap();
+
+        assert_eq!(paths[0].extension().unwrap(), "toml");
+        assert!(paths[1].has_root());
+        assert_eq!(
+            serde_json::to_string(&paths).unwrap(),
+            r#"["a/b.toml","/"]"#
+        );
+    }
+}
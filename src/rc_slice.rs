@@ -1,9 +1,135 @@
-use crate::rc::{data_offset, padding_needed, RcBox};
-use std::{
-    alloc::{self, Layout},
-    mem, ptr,
-    rc::Rc,
-};
+#[cfg(feature = "allocator_api")]
+use crate::raw::RawBuf;
+use crate::rc::{into_rc, RcBuf};
+use std::{borrow::Borrow, fmt, ops::Deref, rc::Rc};
+
+/// A newtype around an `Rc<[T]>`, for implementing traits that can't be implemented on
+/// `Rc<[T]>` itself.
+///
+/// With the `serde` feature, it deserializes sequences straight into a single `Rc<[T]>`
+/// allocation instead of collecting them into a `Vec<T>` first.
+///
+/// # Examples
+/// ```rust
+/// use collect_into_rc_slice::*;
+///
+/// let slice = RcSlice::from((1..=3).collect_into_rc_slice());
+///
+/// assert_eq!(&*slice, &[1, 2, 3]);
+/// ```
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct RcSlice<T>(pub Rc<[T]>);
+
+impl<T> RcSlice<T> {
+    #[inline]
+    pub fn as_slice(&self) -> &[T] {
+        &self.0
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> Rc<[T]> {
+        self.0
+    }
+}
+
+impl<T> Clone for RcSlice<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T> From<Rc<[T]>> for RcSlice<T> {
+    #[inline]
+    fn from(slice: Rc<[T]>) -> Self {
+        Self(slice)
+    }
+}
+
+impl<T> From<RcSlice<T>> for Rc<[T]> {
+    #[inline]
+    fn from(slice: RcSlice<T>) -> Self {
+        slice.0
+    }
+}
+
+impl<T> FromIterator<T> for RcSlice<T> {
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self(into_rc(RcBuf::from_iter(iter.into_iter())))
+    }
+}
+
+impl<T> Deref for RcSlice<T> {
+    type Target = [T];
+
+    #[inline]
+    fn deref(&self) -> &[T] {
+        &self.0
+    }
+}
+
+impl<T> AsRef<[T]> for RcSlice<T> {
+    #[inline]
+    fn as_ref(&self) -> &[T] {
+        &self.0
+    }
+}
+
+impl<T> Borrow<[T]> for RcSlice<T> {
+    #[inline]
+    fn borrow(&self) -> &[T] {
+        &self.0
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for RcSlice<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_slice(), f)
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<T: Send> rayon::iter::FromParallelIterator<T> for RcSlice<T> {
+    /// Collects the items into per-thread segments, which are then moved into the `Rc<[T]>`
+    /// allocation in parallel.
+    fn from_par_iter<I: rayon::iter::IntoParallelIterator<Item = T>>(iter: I) -> Self {
+        Self(into_rc(crate::par::from_par_iter(iter.into_par_iter())))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize> serde::Serialize for RcSlice<T> {
+    #[inline]
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: serde::Deserialize<'de>> serde::Deserialize<'de> for RcSlice<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Visitor<T>(std::marker::PhantomData<T>);
+
+        impl<'de, T: serde::Deserialize<'de>> serde::de::Visitor<'de> for Visitor<T> {
+            type Value = RcSlice<T>;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a sequence")
+            }
+
+            fn visit_seq<S: serde::de::SeqAccess<'de>>(
+                self,
+                seq: S,
+            ) -> Result<RcSlice<T>, S::Error> {
+                Ok(RcSlice(into_rc(crate::raw::from_seq(seq)?)))
+            }
+        }
+
+        deserializer.deserialize_seq(Visitor(std::marker::PhantomData))
+    }
+}
 
 pub trait CollectIntoRcSlice<T> {
     /// Collects the iterator into an `Rc<[T]>`.
@@ -27,88 +153,38 @@ pub trait CollectIntoRcSlice<T> {
 
 impl<T, I> CollectIntoRcSlice<T> for I
 where
-    I: Iterator<Item = T>,
+    I: IntoIterator<Item = T>,
 {
     fn collect_into_rc_slice(self) -> Rc<[T]> {
-        let metadata = data_offset::<T>();
-        let align = mem::align_of::<RcBox<()>>();
-
-        // the size should be at least metadata
-        // but if bounds are known, it should be at least largest_known_bound + metadata
-        let (lower_bound, upper_bound) = self.size_hint();
-        let mut len = metadata;
-        let mut cap = upper_bound.unwrap_or(lower_bound) * mem::size_of::<T>() + metadata;
-
-        // SAFETY:
-        // - `len` is always greater than or equal to `metadata` which is non-zero.
-        // - `align` is always a power of two and non-zero.
-        // - The layout is padded to the alignment.
-        let layout = Layout::from_size_align(cap, align).unwrap().pad_to_align();
-        let mut alloc = unsafe { alloc::alloc(layout) };
-
-        if alloc.is_null() {
-            alloc::handle_alloc_error(layout);
-        }
-
-        // SAFETY: The metadata part is not meant to be valid data, so it's safe to
-        // initialize it with arbitrary data.
-        unsafe {
-            let init: *const u8 = &RcBox {
-                strong_count: 1,
-                weak_count: 1,
-                data: (),
-            } as *const _ as *const u8;
-
-            ptr::copy_nonoverlapping(init, alloc, metadata);
-        }
-
-        for item in self {
-            if len + mem::size_of::<T>() > cap {
-                // SAFETY:
-                // - `size` is always non-zero.
-                // - `align` is always a power of two and non-zero.
-                // - The layout is padded to the alignment.
-                let layout = Layout::from_size_align(cap, align).unwrap().pad_to_align();
-                cap *= 2;
-                alloc = unsafe { alloc::realloc(alloc, layout, cap) };
-
-                if alloc.is_null() {
-                    alloc::handle_alloc_error(layout);
-                }
-            }
-
-            unsafe {
-                ptr::write(alloc.add(len) as *mut T, item);
-            }
-            len += mem::size_of::<T>();
-        }
-
-        // Trim the allocation down to `len`.
-        if cap > len {
-            // SAFETY:
-            // - `cap` is always non-zero.
-            // - `align` is always a power of two and non-zero.
-            // - The layout is padded to the alignment.
-            let layout = Layout::from_size_align(cap, align).unwrap().pad_to_align();
-            alloc = unsafe { alloc::realloc(alloc, layout, len + padding_needed(len, align)) };
-
-            if alloc.is_null() {
-                alloc::handle_alloc_error(layout);
-            }
-        }
+        into_rc(RcBuf::from_iter(self.into_iter()))
+    }
+}
 
-        // SAFETY: The allocation is non-null and has the proper layout.
-        let data = unsafe {
-            ptr::slice_from_raw_parts(
-                alloc.add(metadata) as *mut T,
-                (len - metadata) / mem::size_of::<T>(),
-            )
-        };
+#[cfg(feature = "allocator_api")]
+pub trait CollectIntoRcSliceIn<T> {
+    /// Collects the iterator into an `Rc<[T], A>` allocated with `alloc`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// #![feature(allocator_api)]
+    /// use std::alloc::Global;
+    /// use collect_into_rc_slice::*;
+    ///
+    /// let arr = [1, 2, 3, 4, 5];
+    /// let rc = arr.into_iter().collect_into_rc_slice_in(Global);
+    ///
+    /// assert_eq!(&*rc, &[1, 2, 3, 4, 5]);
+    /// ```
+    fn collect_into_rc_slice_in<A: std::alloc::Allocator>(self, alloc: A) -> Rc<[T], A>;
+}
 
-        // SAFETY:
-        // - `data` is a valid pointer to a `[T]` located at the heap
-        // - `data` is part of an RcBox with proper metadata.
-        unsafe { Rc::from_raw(data) }
+#[cfg(feature = "allocator_api")]
+impl<I, T> CollectIntoRcSliceIn<T> for I
+where
+    I: IntoIterator<Item = T>,
+{
+    fn collect_into_rc_slice_in<A: std::alloc::Allocator>(self, alloc: A) -> Rc<[T], A> {
+        crate::rc::into_rc_in(RawBuf::from_iter_in(self.into_iter(), alloc))
     }
 }
 
@@ -129,4 +205,92 @@ mod tests {
         let rc = v.into_iter().collect_into_rc_slice();
         assert_eq!(&*rc, &[[0; 7]]);
     }
+
+    #[test]
+    fn test_rc_slice_into_iterator() {
+        let v = vec!["a".to_string(), "b".to_string()];
+        let refs: Rc<[&String]> = (&v).collect_into_rc_slice();
+        assert_eq!(refs.len(), 2);
+
+        let owned = v.collect_into_rc_slice();
+        assert_eq!(&*owned, ["a", "b"]);
+        assert_eq!(&*[1, 2, 3].collect_into_rc_slice(), &[1, 2, 3]);
+    }
+
+    #[test]
+    fn test_rc_slice_unknown_size() {
+        let mut iter = (0..100).map(|i| [i; 100]);
+        let rc = std::iter::from_fn(move || iter.next()).collect_into_rc_slice();
+        assert_eq!(rc.len(), 100);
+        assert!(rc.iter().enumerate().all(|(i, arr)| arr == &[i; 100]));
+    }
+
+    #[test]
+    fn test_rc_slice_zst() {
+        let rc = std::iter::repeat_n((), 10).collect_into_rc_slice();
+        assert_eq!(rc.len(), 10);
+    }
+
+    #[cfg(feature = "allocator_api")]
+    #[test]
+    fn test_rc_slice_in() {
+        use std::alloc::{AllocError, Allocator, Global, Layout};
+        use std::{cell::Cell, ptr::NonNull};
+
+        struct Counting<'a>(&'a Cell<usize>);
+
+        unsafe impl Allocator for Counting<'_> {
+            fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+                self.0.set(self.0.get() + 1);
+                Global.allocate(layout)
+            }
+
+            unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+                self.0.set(self.0.get() - 1);
+                Global.deallocate(ptr, layout)
+            }
+        }
+
+        let live = Cell::new(0);
+        let mut iter = 0..10;
+        let rc = std::iter::from_fn(move || iter.next()).collect_into_rc_slice_in(Counting(&live));
+
+        assert_eq!(&*rc, &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9]);
+        assert_eq!(live.get(), 1);
+
+        drop(rc);
+        assert_eq!(live.get(), 0);
+    }
+
+    #[test]
+    fn test_rc_slice_newtype() {
+        let slice: RcSlice<String> = ["a", "b"].into_iter().map(String::from).collect();
+        let inner: Rc<[String]> = slice.clone().into();
+
+        assert_eq!(format!("{slice:?}"), r#"["a", "b"]"#);
+        assert_eq!(Rc::strong_count(&inner), 2);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_rc_slice_deserialize() {
+        let slice: RcSlice<RcSlice<u32>> = serde_json::from_str("[[1, 2], [], [3]]").unwrap();
+
+        assert_eq!(slice.len(), 3);
+        assert_eq!(&*slice[0], &[1, 2]);
+        assert!(slice[1].is_empty());
+        assert!(serde_json::from_str::<RcSlice<u32>>(r#"[1, "2"]"#).is_err());
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_rc_slice_from_par_iter() {
+        use rayon::prelude::*;
+
+        let slice: RcSlice<()> = rayon::iter::repeat_n((), 100).collect();
+        assert_eq!(slice.len(), 100);
+
+        let empty: RcSlice<u8> = rayon::iter::empty().collect();
+        assert!(empty.is_empty());
+    }
 }
@@ -0,0 +1,299 @@
+use std::{
+    borrow::Borrow,
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+    mem,
+    ops::{Bound, Deref, Index, Range, RangeBounds},
+    rc::Rc,
+    slice::{self, SliceIndex},
+};
+
+/// A view of a range of an `Rc<[T]>`, sharing the allocation of the whole slice.
+///
+/// # Examples
+/// ```rust
+/// use collect_into_rc_slice::*;
+///
+/// let view = RcSliceView::new((0..10).collect_into_rc_slice());
+/// let (head, tail) = view.slice(2..).split_at(3);
+///
+/// assert_eq!(&*head, &[2, 3, 4]);
+/// assert_eq!(&*tail, &[5, 6, 7, 8, 9]);
+/// ```
+pub struct RcSliceView<T> {
+    parent: Rc<[T]>,
+    start: usize,
+    end: usize,
+}
+
+impl<T> RcSliceView<T> {
+    /// A view covering all of `parent`.
+    #[inline]
+    pub fn new(parent: Rc<[T]>) -> Self {
+        let end = parent.len();
+        Self {
+            parent,
+            start: 0,
+            end,
+        }
+    }
+
+    #[inline]
+    pub fn as_slice(&self) -> &[T] {
+        // SAFETY: The range is always in bounds of the parent.
+        unsafe { self.parent.get_unchecked(self.start..self.end) }
+    }
+
+    /// The whole slice this is a view of.
+    #[inline]
+    pub fn parent(&self) -> &Rc<[T]> {
+        &self.parent
+    }
+
+    /// The range of this view within [`parent`](RcSliceView::parent).
+    #[inline]
+    pub fn range(&self) -> Range<usize> {
+        self.start..self.end
+    }
+
+    /// Returns a view of a range of this view, sharing the same allocation.
+    ///
+    /// # Panics
+    /// Panics if the range is out of bounds.
+    pub fn slice(&self, range: impl RangeBounds<usize>) -> Self {
+        let bounds: (Bound<usize>, Bound<usize>) =
+            (range.start_bound().cloned(), range.end_bound().cloned());
+        // indexing panics with the same messages as slicing a `[T]`
+        let len = self.as_slice()[bounds].len();
+        let start = match bounds.0 {
+            Bound::Included(start) => start,
+            Bound::Excluded(start) => start + 1,
+            Bound::Unbounded => 0,
+        };
+
+        Self {
+            parent: self.parent.clone(),
+            start: self.start + start,
+            end: self.start + start + len,
+        }
+    }
+
+    /// Returns the view for `sub`, which has to be a part of this view.
+    ///
+    /// Zero-sized elements have no address to tell them apart, so for those the view always
+    /// starts at the beginning of this one.
+    ///
+    /// # Panics
+    /// Panics if `sub` is not contained in this view.
+    pub fn slice_ref(&self, sub: &[T]) -> Self {
+        let range = self.as_slice().as_ptr_range();
+        let sub_range = sub.as_ptr_range();
+
+        assert!(
+            range.start <= sub_range.start && sub_range.end <= range.end,
+            "the slice is not a part of this view"
+        );
+
+        let offset = if mem::size_of::<T>() == 0 {
+            0
+        } else {
+            // SAFETY: Both pointers are within the same allocation, and `sub` starts after `self`.
+            unsafe { sub_range.start.offset_from(range.start) as usize }
+        };
+
+        Self {
+            parent: self.parent.clone(),
+            start: self.start + offset,
+            end: self.start + offset + sub.len(),
+        }
+    }
+
+    /// Splits the view in two at `mid`.
+    ///
+    /// # Panics
+    /// Panics if `mid > len`.
+    pub fn split_at(&self, mid: usize) -> (Self, Self) {
+        assert!(mid <= self.len(), "mid > len");
+        (self.slice(..mid), self.slice(mid..))
+    }
+}
+
+impl<T> Clone for RcSliceView<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            parent: self.parent.clone(),
+            start: self.start,
+            end: self.end,
+        }
+    }
+}
+
+impl<T> From<Rc<[T]>> for RcSliceView<T> {
+    #[inline]
+    fn from(parent: Rc<[T]>) -> Self {
+        Self::new(parent)
+    }
+}
+
+impl<T> Deref for RcSliceView<T> {
+    type Target = [T];
+
+    #[inline]
+    fn deref(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+impl<T> AsRef<[T]> for RcSliceView<T> {
+    #[inline]
+    fn as_ref(&self) -> &[T] {
+        self
+    }
+}
+
+impl<T, I: SliceIndex<[T]>> Index<I> for RcSliceView<T> {
+    type Output = I::Output;
+
+    #[inline]
+    fn index(&self, index: I) -> &I::Output {
+        &(**self)[index]
+    }
+}
+
+/// An empty view.
+impl<T> Default for RcSliceView<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new(Rc::default())
+    }
+}
+
+impl<'a, T> IntoIterator for &'a RcSliceView<T> {
+    type Item = &'a T;
+    type IntoIter = slice::Iter<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T> Borrow<[T]> for RcSliceView<T> {
+    #[inline]
+    fn borrow(&self) -> &[T] {
+        self
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for RcSliceView<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_slice(), f)
+    }
+}
+
+impl<T: PartialEq> PartialEq for RcSliceView<T> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl<T: PartialEq> PartialEq<[T]> for RcSliceView<T> {
+    #[inline]
+    fn eq(&self, other: &[T]) -> bool {
+        self.as_slice() == other
+    }
+}
+
+impl<T: Eq> Eq for RcSliceView<T> {}
+
+impl<T: PartialOrd> PartialOrd for RcSliceView<T> {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.as_slice().partial_cmp(other.as_slice())
+    }
+}
+
+impl<T: Ord> Ord for RcSliceView<T> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_slice().cmp(other.as_slice())
+    }
+}
+
+impl<T: Hash> Hash for RcSliceView<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_slice().hash(state);
+    }
+}
+
+/// Splits `rc` into views of `chunk_len` elements that all share its allocation, with the last
+/// one being shorter if the length doesn't divide evenly, like `slice::chunks`.
+///
+/// # Panics
+/// Panics if `chunk_len` is 0.
+///
+/// # Examples
+/// ```rust
+/// use collect_into_rc_slice::*;
+///
+/// let rc = (0..7).collect_into_rc_slice();
+/// let chunks: Vec<_> = rc_slice_chunks(rc, 3).collect();
+///
+/// assert_eq!(&*chunks[0], &[0, 1, 2]);
+/// assert_eq!(&*chunks[1], &[3, 4, 5]);
+/// assert_eq!(&*chunks[2], &[6]);
+/// ```
+pub fn rc_slice_chunks<T>(rc: Rc<[T]>, chunk_len: usize) -> impl Iterator<Item = RcSliceView<T>> {
+    assert!(chunk_len != 0, "chunk size must be non-zero");
+
+    let len = rc.len();
+    (0..len).step_by(chunk_len).map(move |start| RcSliceView {
+        parent: rc.clone(),
+        start,
+        end: len.min(start.saturating_add(chunk_len)),
+    })
+}
+
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize> serde::Serialize for RcSliceView<T> {
+    #[inline]
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CollectIntoRcSlice;
+
+    #[test]
+    fn test_rc_slice_chunks() {
+        let rc = (0..10).map(|i| i.to_string()).collect_into_rc_slice();
+        let chunks: Vec<_> = rc_slice_chunks(rc.clone(), 4).collect();
+
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(&*chunks[2], &["8", "9"]);
+        assert_eq!(chunks[1].range(), 4..8);
+        assert_eq!(Rc::strong_count(&rc), 4);
+    }
+
+    #[test]
+    fn test_rc_slice_view_slice_ref() {
+        let view = RcSliceView::new((0..10).collect_into_rc_slice());
+        let sub = view.slice_ref(&view[3..5]);
+
+        assert_eq!(sub.range(), 3..5);
+        assert_eq!(sub.slice(1..), view.slice(4..5));
+    }
+
+    #[test]
+    fn test_rc_slice_chunks_zst() {
+        let rc = std::iter::repeat_n((), 5).collect_into_rc_slice();
+
+        assert_eq!(rc_slice_chunks(rc, 2).map(|c| c.len()).sum::<usize>(), 5);
+    }
+}
@@ -1,10 +1,155 @@
-use crate::rc::{data_offset, padding_needed, RcBox};
-use std::{
-    alloc::{self, Layout},
-    mem, ptr,
-    rc::Rc,
-    slice,
+use crate::{
+    into_char::IntoChar,
+    rc::{into_rc_str, RcBuf},
 };
+use std::{borrow::Borrow, fmt, ops::Deref, rc::Rc};
+
+/// A newtype around an `Rc<str>`, for implementing traits that can't be implemented on `Rc<str>`
+/// itself.
+///
+/// With the `serde` feature, it deserializes by copying the string straight into a new
+/// `Rc<str>` allocation, without going through a `String` first.
+///
+/// # Examples
+/// ```rust
+/// use collect_into_rc_slice::*;
+/// use std::rc::Rc;
+///
+/// let s = RcStr::from("shared");
+/// let t = s.clone();
+///
+/// assert_eq!(&*t, "shared");
+/// assert!(Rc::ptr_eq(&s.0, &t.0));
+/// ```
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct RcStr(pub Rc<str>);
+
+impl RcStr {
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> Rc<str> {
+        self.0
+    }
+}
+
+/// Copies `s` into a new `Rc<str>`.
+pub(crate) fn rc_str_from(s: &str) -> Rc<str> {
+    let mut buf = RcBuf::with_capacity(s.len());
+    buf.extend_from_slice(s.as_bytes());
+    // SAFETY: The buffer is a copy of a `str`.
+    unsafe { into_rc_str(buf) }
+}
+
+impl From<Rc<str>> for RcStr {
+    #[inline]
+    fn from(s: Rc<str>) -> Self {
+        Self(s)
+    }
+}
+
+impl From<&str> for RcStr {
+    #[inline]
+    fn from(s: &str) -> Self {
+        Self(rc_str_from(s))
+    }
+}
+
+impl From<RcStr> for Rc<str> {
+    #[inline]
+    fn from(s: RcStr) -> Self {
+        s.0
+    }
+}
+
+impl Deref for RcStr {
+    type Target = str;
+
+    #[inline]
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for RcStr {
+    #[inline]
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Borrow<str> for RcStr {
+    #[inline]
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for RcStr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl fmt::Display for RcStr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self.as_str(), f)
+    }
+}
+
+impl PartialEq<str> for RcStr {
+    #[inline]
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for RcStr {
+    #[inline]
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for RcStr {
+    #[inline]
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for RcStr {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Visitor;
+
+        impl serde::de::Visitor<'_> for Visitor {
+            type Value = RcStr;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a string")
+            }
+
+            fn visit_str<E: serde::de::Error>(self, s: &str) -> Result<RcStr, E> {
+                Ok(RcStr(rc_str_from(s)))
+            }
+
+            fn visit_bytes<E: serde::de::Error>(self, bytes: &[u8]) -> Result<RcStr, E> {
+                match std::str::from_utf8(bytes) {
+                    Ok(s) => self.visit_str(s),
+                    Err(_) => Err(E::invalid_value(serde::de::Unexpected::Bytes(bytes), &self)),
+                }
+            }
+        }
+
+        deserializer.deserialize_str(Visitor)
+    }
+}
 
 pub trait CollectIntoRcStr {
     /// Collects the iterator into an `Rc<str>`.
@@ -27,85 +172,12 @@ pub trait CollectIntoRcStr {
 
 impl<T> CollectIntoRcStr for T
 where
-    T: Iterator<Item = char>,
+    T: IntoIterator,
+    T::Item: IntoChar,
 {
     fn collect_into_rc_str(self) -> Rc<str> {
-        let metadata = data_offset::<u8>();
-        let align = mem::align_of::<RcBox<()>>();
-
-        // the size should be at least metadata
-        // but if bounds are known, it should be at least largest_known_bound+metadata
-        let (lower_bound, upper_bound) = self.size_hint();
-        let mut len = metadata;
-        let mut cap = upper_bound.unwrap_or(lower_bound) + metadata;
-
-        // SAFETY:
-        // - `len` is always greater than or equal to `metadata` which is non-zero.
-        // - `align` is always a power of two and non-zero.
-        // - The layout is padded to the alignment.
-        let layout = Layout::from_size_align(cap, align).unwrap().pad_to_align();
-        let mut alloc = unsafe { alloc::alloc(layout) };
-
-        if alloc.is_null() {
-            alloc::handle_alloc_error(layout);
-        }
-
-        // SAFETY: The metadata part is not meant to be valid UTF-8 data, so it's safe to
-        // initialize it with arbitrary data.
-        unsafe {
-            let init: *const u8 = &RcBox {
-                strong_count: 1,
-                weak_count: 1,
-                data: (),
-            } as *const _ as *const u8;
-
-            ptr::copy_nonoverlapping(init, alloc, metadata);
-        }
-
-        for c in self {
-            let new_len = len + c.len_utf8();
-            if new_len > cap {
-                // SAFETY:
-                // - `size` is always non-zero.
-                // - `align` is always a power of two and non-zero.
-                // - The layout is padded to the alignment.
-                let layout = Layout::from_size_align(cap, align).unwrap().pad_to_align();
-                cap *= 2;
-                alloc = unsafe { alloc::realloc(alloc, layout, cap) };
-
-                if alloc.is_null() {
-                    alloc::handle_alloc_error(layout);
-                }
-            }
-
-            unsafe {
-                let ptr = alloc.add(len);
-                len = new_len;
-                c.encode_utf8(slice::from_raw_parts_mut(ptr, c.len_utf8()));
-            }
-        }
-
-        // Trim the allocation down to `len`.
-        if cap > len {
-            // SAFETY:
-            // - `cap` is always non-zero.
-            // - `align` is always a power of two and non-zero.
-            // - The layout is padded to the alignment.
-            let layout = Layout::from_size_align(cap, align).unwrap().pad_to_align();
-            alloc = unsafe { alloc::realloc(alloc, layout, len + padding_needed(len, align)) };
-
-            if alloc.is_null() {
-                alloc::handle_alloc_error(layout);
-            }
-        }
-
-        let data =
-            unsafe { ptr::slice_from_raw_parts(alloc.add(metadata), len - metadata) as *const str };
-
-        // SAFETY:
-        // - `data` is a valid pointer to a `str` located at the heap
-        // - `data` is part of an RcBox with proper metadata.
-        unsafe { Rc::from_raw(data) }
+        // SAFETY: The buffer is made up of UTF-8 encoded `char`s.
+        unsafe { into_rc_str(RcBuf::from_chars(self.into_iter().map(IntoChar::into_char))) }
     }
 }
 
@@ -143,4 +215,24 @@ mod tests {
         assert_eq!(Rc::strong_count(&s), 1);
         assert_eq!(Rc::weak_count(&s), 0);
     }
+
+    #[test]
+    fn test_rc_str_newtype() {
+        let s = RcStr::from("abc");
+        let inner: Rc<str> = s.clone().into();
+
+        assert_eq!(s.as_str(), "abc");
+        assert_eq!(format!("{s:?}"), "\"abc\"");
+        assert_eq!(Rc::strong_count(&inner), 2);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_rc_str_deserialize() {
+        let s: Vec<RcStr> = serde_json::from_str(r#"["borrowed", "esc\"aped"]"#).unwrap();
+
+        assert_eq!(s[0].as_str(), "borrowed");
+        assert_eq!(s[1].as_str(), "esc\"aped");
+        assert!(serde_json::from_str::<RcStr>("1").is_err());
+    }
 }
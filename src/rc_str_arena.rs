@@ -0,0 +1,190 @@
+use crate::{
+    rc::{into_rc_str, RcBuf},
+    RcSubstr,
+};
+use std::{fmt, rc::Rc, str};
+
+const DEFAULT_BLOCK_SIZE: usize = 4096;
+
+/// An arena packing many small strings into a few large `Rc<str>` blocks.
+///
+/// Strings are pushed into the arena one at a time, and [`finish`](RcStrArena::finish) then turns
+/// them into [`RcSubstr`]s of the blocks, so thousands of tiny strings only cost one allocation per
+/// block. A block can't be written to anymore once a substring of it is handed out, which is why
+/// the substrings are only available after the arena is finished; until then, the pushed strings
+/// are identified by the index returned by [`push`](RcStrArena::push).
+///
+/// # Examples
+/// ```rust
+/// use collect_into_rc_slice::*;
+/// use std::rc::Rc;
+///
+/// let mut arena = RcStrArena::new();
+/// let name = arena.push("name");
+/// let value = arena.push("value");
+///
+/// assert_eq!(arena.get(value), "value");
+///
+/// let strings = arena.finish();
+/// assert_eq!(strings[name], "name");
+/// assert!(Rc::ptr_eq(strings[name].parent(), strings[value].parent()));
+/// ```
+pub struct RcStrArena {
+    block_size: usize,
+    blocks: Vec<Rc<str>>,
+    current: RcBuf<u8>,
+    spans: Vec<Span>,
+}
+
+/// The location of a pushed string, as the index of its block and its byte range in it.
+#[derive(Clone, Copy)]
+struct Span {
+    block: usize,
+    start: usize,
+    end: usize,
+}
+
+impl RcStrArena {
+    #[inline]
+    pub fn new() -> Self {
+        Self::with_block_size(DEFAULT_BLOCK_SIZE)
+    }
+
+    /// An arena whose blocks are `block_size` bytes long. Strings longer than that get a block of
+    /// their own.
+    pub fn with_block_size(block_size: usize) -> Self {
+        Self {
+            block_size,
+            blocks: Vec::new(),
+            current: RcBuf::with_capacity(block_size),
+            spans: Vec::new(),
+        }
+    }
+
+    /// Copies `s` into the arena, returning its index in the strings returned by
+    /// [`finish`](RcStrArena::finish).
+    pub fn push(&mut self, s: &str) -> usize {
+        if self.current.len() + s.len() > self.current.capacity() {
+            let next = RcBuf::with_capacity(self.block_size.max(s.len()));
+            self.seal(next);
+        }
+
+        let start = self.current.len();
+        self.current.extend_from_slice(s.as_bytes());
+        self.spans.push(Span {
+            block: self.blocks.len(),
+            start,
+            end: self.current.len(),
+        });
+
+        self.spans.len() - 1
+    }
+
+    /// Finishes the current block, if it has any strings, and starts filling `next`.
+    fn seal(&mut self, next: RcBuf<u8>) {
+        let current = std::mem::replace(&mut self.current, next);
+        if current.len() > 0 {
+            // SAFETY: The block is a concatenation of `str`s.
+            self.blocks.push(unsafe { into_rc_str(current) });
+        }
+    }
+
+    /// Returns the string pushed at `index`.
+    ///
+    /// # Panics
+    /// Panics if `index` is out of bounds.
+    pub fn get(&self, index: usize) -> &str {
+        let Span { block, start, end } = self.spans[index];
+        match self.blocks.get(block) {
+            Some(block) => &block[start..end],
+            // SAFETY: The current block is a concatenation of `str`s and the span is one of them.
+            None => unsafe { str::from_utf8_unchecked(&self.current.as_slice()[start..end]) },
+        }
+    }
+
+    /// The number of strings pushed into the arena.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.spans.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.spans.is_empty()
+    }
+
+    /// The number of blocks allocated so far, including the one being filled.
+    #[inline]
+    pub fn block_count(&self) -> usize {
+        self.blocks.len() + 1
+    }
+
+    /// Returns the pushed strings as substrings of the blocks, in the order they were pushed in.
+    pub fn finish(mut self) -> Vec<RcSubstr> {
+        self.seal(RcBuf::with_capacity(0));
+        let empty = RcSubstr::new(Rc::from(""));
+
+        self.spans
+            .iter()
+            .map(|&Span { block, start, end }| match self.blocks.get(block) {
+                Some(block) => RcSubstr::new(block.clone()).slice(start..end),
+                // only empty strings are left in a block that was never sealed
+                None => empty.clone(),
+            })
+            .collect()
+    }
+}
+
+impl Default for RcStrArena {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for RcStrArena {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries((0..self.len()).map(|i| self.get(i)))
+            .finish()
+    }
+}
+
+impl<'a> Extend<&'a str> for RcStrArena {
+    fn extend<I: IntoIterator<Item = &'a str>>(&mut self, iter: I) {
+        for s in iter {
+            self.push(s);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rc_str_arena_blocks() {
+        let mut arena = RcStrArena::with_block_size(8);
+        arena.extend(["abc", "defg", "h", "a long string", "", "ij"]);
+
+        assert_eq!(arena.block_count(), 3);
+        assert_eq!(arena.get(3), "a long string");
+        assert_eq!(arena.get(5), "ij");
+
+        let strings = arena.finish();
+        assert_eq!(strings, ["abc", "defg", "h", "a long string", "", "ij"]);
+        assert!(Rc::ptr_eq(strings[0].parent(), strings[1].parent()));
+        assert_eq!(&**strings[2].parent(), "abcdefgh");
+        assert_eq!(&**strings[5].parent(), "ij");
+        assert_eq!(Rc::strong_count(strings[0].parent()), 3);
+    }
+
+    #[test]
+    fn test_rc_str_arena_empty_strings() {
+        let mut arena = RcStrArena::new();
+        let empty = arena.push("");
+
+        assert_eq!(arena.get(empty), "");
+        assert_eq!(arena.finish()[empty], "");
+    }
+}
@@ -0,0 +1,226 @@
+use crate::rc::{into_rc_str, RcBuf};
+use std::{collections::HashSet, fmt, mem, rc::Rc};
+
+/// Memory usage of an interner.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct InternerStats {
+    /// The number of interned strings.
+    pub len: usize,
+    /// The total length of the interned strings in bytes.
+    pub string_bytes: usize,
+    /// The bytes allocated for the strings, including their reference counts and padding, and
+    /// for the table of the interner.
+    pub allocated_bytes: usize,
+}
+
+/// A string interner that hands out deduplicated `Rc<str>`s.
+///
+/// Every distinct string is stored once, in a single allocation holding both its reference counts
+/// and its bytes, and interning it again only clones the existing `Rc<str>`.
+///
+/// The interner keeps its strings alive until they are purged, either explicitly with
+/// [`purge_dead`](RcStrInterner::purge_dead) or automatically with
+/// [`set_auto_purge`](RcStrInterner::set_auto_purge).
+///
+/// # Examples
+/// ```rust
+/// use collect_into_rc_slice::*;
+/// use std::rc::Rc;
+///
+/// let mut interner = RcStrInterner::new();
+/// let a = interner.get_or_intern("foo");
+/// let b = interner.get_or_intern("foo");
+///
+/// assert!(Rc::ptr_eq(&a, &b));
+/// assert_eq!(interner.len(), 1);
+/// ```
+#[derive(Default)]
+pub struct RcStrInterner {
+    strings: HashSet<Rc<str>>,
+    string_bytes: usize,
+    auto_purge: Option<usize>,
+    interned_since_purge: usize,
+}
+
+impl RcStrInterner {
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// An interner with room for at least `capacity` strings before its table has to grow.
+    #[inline]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            strings: HashSet::with_capacity(capacity),
+            ..Self::default()
+        }
+    }
+
+    /// Returns the interned copy of `s`, interning it first if needed.
+    pub fn get_or_intern(&mut self, s: &str) -> Rc<str> {
+        if let Some(rc) = self.strings.get(s) {
+            return rc.clone();
+        }
+
+        let mut buf = RcBuf::with_capacity(s.len());
+        buf.extend_from_slice(s.as_bytes());
+        // SAFETY: The buffer is a copy of a `str`.
+        let rc = unsafe { into_rc_str(buf) };
+
+        self.string_bytes += s.len();
+        self.strings.insert(rc.clone());
+
+        self.interned_since_purge += 1;
+        if self
+            .auto_purge
+            .is_some_and(|n| self.interned_since_purge >= n)
+        {
+            self.purge_dead();
+        }
+
+        rc
+    }
+
+    /// Drops the strings that are only referenced by the interner anymore, returning how many were
+    /// dropped.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use collect_into_rc_slice::*;
+    ///
+    /// let mut interner = RcStrInterner::new();
+    /// let kept = interner.get_or_intern("kept");
+    /// interner.get_or_intern("temporary");
+    ///
+    /// assert_eq!(interner.purge_dead(), 1);
+    /// assert!(interner.contains("kept"));
+    /// ```
+    pub fn purge_dead(&mut self) -> usize {
+        let len = self.strings.len();
+        let mut string_bytes = self.string_bytes;
+
+        self.strings.retain(|s| {
+            let alive = Rc::strong_count(s) > 1;
+            if !alive {
+                string_bytes -= s.len();
+            }
+            alive
+        });
+
+        self.string_bytes = string_bytes;
+        self.interned_since_purge = 0;
+        len - self.strings.len()
+    }
+
+    /// Makes the interner call [`purge_dead`](RcStrInterner::purge_dead) every `threshold` newly
+    /// interned strings, or never if it is `None`, which is the default.
+    #[inline]
+    pub fn set_auto_purge(&mut self, threshold: Option<usize>) {
+        self.auto_purge = threshold;
+    }
+
+    /// Returns the interned copy of `s`, if there is one.
+    #[inline]
+    pub fn get(&self, s: &str) -> Option<Rc<str>> {
+        self.strings.get(s).cloned()
+    }
+
+    #[inline]
+    pub fn contains(&self, s: &str) -> bool {
+        self.strings.contains(s)
+    }
+
+    /// The number of interned strings.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+
+    /// Iterates over the interned strings in an arbitrary order.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = &Rc<str>> {
+        self.strings.iter()
+    }
+
+    /// The memory used by the interner and its strings.
+    pub fn stats(&self) -> InternerStats {
+        let strings: usize = self
+            .strings
+            .iter()
+            .map(|s| RcBuf::<u8>::layout(s.len()).size())
+            .sum();
+
+        InternerStats {
+            len: self.len(),
+            string_bytes: self.string_bytes,
+            allocated_bytes: strings + self.strings.capacity() * mem::size_of::<Rc<str>>(),
+        }
+    }
+}
+
+impl fmt::Debug for RcStrInterner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(&self.strings).finish()
+    }
+}
+
+impl<'a> Extend<&'a str> for RcStrInterner {
+    fn extend<I: IntoIterator<Item = &'a str>>(&mut self, iter: I) {
+        for s in iter {
+            self.get_or_intern(s);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rc_str_interner() {
+        let mut interner = RcStrInterner::new();
+        interner.extend(["if", "else", "if"]);
+        let kw = interner.get("if").unwrap();
+
+        assert_eq!(interner.len(), 2);
+        assert!(interner.get("while").is_none());
+        assert!(Rc::ptr_eq(&kw, &interner.get_or_intern("if")));
+        assert_eq!(Rc::strong_count(&kw), 2);
+    }
+
+    #[test]
+    fn test_rc_str_interner_auto_purge() {
+        let mut interner = RcStrInterner::new();
+        interner.set_auto_purge(Some(3));
+        let a = interner.get_or_intern("a");
+        interner.get_or_intern("b");
+        assert_eq!(interner.len(), 2);
+
+        interner.get_or_intern("c");
+        assert_eq!(interner.len(), 2);
+        assert!(interner.contains("a"));
+        assert_eq!(interner.stats().string_bytes, 2);
+
+        drop(a);
+        assert_eq!(interner.purge_dead(), 2);
+        assert!(interner.is_empty());
+    }
+
+    #[test]
+    fn test_rc_str_interner_stats() {
+        let mut interner = RcStrInterner::with_capacity(4);
+        interner.get_or_intern("hello");
+        interner.get_or_intern("");
+
+        let stats = interner.stats();
+        assert_eq!(stats.len, 2);
+        assert_eq!(stats.string_bytes, 5);
+        assert!(stats.allocated_bytes >= 2 * 2 * mem::size_of::<usize>() + 5);
+    }
+}
@@ -0,0 +1,407 @@
+use crate::{SplitIntoSubstrs, SplitPattern};
+use std::{
+    borrow::Borrow,
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+    ops::{Bound, Deref, Index, Range, RangeBounds},
+    rc::Rc,
+    slice::SliceIndex,
+};
+
+/// A substring of an `Rc<str>`, sharing the allocation of the whole string.
+///
+/// Slicing an `RcSubstr` only bumps the reference count, so a tokenizer can collect its input into
+/// one `Rc<str>` and hand out any number of substrings of it without copying.
+///
+/// # Examples
+/// ```rust
+/// use collect_into_rc_slice::*;
+///
+/// let source = RcSubstr::from("let x = 42;".chars().collect_into_rc_str());
+/// let ident = source.slice(4..5);
+/// let (value, rest) = source.slice(8..).split_at(2);
+///
+/// assert_eq!(&*ident, "x");
+/// assert_eq!(&*value, "42");
+/// assert_eq!(&*rest, ";");
+/// assert_eq!(&**value.parent(), "let x = 42;");
+/// ```
+#[derive(Clone)]
+pub struct RcSubstr {
+    parent: Rc<str>,
+    start: usize,
+    end: usize,
+}
+
+impl RcSubstr {
+    /// A substring covering all of `parent`.
+    #[inline]
+    pub fn new(parent: Rc<str>) -> Self {
+        let end = parent.len();
+        Self {
+            parent,
+            start: 0,
+            end,
+        }
+    }
+
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        // SAFETY: The range is always in bounds and on `char` boundaries of the parent.
+        unsafe { self.parent.get_unchecked(self.start..self.end) }
+    }
+
+    /// The whole string this is a substring of.
+    #[inline]
+    pub fn parent(&self) -> &Rc<str> {
+        &self.parent
+    }
+
+    /// The byte range of this substring within [`parent`](RcSubstr::parent).
+    #[inline]
+    pub fn range(&self) -> Range<usize> {
+        self.start..self.end
+    }
+
+    /// Returns a substring of this substring, sharing the same allocation.
+    ///
+    /// # Panics
+    /// Panics if the range is out of bounds or doesn't fall on `char` boundaries, just like
+    /// indexing a `str`.
+    pub fn slice(&self, range: impl RangeBounds<usize>) -> Self {
+        let bounds: (Bound<usize>, Bound<usize>) =
+            (range.start_bound().cloned(), range.end_bound().cloned());
+        let sub = &self.as_str()[bounds];
+        self.slice_ref(sub)
+    }
+
+    /// Returns the substring for `sub`, which has to be a part of this substring, e.g. one
+    /// returned by `str::split` or `str::trim`.
+    ///
+    /// # Panics
+    /// Panics if `sub` is not contained in this substring, or if it is an empty string in the
+    /// middle of one of its `char`s.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use collect_into_rc_slice::*;
+    ///
+    /// let line = RcSubstr::from("  key = value ".chars().collect_into_rc_str());
+    /// let key = line.slice_ref(line.split('=').next().unwrap().trim());
+    ///
+    /// assert_eq!(&*key, "key");
+    /// assert_eq!(key.range(), 2..5);
+    /// ```
+    pub fn slice_ref(&self, sub: &str) -> Self {
+        let base = self.as_str().as_ptr() as usize;
+        let offset = (sub.as_ptr() as usize).wrapping_sub(base);
+
+        assert!(
+            offset <= self.len() && sub.len() <= self.len() - offset,
+            "the string is not a part of this substring"
+        );
+        // only an empty string can start in the middle of a `char` of the parent
+        assert!(
+            self.as_str().is_char_boundary(offset),
+            "the string doesn't start on a `char` boundary of this substring"
+        );
+
+        Self {
+            parent: self.parent.clone(),
+            start: self.start + offset,
+            end: self.start + offset + sub.len(),
+        }
+    }
+
+    /// Splits the substring in two at the byte index `mid`.
+    ///
+    /// # Panics
+    /// Panics if `mid` is out of bounds or not on a `char` boundary, just like `str::split_at`.
+    pub fn split_at(&self, mid: usize) -> (Self, Self) {
+        let (head, tail) = self.as_str().split_at(mid);
+        (self.slice_ref(head), self.slice_ref(tail))
+    }
+
+    /// Splits the substring by `pat`, like `str::split`, returning substrings that share the same
+    /// allocation and can outlive `self`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use collect_into_rc_slice::*;
+    ///
+    /// let csv = RcSubstr::from("a,b,,c".chars().collect_into_rc_str());
+    /// let fields: Vec<RcSubstr> = csv.split(',').collect();
+    ///
+    /// assert_eq!(fields, ["a", "b", "", "c"]);
+    /// ```
+    pub fn split<'a, P>(&'a self, pat: P) -> impl Iterator<Item = RcSubstr> + 'a
+    where
+        P: SplitPattern + 'a,
+    {
+        pat.split(self.as_str()).map(|sub| self.slice_ref(sub))
+    }
+
+    /// Splits the substring by `pat` into at most `n` substrings, like `str::splitn`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use collect_into_rc_slice::*;
+    ///
+    /// let header = RcSubstr::from("Host: example.com:80".chars().collect_into_rc_str());
+    /// let parts: Vec<RcSubstr> = header.splitn(2, ':').collect();
+    ///
+    /// assert_eq!(parts, ["Host", " example.com:80"]);
+    /// ```
+    pub fn splitn<'a, P>(&'a self, n: usize, pat: P) -> impl Iterator<Item = RcSubstr> + 'a
+    where
+        P: SplitPattern + 'a,
+    {
+        pat.splitn(self.as_str(), n).map(|sub| self.slice_ref(sub))
+    }
+
+    /// Iterates over the lines of the substring, like `str::lines`, as substrings sharing the
+    /// same allocation. Lines end with `\n` or `\r\n`, and the line endings are not included.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use collect_into_rc_slice::*;
+    ///
+    /// let text = RcSubstr::from("first\r\nsecond\nthird\n".chars().collect_into_rc_str());
+    /// let lines: Vec<RcSubstr> = text.lines().collect();
+    ///
+    /// assert_eq!(lines, ["first", "second", "third"]);
+    /// ```
+    pub fn lines(&self) -> impl Iterator<Item = RcSubstr> + '_ {
+        self.as_str().lines().map(|line| self.slice_ref(line))
+    }
+}
+
+impl SplitIntoSubstrs for Rc<str> {
+    type Substr = RcSubstr;
+
+    fn split_substrs<'a, P>(&'a self, pat: P) -> impl Iterator<Item = RcSubstr> + 'a
+    where
+        P: SplitPattern + 'a,
+    {
+        let whole = RcSubstr::new(self.clone());
+        pat.split(self).map(move |sub| whole.slice_ref(sub))
+    }
+
+    fn splitn_substrs<'a, P>(&'a self, n: usize, pat: P) -> impl Iterator<Item = RcSubstr> + 'a
+    where
+        P: SplitPattern + 'a,
+    {
+        let whole = RcSubstr::new(self.clone());
+        pat.splitn(self, n).map(move |sub| whole.slice_ref(sub))
+    }
+
+    fn lines_substrs(&self) -> impl Iterator<Item = RcSubstr> + '_ {
+        let whole = RcSubstr::new(self.clone());
+        self.lines().map(move |line| whole.slice_ref(line))
+    }
+}
+
+impl From<Rc<str>> for RcSubstr {
+    #[inline]
+    fn from(parent: Rc<str>) -> Self {
+        Self::new(parent)
+    }
+}
+
+impl Deref for RcSubstr {
+    type Target = str;
+
+    #[inline]
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl AsRef<str> for RcSubstr {
+    #[inline]
+    fn as_ref(&self) -> &str {
+        self
+    }
+}
+
+impl AsRef<[u8]> for RcSubstr {
+    #[inline]
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl<I: SliceIndex<str>> Index<I> for RcSubstr {
+    type Output = I::Output;
+
+    #[inline]
+    fn index(&self, index: I) -> &I::Output {
+        &(**self)[index]
+    }
+}
+
+/// An empty substring.
+impl Default for RcSubstr {
+    #[inline]
+    fn default() -> Self {
+        Self::new(Rc::default())
+    }
+}
+
+impl Borrow<str> for RcSubstr {
+    #[inline]
+    fn borrow(&self) -> &str {
+        self
+    }
+}
+
+impl fmt::Debug for RcSubstr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl fmt::Display for RcSubstr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self.as_str(), f)
+    }
+}
+
+impl PartialEq for RcSubstr {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for RcSubstr {}
+
+impl PartialEq<str> for RcSubstr {
+    #[inline]
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for RcSubstr {
+    #[inline]
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl PartialOrd for RcSubstr {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for RcSubstr {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+impl Hash for RcSubstr {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state);
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for RcSubstr {
+    #[inline]
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CollectIntoRcStr;
+
+    #[test]
+    fn test_rc_substr() {
+        let s = RcSubstr::from("héllo wörld".chars().collect_into_rc_str());
+        let (hello, world) = s.split_at(6);
+
+        assert_eq!(hello, "héllo");
+        assert_eq!(world.slice(1..), "wörld");
+        assert_eq!(world.slice(1..).range(), 7..13);
+        assert!(Rc::ptr_eq(hello.parent(), world.parent()));
+        assert_eq!(Rc::strong_count(s.parent()), 3);
+    }
+
+    #[test]
+    fn test_rc_substr_split() {
+        let s = RcSubstr::from("one two  three".chars().collect_into_rc_str());
+        let words: Vec<_> = s.split(char::is_whitespace).collect();
+        let rest = s.splitn(2, " ").nth(1).unwrap();
+
+        drop(s);
+        assert_eq!(words, ["one", "two", "", "three"]);
+        assert_eq!(words[3].range(), 9..14);
+        assert_eq!(rest, "two  three");
+
+        let parent: Rc<str> = Rc::from("k=v=w");
+        let pair: Vec<_> = parent.splitn_substrs(2, '=').collect();
+        assert_eq!(pair, ["k", "v=w"]);
+        assert!(Rc::ptr_eq(pair[1].parent(), &parent));
+    }
+
+    #[test]
+    fn test_rc_substr_lines() {
+        let text = RcSubstr::from("a\n\r\nb\r\nc".chars().collect_into_rc_str());
+        let lines: Vec<_> = text.lines().collect();
+
+        assert_eq!(lines, ["a", "", "b", "c"]);
+        assert_eq!(lines[2].range(), 4..5);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_rc_substr_char_boundary() {
+        let s = RcSubstr::from("héllo".chars().collect_into_rc_str());
+        s.slice(..2);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_rc_substr_slice_ref_outside() {
+        let s = RcSubstr::from("hello".chars().collect_into_rc_str());
+        s.slice(1..).slice_ref(&s[..1]);
+    }
+
+    #[test]
+    #[should_panic(expected = "`char` boundary")]
+    // indexing the `str` itself would panic instead of building the empty string
+    #[allow(clippy::string_from_utf8_as_bytes)]
+    fn test_rc_substr_slice_ref_mid_char() {
+        let s = RcSubstr::from("é".chars().collect_into_rc_str());
+        s.slice_ref(std::str::from_utf8(&s.as_bytes()[1..1]).unwrap());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_rc_substr_serialize() {
+        let s = RcSubstr::from(Rc::<str>::from("a \"quoted\" word"));
+
+        assert_eq!(
+            serde_json::to_string(&s.slice(2..10)).unwrap(),
+            r#""\"quoted\"""#
+        );
+    }
+
+    #[test]
+    fn test_rc_substr_std_traits() {
+        let s = RcSubstr::from(Rc::<str>::from("key=value")).slice(4..);
+
+        assert_eq!(&s[..3], "val");
+        assert_eq!(AsRef::<[u8]>::as_ref(&s), b"value");
+        assert!(RcSubstr::default().is_empty());
+    }
+}
@@ -0,0 +1,446 @@
+#[cfg(target_has_atomic = "ptr")]
+use crate::arc::{into_arc, ArcBuf};
+use crate::{
+    raw::{Prefix, RawBuf},
+    rc::{into_rc, into_rc_str, RcBuf},
+};
+use std::{
+    error::Error,
+    fmt,
+    io::{self, BufRead, ErrorKind, Read},
+    mem::MaybeUninit,
+    rc::Rc,
+    str,
+};
+#[cfg(target_has_atomic = "ptr")]
+use std::{fs::File, path::Path, sync::Arc};
+
+/// The size of the stack buffer used to check for the end of the stream when a buffer is full.
+pub(crate) const PROBE_SIZE: usize = 32;
+
+/// Fills a buffer from a reader, keeping track of how much of its spare capacity is already
+/// zeroed so it is only zeroed once.
+struct Filler<'a, P: Prefix> {
+    buf: &'a mut RawBuf<P, u8>,
+    initialized: usize,
+}
+
+impl<'a, P: Prefix> Filler<'a, P> {
+    #[inline]
+    fn new(buf: &'a mut RawBuf<P, u8>) -> Self {
+        Self {
+            buf,
+            initialized: 0,
+        }
+    }
+
+    /// Reads from `reader` once, returning how many bytes were appended to the buffer, which is
+    /// only 0 at the end of the stream.
+    ///
+    /// When the buffer is full, the stream is first probed through a small stack buffer, so a
+    /// buffer that was allocated with the exact length of the stream never grows.
+    fn read_from<R: Read + ?Sized>(&mut self, reader: &mut R) -> io::Result<usize> {
+        if self.buf.len() == self.buf.capacity() {
+            let mut probe = [0; PROBE_SIZE];
+            let n = retry(|| reader.read(&mut probe))?;
+            self.buf.extend_from_slice(&probe[..n]);
+            self.initialized = 0;
+            return Ok(n);
+        }
+
+        let spare = self.buf.spare_capacity_mut();
+        spare[self.initialized..].fill(MaybeUninit::new(0));
+        self.initialized = spare.len();
+
+        // SAFETY: The whole spare capacity was just initialized.
+        let spare = unsafe { &mut *(spare as *mut [MaybeUninit<u8>] as *mut [u8]) };
+        let n = retry(|| reader.read(spare))?;
+        assert!(
+            n <= self.initialized,
+            "the reader returned an invalid length"
+        );
+
+        // SAFETY: The first `n` bytes of the spare capacity are initialized.
+        unsafe { self.buf.set_len(self.buf.len() + n) };
+        self.initialized -= n;
+        Ok(n)
+    }
+
+    /// Reads from `reader` until the end of the stream.
+    fn read_to_end<R: Read + ?Sized>(&mut self, reader: &mut R) -> io::Result<()> {
+        while self.read_from(reader)? != 0 {}
+        Ok(())
+    }
+}
+
+/// Calls `read` again for as long as it is interrupted.
+fn retry(mut read: impl FnMut() -> io::Result<usize>) -> io::Result<usize> {
+    loop {
+        match read() {
+            Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+            result => return result,
+        }
+    }
+}
+
+/// Reads all of `reader` into an `Arc<[u8]>`.
+///
+/// Unlike `read_to_end` followed by `.into()`, the bytes are read straight into the `Arc`
+/// allocation, which grows as needed and is trimmed once at the end of the stream.
+///
+/// # Examples
+/// ```rust
+/// use collect_into_rc_slice::*;
+///
+/// let arc = read_to_arc_bytes(&b"hello world"[..]).unwrap();
+///
+/// assert_eq!(&*arc, b"hello world");
+/// ```
+#[cfg(target_has_atomic = "ptr")]
+pub fn read_to_arc_bytes<R: Read>(mut reader: R) -> io::Result<Arc<[u8]>> {
+    let mut buf = ArcBuf::with_capacity(0);
+    Filler::new(&mut buf).read_to_end(&mut reader)?;
+    Ok(into_arc(buf))
+}
+
+/// Reads the file at `path` into an `Arc<[u8]>`.
+///
+/// The allocation is sized with the length of the file from its metadata, so the file is read
+/// into it without growing or trimming it. If the file changed size since, the buffer still grows
+/// or is trimmed as needed.
+///
+/// # Examples
+/// ```rust,no_run
+/// use collect_into_rc_slice::*;
+///
+/// let manifest = read_file_to_arc_bytes("Cargo.toml").unwrap();
+///
+/// assert!(manifest.starts_with(b"[package]"));
+/// ```
+#[cfg(target_has_atomic = "ptr")]
+pub fn read_file_to_arc_bytes<P: AsRef<Path>>(path: P) -> io::Result<Arc<[u8]>> {
+    let mut file = File::open(path)?;
+    // the length is only a hint, e.g. files in `/proc` report a length of 0
+    let len = file.metadata().map_or(0, |metadata| metadata.len());
+
+    let mut buf = ArcBuf::with_capacity(usize::try_from(len).unwrap_or(0));
+    Filler::new(&mut buf).read_to_end(&mut file)?;
+    Ok(into_arc(buf))
+}
+
+/// Reads exactly `len` bytes of `file` at `offset` into an `Arc<[u8]>`, with positioned reads that
+/// don't move the cursor of the file.
+///
+/// The allocation is made with exactly `len` bytes up front, so it is filled in place without
+/// growing or trimming it.
+///
+/// # Errors
+/// Fails with an error of kind `UnexpectedEof` if the file ends before `len` bytes were read, and
+/// with the error of the file if reading it fails.
+///
+/// # Examples
+/// ```rust,no_run
+/// use collect_into_rc_slice::*;
+/// use std::fs::File;
+///
+/// let file = File::open("pages.db").unwrap();
+/// let page = read_exact_at_to_arc_bytes(&file, 3 * 4096, 4096).unwrap();
+///
+/// assert_eq!(page.len(), 4096);
+/// ```
+#[cfg(all(target_has_atomic = "ptr", any(unix, windows)))]
+pub fn read_exact_at_to_arc_bytes(file: &File, offset: u64, len: usize) -> io::Result<Arc<[u8]>> {
+    let mut buf = ArcBuf::with_capacity(len);
+    buf.extend_zeroed(len);
+    read_exact_at(file, buf.as_mut_slice(), offset)?;
+    Ok(into_arc(buf))
+}
+
+#[cfg(all(target_has_atomic = "ptr", unix))]
+#[inline]
+fn read_exact_at(file: &File, buf: &mut [u8], offset: u64) -> io::Result<()> {
+    std::os::unix::fs::FileExt::read_exact_at(file, buf, offset)
+}
+
+/// Fills `buf` with `seek_read`, which unlike `pread` on Unix does move the cursor of the file.
+#[cfg(all(target_has_atomic = "ptr", windows))]
+fn read_exact_at(file: &File, mut buf: &mut [u8], mut offset: u64) -> io::Result<()> {
+    use std::os::windows::fs::FileExt;
+
+    while !buf.is_empty() {
+        match retry(|| file.seek_read(buf, offset))? {
+            0 => return Err(ErrorKind::UnexpectedEof.into()),
+            n => {
+                buf = &mut buf[n..];
+                offset += n as u64;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Reads all of `reader` into an `Rc<str>`, like `read_to_string` but without going through a
+/// `String`.
+///
+/// The bytes are validated as UTF-8 as they arrive, a code point split between two reads being
+/// validated once the rest of it is read, so only the new bytes are ever scanned.
+///
+/// # Errors
+/// Fails with an error of kind `InvalidData` wrapping an [`InvalidUtf8Error`] if the stream is not
+/// valid UTF-8, and with the error of the reader if it fails.
+///
+/// # Examples
+/// ```rust
+/// use collect_into_rc_slice::*;
+///
+/// let rc = read_to_rc_str("héllo".as_bytes()).unwrap();
+/// assert_eq!(&*rc, "héllo");
+///
+/// let err = read_to_rc_str(&b"ok\xffno"[..]).unwrap_err();
+/// let err = err.get_ref().unwrap().downcast_ref::<InvalidUtf8Error>().unwrap();
+/// assert_eq!(err.valid_up_to(), 2);
+/// ```
+pub fn read_to_rc_str<R: Read>(mut reader: R) -> io::Result<Rc<str>> {
+    let mut buf = RcBuf::with_capacity(0);
+    let mut filler = Filler::new(&mut buf);
+    let mut valid_up_to = 0;
+
+    loop {
+        let n = filler.read_from(&mut reader)?;
+        let unchecked = &filler.buf.as_slice()[valid_up_to..];
+
+        match str::from_utf8(unchecked) {
+            Ok(_) => valid_up_to += unchecked.len(),
+            // the last code point may be completed by the next read
+            Err(err) if err.error_len().is_none() && n != 0 => valid_up_to += err.valid_up_to(),
+            Err(err) => return Err(invalid_utf8(valid_up_to + err.valid_up_to())),
+        }
+
+        if n == 0 {
+            break;
+        }
+    }
+
+    // SAFETY: The whole buffer was validated as UTF-8.
+    Ok(unsafe { into_rc_str(buf) })
+}
+
+/// Reads the next record ending with `delimiter` from `reader` into an `Rc<[u8]>`, like
+/// `BufRead::read_until` but writing the record straight into its own allocation.
+///
+/// The record includes the delimiter, unless it is the last one and the stream doesn't end with
+/// it. Returns `None` once the end of the stream is reached.
+///
+/// # Examples
+/// ```rust
+/// use collect_into_rc_slice::*;
+///
+/// let mut reader = &b"a,bc,"[..];
+///
+/// assert_eq!(read_until_into_rc_bytes(&mut reader, b',').unwrap().as_deref(), Some(&b"a,"[..]));
+/// assert_eq!(read_until_into_rc_bytes(&mut reader, b',').unwrap().as_deref(), Some(&b"bc,"[..]));
+/// assert_eq!(read_until_into_rc_bytes(&mut reader, b',').unwrap(), None);
+/// ```
+pub fn read_until_into_rc_bytes<R: BufRead>(
+    mut reader: R,
+    delimiter: u8,
+) -> io::Result<Option<Rc<[u8]>>> {
+    let mut buf = RcBuf::with_capacity(0);
+
+    loop {
+        let available = match reader.fill_buf() {
+            Ok(available) => available,
+            Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err),
+        };
+
+        let (done, used) = match available.iter().position(|&b| b == delimiter) {
+            Some(i) => (true, i + 1),
+            None => (available.is_empty(), available.len()),
+        };
+        buf.extend_from_slice(&available[..used]);
+        reader.consume(used);
+
+        if done {
+            break;
+        }
+    }
+
+    Ok((buf.len() != 0).then(|| into_rc(buf)))
+}
+
+/// The error wrapped in the `io::Error` returned when a stream read into a string isn't valid
+/// UTF-8.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InvalidUtf8Error {
+    valid_up_to: usize,
+}
+
+impl InvalidUtf8Error {
+    /// The offset in the stream up to which it was valid UTF-8.
+    #[inline]
+    pub fn valid_up_to(&self) -> usize {
+        self.valid_up_to
+    }
+}
+
+/// The error returned when a stream is only valid UTF-8 up to `valid_up_to`.
+pub(crate) fn invalid_utf8(valid_up_to: usize) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, InvalidUtf8Error { valid_up_to })
+}
+
+impl fmt::Display for InvalidUtf8Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid UTF-8 at byte offset {}", self.valid_up_to)
+    }
+}
+
+impl Error for InvalidUtf8Error {}
+
+/// A reader of `data` for the tests, returning at most `chunk` bytes per read.
+#[cfg(test)]
+pub(crate) struct Chunked<'a> {
+    data: &'a [u8],
+    chunk: usize,
+    interrupt: bool,
+    interrupted: bool,
+    first_buffer_only: bool,
+}
+
+#[cfg(test)]
+impl<'a> Chunked<'a> {
+    pub(crate) fn new(data: &'a [u8], chunk: usize) -> Self {
+        Self {
+            data,
+            chunk,
+            interrupt: false,
+            interrupted: false,
+            first_buffer_only: false,
+        }
+    }
+
+    /// Makes every other read fail with `Interrupted`, starting with the first one.
+    pub(crate) fn interrupting(mut self) -> Self {
+        self.interrupt = true;
+        self
+    }
+
+    /// Makes vectored reads only fill the first non-empty buffer, like the default
+    /// `read_vectored` does, instead of spreading the chunk over all of them.
+    pub(crate) fn first_buffer_only(mut self) -> Self {
+        self.first_buffer_only = true;
+        self
+    }
+}
+
+#[cfg(test)]
+impl Read for Chunked<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.read_vectored(&mut [io::IoSliceMut::new(buf)])
+    }
+
+    fn read_vectored(&mut self, bufs: &mut [io::IoSliceMut<'_>]) -> io::Result<usize> {
+        if self.interrupt {
+            self.interrupted = !self.interrupted;
+            if self.interrupted {
+                return Err(ErrorKind::Interrupted.into());
+            }
+        }
+
+        let mut read = 0;
+        for buf in bufs.iter_mut().filter(|buf| !buf.is_empty()) {
+            let n = (self.chunk - read).min(buf.len()).min(self.data.len());
+            buf[..n].copy_from_slice(&self.data[..n]);
+            self.data = &self.data[n..];
+            read += n;
+            if self.first_buffer_only {
+                break;
+            }
+        }
+        Ok(read)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(target_has_atomic = "ptr")]
+    #[test]
+    fn test_read_to_arc_bytes_chunked() {
+        let data: Vec<u8> = (0..=255).cycle().take(1000).collect();
+        let reader = Chunked::new(&data, 7).interrupting();
+
+        assert_eq!(&*read_to_arc_bytes(reader).unwrap(), &data[..]);
+        assert!(read_to_arc_bytes(io::empty()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_read_to_rc_str_split_code_points() {
+        let text = "ünïcödé ✓ 🦀".repeat(20);
+        let reader = Chunked::new(text.as_bytes(), 3).interrupting();
+        assert_eq!(&*read_to_rc_str(reader).unwrap(), text);
+
+        let truncated = &"🦀".as_bytes()[..3];
+        let err = read_to_rc_str(truncated).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        assert_eq!(err.to_string(), "invalid UTF-8 at byte offset 0");
+    }
+
+    #[cfg(target_has_atomic = "ptr")]
+    #[cfg_attr(miri, ignore = "miri isolates the file system")]
+    #[test]
+    fn test_read_file_to_arc_bytes_exact() {
+        let path = std::env::temp_dir().join(format!("read_file_test_{}", std::process::id()));
+        let data: Vec<u8> = (0..=255).collect();
+        std::fs::write(&path, &data).unwrap();
+
+        let mut buf = ArcBuf::with_capacity(data.len());
+        Filler::new(&mut buf)
+            .read_to_end(&mut File::open(&path).unwrap())
+            .unwrap();
+        assert_eq!(buf.capacity(), data.len());
+        assert_eq!(buf.as_slice(), &data[..]);
+
+        assert_eq!(&*read_file_to_arc_bytes(&path).unwrap(), &data[..]);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[cfg(all(target_has_atomic = "ptr", any(unix, windows)))]
+    #[cfg_attr(miri, ignore = "miri isolates the file system")]
+    #[test]
+    fn test_read_exact_at_to_arc_bytes() {
+        let path = std::env::temp_dir().join(format!("read_at_test_{}", std::process::id()));
+        let data: Vec<u8> = (0..=255).cycle().take(1000).collect();
+        std::fs::write(&path, &data).unwrap();
+
+        let file = File::open(&path).unwrap();
+        assert_eq!(
+            &*read_exact_at_to_arc_bytes(&file, 100, 300).unwrap(),
+            &data[100..400]
+        );
+        assert!(read_exact_at_to_arc_bytes(&file, 1000, 0)
+            .unwrap()
+            .is_empty());
+
+        let err = read_exact_at_to_arc_bytes(&file, 900, 200).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_read_until_into_rc_bytes_records() {
+        let data = b"first\nsecond record\n\nlast";
+        let mut reader = io::BufReader::with_capacity(4, &data[..]);
+        let mut records = Vec::new();
+        while let Some(record) = read_until_into_rc_bytes(&mut reader, b'\n').unwrap() {
+            records.push(record);
+        }
+
+        assert_eq!(records.len(), 4);
+        assert_eq!(&*records[1], b"second record\n");
+        assert_eq!(&*records[2], b"\n");
+        assert_eq!(&*records[3], b"last");
+    }
+}
@@ -0,0 +1,143 @@
+#![cfg(target_has_atomic = "ptr")]
+use crate::{ArcSubstr, CollectIntoArcSlice};
+use regex::Regex;
+use std::sync::Arc;
+
+/// Splits `s` by the matches of `re`, like `Regex::split`, returning substrings that share the
+/// allocation of `s`.
+///
+/// # Examples
+/// ```rust
+/// use collect_into_rc_slice::*;
+/// use regex::Regex;
+///
+/// let line = ArcSubstr::from("GET  /index.html\tHTTP/1.1".chars().collect_into_arc_str());
+/// let parts: Vec<ArcSubstr> = split_regex(&line, &Regex::new(r"\s+").unwrap()).collect();
+///
+/// assert_eq!(parts, ["GET", "/index.html", "HTTP/1.1"]);
+/// ```
+pub fn split_regex<'a>(s: &'a ArcSubstr, re: &'a Regex) -> impl Iterator<Item = ArcSubstr> + 'a {
+    re.split(s).map(|sub| s.slice_ref(sub))
+}
+
+/// The names of the capture groups of a regex, shared by the captures of all of its matches.
+type GroupNames = Arc<[Option<Box<str>>]>;
+
+fn group_names(re: &Regex) -> GroupNames {
+    re.capture_names()
+        .map(|name| name.map(Box::from))
+        .collect_into_arc_slice()
+}
+
+/// The capture groups of a regex match, as substrings sharing the allocation of the searched
+/// string.
+#[derive(Clone, Debug)]
+pub struct ArcCaptures {
+    groups: Vec<Option<ArcSubstr>>,
+    names: GroupNames,
+}
+
+impl ArcCaptures {
+    fn new(s: &ArcSubstr, names: GroupNames, caps: regex::Captures<'_>) -> Self {
+        Self {
+            groups: caps
+                .iter()
+                .map(|group| group.map(|m| s.slice_ref(m.as_str())))
+                .collect(),
+            names,
+        }
+    }
+
+    /// The group at index `i`, where the group at index 0 is the whole match.
+    #[inline]
+    pub fn get(&self, i: usize) -> Option<&ArcSubstr> {
+        self.groups.get(i)?.as_ref()
+    }
+
+    /// The group named `name`.
+    pub fn name(&self, name: &str) -> Option<&ArcSubstr> {
+        let i = self
+            .names
+            .iter()
+            .position(|group| group.as_deref() == Some(name))?;
+        self.get(i)
+    }
+
+    /// The number of groups, including the whole match and the groups that didn't participate in
+    /// the match.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.groups.len()
+    }
+
+    /// Always `false`, since there is always a group for the whole match.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.groups.is_empty()
+    }
+
+    /// Turns the captures into their groups, in order.
+    #[inline]
+    pub fn into_groups(self) -> Vec<Option<ArcSubstr>> {
+        self.groups
+    }
+}
+
+/// Returns the capture groups of the first match of `re` in `s`.
+///
+/// # Examples
+/// ```rust
+/// use collect_into_rc_slice::*;
+/// use regex::Regex;
+///
+/// let log = ArcSubstr::from("[warn] disk almost full".chars().collect_into_arc_str());
+/// let re = Regex::new(r"\[(?<level>\w+)\] (?<msg>.*)").unwrap();
+/// let caps = captures_regex(&log, &re).unwrap();
+///
+/// assert_eq!(caps.name("level").unwrap(), "warn");
+/// assert_eq!(caps.name("msg").unwrap().range(), 7..23);
+/// ```
+pub fn captures_regex(s: &ArcSubstr, re: &Regex) -> Option<ArcCaptures> {
+    re.captures(s)
+        .map(|caps| ArcCaptures::new(s, group_names(re), caps))
+}
+
+/// Iterates over the capture groups of every non-overlapping match of `re` in `s`.
+pub fn captures_iter_regex<'a>(
+    s: &'a ArcSubstr,
+    re: &'a Regex,
+) -> impl Iterator<Item = ArcCaptures> + 'a {
+    let names = group_names(re);
+    re.captures_iter(s)
+        .map(move |caps| ArcCaptures::new(s, names.clone(), caps))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CollectIntoArcStr;
+
+    #[test]
+    fn test_split_regex() {
+        let s = ArcSubstr::from("a1b22c333".chars().collect_into_arc_str());
+        let re = Regex::new(r"\d+").unwrap();
+        let parts: Vec<_> = split_regex(&s, &re).collect();
+
+        assert_eq!(parts, ["a", "b", "c", ""]);
+        assert_eq!(Arc::strong_count(s.parent()), 5);
+    }
+
+    #[test]
+    fn test_captures_iter_regex() {
+        let s = ArcSubstr::from("x=1, y=2, z".chars().collect_into_arc_str());
+        let re = Regex::new(r"(?<key>\w)(?:=(\d))?").unwrap();
+        let caps: Vec<_> = captures_iter_regex(&s, &re).collect();
+
+        assert_eq!(caps.len(), 3);
+        assert_eq!(caps[1].name("key").unwrap(), "y");
+        assert!(Arc::ptr_eq(&caps[0].names, &caps[2].names));
+        assert_eq!(caps[1].get(2).unwrap(), "2");
+        assert_eq!(caps[1].get(2).unwrap().range(), 7..8);
+        assert!(caps[2].get(2).is_none());
+    }
+}
@@ -0,0 +1,130 @@
+use crate::rc::{into_rc, RcBuf};
+use std::{
+    mem::{self, MaybeUninit},
+    ptr,
+    rc::Rc,
+};
+
+pub trait CollectRevIntoRcSlice<T> {
+    /// Collects the iterator into an `Rc<[T]>` in reverse order.
+    ///
+    /// The iterator is consumed from the front and its elements are written to the allocation
+    /// back to front, so the slice is never reversed after the fact and the iterator only needs to
+    /// know its exact length.
+    ///
+    /// # Panics
+    /// Panics if the iterator doesn't produce exactly as many elements as its length.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use collect_into_rc_slice::*;
+    ///
+    /// let countdown = (1..6).collect_rev_into_rc_slice();
+    ///
+    /// assert_eq!(&*countdown, &[5, 4, 3, 2, 1]);
+    /// ```
+    fn collect_rev_into_rc_slice(self) -> Rc<[T]>;
+}
+
+impl<T, I> CollectRevIntoRcSlice<T> for I
+where
+    I: IntoIterator<Item = T>,
+    I::IntoIter: ExactSizeIterator,
+{
+    fn collect_rev_into_rc_slice(self) -> Rc<[T]> {
+        let mut iter = self.into_iter();
+        let len = iter.len();
+        let mut buf = RcBuf::with_capacity(len);
+
+        let mut filled = FilledBack {
+            slots: &mut buf.spare_capacity_mut()[..len],
+            start: len,
+        };
+        for item in iter.by_ref().take(len) {
+            filled.slots[filled.start - 1].write(item);
+            filled.start -= 1;
+        }
+        assert!(
+            filled.start == 0 && iter.next().is_none(),
+            "the iterator didn't produce as many items as its length"
+        );
+        // the elements are owned by the buffer now
+        mem::forget(filled);
+
+        // SAFETY: All `len` slots were written.
+        unsafe { buf.set_len(len) };
+        into_rc(buf)
+    }
+}
+
+/// The slots written so far, which are dropped if the iterator panics or runs out early.
+struct FilledBack<'a, T> {
+    slots: &'a mut [MaybeUninit<T>],
+    start: usize,
+}
+
+impl<T> Drop for FilledBack<'_, T> {
+    fn drop(&mut self) {
+        // SAFETY: The slots from `start` on were written.
+        unsafe {
+            ptr::drop_in_place(&mut self.slots[self.start..] as *mut [MaybeUninit<T>] as *mut [T])
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        collections::HashSet,
+        panic::{self, AssertUnwindSafe},
+    };
+
+    #[test]
+    fn test_collect_rev_into_rc_slice_consumes_front() {
+        let mut taken = Vec::new();
+        let rc = ["a", "b", "c"]
+            .iter()
+            .inspect(|s| taken.push(**s))
+            .collect_rev_into_rc_slice();
+
+        assert_eq!(&*rc, [&"c", &"b", &"a"]);
+        assert_eq!(taken, ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn test_collect_rev_into_rc_slice_not_double_ended() {
+        let set: HashSet<u32> = (0..10).collect();
+        let forward: Vec<u32> = set.iter().copied().collect();
+        let rc = set.into_iter().collect_rev_into_rc_slice();
+
+        assert!(rc.iter().eq(forward.iter().rev()));
+    }
+
+    #[test]
+    fn test_collect_rev_into_rc_slice_wrong_len() {
+        struct Lying<I>(I);
+
+        impl<I: Iterator> Iterator for Lying<I> {
+            type Item = I::Item;
+
+            fn next(&mut self) -> Option<I::Item> {
+                self.0.next()
+            }
+        }
+
+        impl<I: ExactSizeIterator> ExactSizeIterator for Lying<I> {
+            fn len(&self) -> usize {
+                self.0.len() + 1
+            }
+        }
+
+        let live = Rc::new(());
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            Lying(vec![live.clone(), live.clone()].into_iter()).collect_rev_into_rc_slice()
+        }));
+
+        assert!(result.is_err());
+        assert_eq!(Rc::strong_count(&live), 1);
+    }
+}
@@ -0,0 +1,123 @@
+#![cfg(target_has_atomic = "ptr")]
+use crate::arc::{into_arc, ArcBuf};
+use std::{mem, num::NonZeroUsize, panic, ptr, sync::Arc, thread};
+
+/// Builds an `Arc<[T]>` of `len` items, `f(i)` being the item at index `i`, on scoped threads.
+///
+/// The allocation is made once with room for the `len` items and split into as many ranges as
+/// there are CPUs, each range being filled in place on a thread of its own, so there is no need
+/// for a thread pool like rayon's. If `f` panics, the items that were already made are dropped and
+/// the panic is propagated once every thread is done.
+///
+/// # Examples
+/// ```rust
+/// use collect_into_rc_slice::*;
+///
+/// let squares = par_collect_exact_into_arc_slice(1000, |i| i * i);
+///
+/// assert_eq!(squares.len(), 1000);
+/// assert_eq!(squares[999], 998_001);
+/// ```
+pub fn par_collect_exact_into_arc_slice<T, F>(len: usize, f: F) -> Arc<[T]>
+where
+    T: Send,
+    F: Fn(usize) -> T + Sync,
+{
+    let threads = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+    par_collect_exact_with_threads(len, threads, f)
+}
+
+fn par_collect_exact_with_threads<T, F>(len: usize, threads: usize, f: F) -> Arc<[T]>
+where
+    T: Send,
+    F: Fn(usize) -> T + Sync,
+{
+    let mut buf = ArcBuf::<T>::with_capacity(len);
+    let chunk_size = len.div_ceil(threads).max(1);
+
+    let spare = &mut buf.spare_capacity_mut()[..len];
+    thread::scope(|scope| {
+        let handles: Vec<_> = spare
+            .chunks_mut(chunk_size)
+            .enumerate()
+            .map(|(i, chunk)| {
+                let f = &f;
+                scope.spawn(move || {
+                    let start = i * chunk_size;
+                    let mut filled = Filled {
+                        ptr: chunk.as_mut_ptr() as *mut T,
+                        len: 0,
+                    };
+
+                    while filled.len < chunk.len() {
+                        let item = f(start + filled.len);
+                        // SAFETY: The item is written within the range of the thread.
+                        unsafe { filled.ptr.add(filled.len).write(item) };
+                        filled.len += 1;
+                    }
+                    filled
+                })
+            })
+            .collect();
+
+        // the ranges of the threads that are done are dropped along with them if another one
+        // panicked
+        let filled = handles
+            .into_iter()
+            .map(|handle| handle.join())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap_or_else(|payload| panic::resume_unwind(payload));
+        filled.into_iter().for_each(mem::forget);
+    });
+
+    // SAFETY: Every thread filled its range, or the panic of the thread that didn't would have
+    // been propagated by the scope.
+    unsafe { buf.set_len(len) };
+    into_arc(buf)
+}
+
+/// The items filled in so far in the range of a thread, which are dropped unless the whole buffer
+/// is filled.
+struct Filled<T> {
+    ptr: *mut T,
+    len: usize,
+}
+
+// SAFETY: `Filled` owns the items in its range.
+unsafe impl<T: Send> Send for Filled<T> {}
+
+impl<T> Drop for Filled<T> {
+    fn drop(&mut self) {
+        // SAFETY: The first `len` items of the range were filled in.
+        unsafe { ptr::drop_in_place(ptr::slice_from_raw_parts_mut(self.ptr, self.len)) };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::panic::AssertUnwindSafe;
+
+    #[test]
+    fn test_par_collect_exact_uneven_chunks() {
+        let arc = par_collect_exact_with_threads(10, 3, |i| i.to_string());
+        assert!(arc.iter().enumerate().all(|(i, s)| *s == i.to_string()));
+
+        assert!(par_collect_exact_with_threads(0, 4, |i| i).is_empty());
+        assert_eq!(&*par_collect_exact_with_threads(2, 4, |i| i), &[0, 1]);
+    }
+
+    #[test]
+    fn test_par_collect_exact_panic_drops_items() {
+        let live = Arc::new(());
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            par_collect_exact_with_threads(20, 4, |i| {
+                assert_ne!(i, 13);
+                live.clone()
+            })
+        }));
+
+        assert!(result.is_err());
+        assert_eq!(Arc::strong_count(&live), 1);
+    }
+}
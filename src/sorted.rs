@@ -0,0 +1,166 @@
+#[cfg(target_has_atomic = "ptr")]
+use crate::arc::{into_arc, ArcBuf};
+use crate::rc::{into_rc, RcBuf};
+use std::rc::Rc;
+#[cfg(target_has_atomic = "ptr")]
+use std::sync::Arc;
+
+pub trait CollectSortedIntoRcSlice<T: Ord> {
+    /// Collects the iterator into an `Rc<[T]>` and sorts it in place before handing it over to
+    /// the `Rc`, with a stable sort.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use collect_into_rc_slice::*;
+    ///
+    /// let table = ["pear", "apple", "fig"].into_iter().collect_sorted_into_rc_slice();
+    ///
+    /// assert_eq!(&*table, ["apple", "fig", "pear"]);
+    /// assert!(table.binary_search(&"fig").is_ok());
+    /// ```
+    fn collect_sorted_into_rc_slice(self) -> Rc<[T]>;
+}
+
+impl<T, I> CollectSortedIntoRcSlice<T> for I
+where
+    T: Ord,
+    I: IntoIterator<Item = T>,
+{
+    fn collect_sorted_into_rc_slice(self) -> Rc<[T]> {
+        let mut buf = RcBuf::from_iter(self.into_iter());
+        buf.as_mut_slice().sort();
+        into_rc(buf)
+    }
+}
+
+#[cfg(target_has_atomic = "ptr")]
+pub trait CollectSortedDedupIntoArcSlice<T> {
+    /// Collects the iterator into an `Arc<[T]>`, sorts it and removes the duplicates in place,
+    /// keeping the first of them, and trims the allocation to the elements that are left.
+    ///
+    /// The result is sorted and unique, the way [`FrozenArcSet`](crate::FrozenArcSet) stores its
+    /// elements.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use collect_into_rc_slice::*;
+    ///
+    /// let tags = ["b", "a", "b", "c", "a"].into_iter().collect_sorted_dedup_into_arc_slice();
+    ///
+    /// assert_eq!(&*tags, ["a", "b", "c"]);
+    /// ```
+    fn collect_sorted_dedup_into_arc_slice(self) -> Arc<[T]>
+    where
+        T: Ord;
+
+    /// Like [`collect_sorted_dedup_into_arc_slice`](Self::collect_sorted_dedup_into_arc_slice),
+    /// but sorts the elements by the key `f` returns for them, and removes the ones with the same
+    /// key.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use collect_into_rc_slice::*;
+    ///
+    /// let users = [(2, "bob"), (1, "ann"), (2, "bobby")]
+    ///     .into_iter()
+    ///     .collect_sorted_dedup_by_key_into_arc_slice(|&(id, _)| id);
+    ///
+    /// assert_eq!(&*users, [(1, "ann"), (2, "bob")]);
+    /// ```
+    fn collect_sorted_dedup_by_key_into_arc_slice<K, F>(self, f: F) -> Arc<[T]>
+    where
+        K: Ord,
+        F: FnMut(&T) -> K;
+}
+
+#[cfg(target_has_atomic = "ptr")]
+impl<T, I> CollectSortedDedupIntoArcSlice<T> for I
+where
+    I: IntoIterator<Item = T>,
+{
+    fn collect_sorted_dedup_into_arc_slice(self) -> Arc<[T]>
+    where
+        T: Ord,
+    {
+        let mut buf = ArcBuf::from_iter(self.into_iter());
+        buf.as_mut_slice().sort();
+        buf.dedup_by(|item, prev| item == prev);
+        into_arc(buf)
+    }
+
+    fn collect_sorted_dedup_by_key_into_arc_slice<K, F>(self, mut f: F) -> Arc<[T]>
+    where
+        K: Ord,
+        F: FnMut(&T) -> K,
+    {
+        let mut buf = ArcBuf::from_iter(self.into_iter());
+        buf.as_mut_slice().sort_by_key(&mut f);
+        buf.dedup_by(|item, prev| f(item) == f(prev));
+        into_arc(buf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Ordered by `key` only, to tell equal elements apart.
+    struct Entry {
+        key: u8,
+        value: char,
+    }
+
+    impl PartialEq for Entry {
+        fn eq(&self, other: &Self) -> bool {
+            self.key == other.key
+        }
+    }
+
+    impl Eq for Entry {}
+
+    impl PartialOrd for Entry {
+        fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+
+    impl Ord for Entry {
+        fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+            self.key.cmp(&other.key)
+        }
+    }
+
+    #[test]
+    fn test_collect_sorted_into_rc_slice_stable() {
+        let rc = [(3, 'a'), (1, 'b'), (3, 'c'), (1, 'd')]
+            .into_iter()
+            .map(|(key, value)| Entry { key, value })
+            .collect_sorted_into_rc_slice();
+
+        let values: String = rc.iter().map(|entry| entry.value).collect();
+        assert_eq!(values, "bdac");
+        assert!(std::iter::empty::<u8>()
+            .collect_sorted_into_rc_slice()
+            .is_empty());
+    }
+
+    #[cfg(target_has_atomic = "ptr")]
+    #[test]
+    fn test_collect_sorted_dedup_into_arc_slice_keeps_first() {
+        let arc = [(3, 'a'), (1, 'b'), (3, 'c'), (1, 'd'), (2, 'e')]
+            .into_iter()
+            .map(|(key, value)| Entry { key, value })
+            .collect_sorted_dedup_into_arc_slice();
+
+        let values: String = arc.iter().map(|entry| entry.value).collect();
+        assert_eq!(values, "bea");
+
+        let live = Arc::new(());
+        let arc = (0..10)
+            .map(|i| (i % 3, live.clone()))
+            .collect_sorted_dedup_by_key_into_arc_slice(|(key, _)| *key);
+        assert_eq!(arc.len(), 3);
+        // the duplicates were dropped
+        assert_eq!(Arc::strong_count(&live), 4);
+    }
+}
@@ -0,0 +1,266 @@
+#![cfg(target_has_atomic = "ptr")]
+use crate::arc::{into_arc_str, ArcBuf};
+use std::{
+    borrow::Borrow,
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+    ops::Deref,
+    sync::Arc,
+};
+
+/// Either a `&'static str` or an `Arc<str>`, so string literals can be used wherever a shared
+/// string is expected without allocating, from any thread.
+///
+/// Only the strings that are built at runtime are collected into an `Arc<str>`. Literals are
+/// wrapped with [`static_arc_str!`](crate::static_arc_str!), which also works in `const` and
+/// `static` items. Cloning a literal copies the reference, and comparing or hashing a
+/// `StaticArcStr` only looks at the string itself, whichever variant it is.
+///
+/// It is also what an [`ArcStrInterner`](crate::ArcStrInterner) hands out, so the static strings
+/// it was seeded with are returned without allocating.
+///
+/// # Examples
+/// ```rust
+/// use collect_into_rc_slice::*;
+///
+/// let names = [
+///     static_arc_str!("root"),
+///     "guest".chars().rev().collect(),
+/// ];
+///
+/// assert!(names[0].is_static());
+/// assert!(!names[1].is_static());
+/// assert_eq!(names[1], "tseug");
+/// ```
+pub enum StaticArcStr {
+    Static(&'static str),
+    Arc(Arc<str>),
+}
+
+/// Wraps a string literal in a [`StaticArcStr`] without allocating, in a `const` context if
+/// needed.
+///
+/// # Examples
+/// ```rust
+/// use collect_into_rc_slice::*;
+///
+/// const UNKNOWN: StaticArcStr = static_arc_str!("<unknown>");
+///
+/// let name = UNKNOWN.clone();
+/// assert!(name.is_static());
+/// assert_eq!(name, "<unknown>");
+/// ```
+#[macro_export]
+macro_rules! static_arc_str {
+    ($s:literal $(,)?) => {
+        $crate::StaticArcStr::Static($s)
+    };
+}
+
+impl StaticArcStr {
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Static(s) => s,
+            Self::Arc(arc) => arc,
+        }
+    }
+
+    /// Returns `true` if the string is a `&'static str`.
+    #[inline]
+    pub fn is_static(&self) -> bool {
+        matches!(self, Self::Static(_))
+    }
+
+    /// Turns the string into an `Arc<str>`, which allocates for a static string.
+    #[inline]
+    pub fn into_arc(self) -> Arc<str> {
+        match self {
+            Self::Static(s) => Arc::from(s),
+            Self::Arc(arc) => arc,
+        }
+    }
+
+    /// Returns `true` if the two strings point to the same allocation.
+    #[inline]
+    pub fn ptr_eq(this: &Self, other: &Self) -> bool {
+        this.as_ptr() == other.as_ptr() && this.len() == other.len()
+    }
+}
+
+impl Clone for StaticArcStr {
+    #[inline]
+    fn clone(&self) -> Self {
+        match self {
+            Self::Static(s) => Self::Static(s),
+            Self::Arc(arc) => Self::Arc(arc.clone()),
+        }
+    }
+}
+
+impl Deref for StaticArcStr {
+    type Target = str;
+
+    #[inline]
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl AsRef<str> for StaticArcStr {
+    #[inline]
+    fn as_ref(&self) -> &str {
+        self
+    }
+}
+
+impl Borrow<str> for StaticArcStr {
+    #[inline]
+    fn borrow(&self) -> &str {
+        self
+    }
+}
+
+impl Default for StaticArcStr {
+    #[inline]
+    fn default() -> Self {
+        Self::Static("")
+    }
+}
+
+impl From<&'static str> for StaticArcStr {
+    #[inline]
+    fn from(s: &'static str) -> Self {
+        Self::Static(s)
+    }
+}
+
+impl From<Arc<str>> for StaticArcStr {
+    #[inline]
+    fn from(arc: Arc<str>) -> Self {
+        Self::Arc(arc)
+    }
+}
+
+impl From<StaticArcStr> for Arc<str> {
+    #[inline]
+    fn from(s: StaticArcStr) -> Self {
+        s.into_arc()
+    }
+}
+
+impl From<String> for StaticArcStr {
+    fn from(s: String) -> Self {
+        let mut buf = ArcBuf::with_capacity(s.len());
+        buf.extend_from_slice(s.as_bytes());
+        // SAFETY: The bytes were copied from a `String`.
+        Self::Arc(unsafe { into_arc_str(buf) })
+    }
+}
+
+impl FromIterator<char> for StaticArcStr {
+    fn from_iter<I: IntoIterator<Item = char>>(iter: I) -> Self {
+        // SAFETY: The buffer is made up of UTF-8 encoded `char`s.
+        Self::Arc(unsafe { into_arc_str(ArcBuf::from_chars(iter.into_iter())) })
+    }
+}
+
+impl fmt::Debug for StaticArcStr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl fmt::Display for StaticArcStr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self.as_str(), f)
+    }
+}
+
+impl PartialEq for StaticArcStr {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for StaticArcStr {}
+
+impl PartialEq<str> for StaticArcStr {
+    #[inline]
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for StaticArcStr {
+    #[inline]
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl PartialOrd for StaticArcStr {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for StaticArcStr {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+impl Hash for StaticArcStr {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state);
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for StaticArcStr {
+    #[inline]
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{collections::HashSet, thread};
+
+    #[test]
+    fn test_static_arc_str_variants_equal() {
+        let literal = static_arc_str!("key");
+        let dynamic = StaticArcStr::from(String::from("key"));
+
+        assert!(literal.is_static());
+        assert!(!dynamic.is_static());
+        assert_eq!(literal, dynamic);
+
+        let set: HashSet<_> = [literal, dynamic].into_iter().collect();
+        assert_eq!(set.len(), 1);
+        assert!(set.contains("key"));
+    }
+
+    #[test]
+    fn test_static_arc_str_clone_shares() {
+        let s: StaticArcStr = "abc".chars().cycle().take(9).collect();
+        let clone = thread::spawn({
+            let s = s.clone();
+            move || s
+        })
+        .join()
+        .unwrap();
+
+        assert_eq!(clone, "abcabcabc");
+        assert_eq!(s.as_ptr(), clone.as_ptr());
+        assert_eq!(StaticArcStr::default(), "");
+    }
+}
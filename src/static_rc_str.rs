@@ -0,0 +1,206 @@
+use crate::rc::{into_rc_str, RcBuf};
+use std::{
+    borrow::Borrow,
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+    ops::Deref,
+    rc::Rc,
+};
+
+/// Either a `&'static str` or an `Rc<str>`, so string literals can be used wherever a shared
+/// string is expected without allocating.
+///
+/// Only the strings that are built at runtime are collected into an `Rc<str>`. Cloning a literal
+/// copies the reference, and comparing or hashing a `StaticRcStr` only looks at the string itself,
+/// whichever variant it is.
+///
+/// # Examples
+/// ```rust
+/// use collect_into_rc_slice::*;
+///
+/// let names = [
+///     StaticRcStr::from("root"),
+///     "guest".chars().rev().collect(),
+/// ];
+///
+/// assert!(names[0].is_static());
+/// assert!(!names[1].is_static());
+/// assert_eq!(names[1], "tseug");
+/// ```
+pub enum StaticRcStr {
+    Static(&'static str),
+    Rc(Rc<str>),
+}
+
+impl StaticRcStr {
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Static(s) => s,
+            Self::Rc(rc) => rc,
+        }
+    }
+
+    /// Returns `true` if the string is a `&'static str`.
+    #[inline]
+    pub fn is_static(&self) -> bool {
+        matches!(self, Self::Static(_))
+    }
+}
+
+impl Clone for StaticRcStr {
+    #[inline]
+    fn clone(&self) -> Self {
+        match self {
+            Self::Static(s) => Self::Static(s),
+            Self::Rc(rc) => Self::Rc(rc.clone()),
+        }
+    }
+}
+
+impl Deref for StaticRcStr {
+    type Target = str;
+
+    #[inline]
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl AsRef<str> for StaticRcStr {
+    #[inline]
+    fn as_ref(&self) -> &str {
+        self
+    }
+}
+
+impl Borrow<str> for StaticRcStr {
+    #[inline]
+    fn borrow(&self) -> &str {
+        self
+    }
+}
+
+impl Default for StaticRcStr {
+    #[inline]
+    fn default() -> Self {
+        Self::Static("")
+    }
+}
+
+impl From<&'static str> for StaticRcStr {
+    #[inline]
+    fn from(s: &'static str) -> Self {
+        Self::Static(s)
+    }
+}
+
+impl From<Rc<str>> for StaticRcStr {
+    #[inline]
+    fn from(rc: Rc<str>) -> Self {
+        Self::Rc(rc)
+    }
+}
+
+impl From<String> for StaticRcStr {
+    fn from(s: String) -> Self {
+        let mut buf = RcBuf::with_capacity(s.len());
+        buf.extend_from_slice(s.as_bytes());
+        // SAFETY: The bytes were copied from a `String`.
+        Self::Rc(unsafe { into_rc_str(buf) })
+    }
+}
+
+impl FromIterator<char> for StaticRcStr {
+    fn from_iter<I: IntoIterator<Item = char>>(iter: I) -> Self {
+        // SAFETY: The buffer is made up of UTF-8 encoded `char`s.
+        Self::Rc(unsafe { into_rc_str(RcBuf::from_chars(iter.into_iter())) })
+    }
+}
+
+impl fmt::Debug for StaticRcStr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl fmt::Display for StaticRcStr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self.as_str(), f)
+    }
+}
+
+impl PartialEq for StaticRcStr {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for StaticRcStr {}
+
+impl PartialEq<str> for StaticRcStr {
+    #[inline]
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for StaticRcStr {
+    #[inline]
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl PartialOrd for StaticRcStr {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for StaticRcStr {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+impl Hash for StaticRcStr {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_static_rc_str_variants_equal() {
+        let literal = StaticRcStr::from("key");
+        let dynamic = StaticRcStr::from(String::from("key"));
+
+        assert!(literal.is_static());
+        assert!(!dynamic.is_static());
+        assert_eq!(literal, dynamic);
+
+        let set: HashSet<_> = [literal, dynamic].into_iter().collect();
+        assert_eq!(set.len(), 1);
+        assert!(set.contains("key"));
+    }
+
+    #[test]
+    fn test_static_rc_str_clone_shares() {
+        let s: StaticRcStr = "abc".chars().cycle().take(9).collect();
+        let clone = s.clone();
+
+        assert_eq!(clone, "abcabcabc");
+        assert_eq!(s.as_ptr(), clone.as_ptr());
+        assert_eq!(StaticRcStr::default(), "");
+    }
+}
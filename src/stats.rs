@@ -0,0 +1,140 @@
+use std::cell::Cell;
+
+/// Counters of the work the collectors did on the current thread, to tell how good the size hints
+/// of the iterators are, e.g. an iterator with an unknown length that makes every collection grow
+/// several times.
+///
+/// Only the allocations of the buffers the elements are collected into are counted, not the ones
+/// of the elements themselves or of intermediate collections.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AllocStats {
+    /// The number of buffers that were finished and handed over to a pointer.
+    pub collections: usize,
+    /// The number of buffers that were allocated.
+    pub allocations: usize,
+    /// The number of times a buffer was grown or trimmed.
+    pub reallocations: usize,
+    /// The number of bytes of elements the reallocations would have to move, which they don't when
+    /// the allocator can resize an allocation in place.
+    pub bytes_copied: usize,
+    /// The number of bytes of capacity that were never used and had to be trimmed when a buffer
+    /// was finished.
+    pub wasted_capacity: usize,
+}
+
+impl AllocStats {
+    const ZERO: Self = Self {
+        collections: 0,
+        allocations: 0,
+        reallocations: 0,
+        bytes_copied: 0,
+        wasted_capacity: 0,
+    };
+
+    fn since(self, before: Self) -> Self {
+        Self {
+            collections: self.collections.wrapping_sub(before.collections),
+            allocations: self.allocations.wrapping_sub(before.allocations),
+            reallocations: self.reallocations.wrapping_sub(before.reallocations),
+            bytes_copied: self.bytes_copied.wrapping_sub(before.bytes_copied),
+            wasted_capacity: self.wasted_capacity.wrapping_sub(before.wasted_capacity),
+        }
+    }
+}
+
+thread_local! {
+    static STATS: Cell<AllocStats> = const { Cell::new(AllocStats::ZERO) };
+}
+
+fn update(f: impl FnOnce(&mut AllocStats)) {
+    // the counters are gone once the thread is being torn down, so there is nothing to update
+    let _ = STATS.try_with(|stats| {
+        let mut current = stats.get();
+        f(&mut current);
+        stats.set(current);
+    });
+}
+
+pub(crate) fn record_alloc() {
+    update(|stats| stats.allocations += 1);
+}
+
+pub(crate) fn record_realloc(bytes_copied: usize) {
+    update(|stats| {
+        stats.reallocations += 1;
+        stats.bytes_copied += bytes_copied;
+    });
+}
+
+pub(crate) fn record_collection(wasted_capacity: usize) {
+    update(|stats| {
+        stats.collections += 1;
+        stats.wasted_capacity += wasted_capacity;
+    });
+}
+
+/// The counters of the current thread since it started, or since they were last reset.
+#[inline]
+pub fn alloc_stats() -> AllocStats {
+    STATS.with(Cell::get)
+}
+
+/// Resets the counters of the current thread.
+#[inline]
+pub fn reset_alloc_stats() {
+    STATS.with(|stats| stats.set(AllocStats::ZERO));
+}
+
+/// Calls `f`, returning its result along with the work the collectors did on the current thread
+/// while it ran.
+///
+/// # Examples
+/// ```rust
+/// use collect_into_rc_slice::*;
+///
+/// // a `filter` over 100 numbers could keep all of them, so it makes room for 100
+/// let (_, filtered) =
+///     measure_allocs(|| (0..100u32).filter(|i| i % 4 == 0).collect_into_rc_slice());
+/// // but a `map` has an exact size hint
+/// let (_, mapped) = measure_allocs(|| (0..25u32).map(|i| i * 4).collect_into_rc_slice());
+///
+/// assert_eq!(filtered.wasted_capacity, 75 * 4);
+/// assert_eq!(mapped.wasted_capacity, 0);
+/// assert_eq!(mapped.reallocations, 0);
+/// ```
+pub fn measure_allocs<R>(f: impl FnOnce() -> R) -> (R, AllocStats) {
+    let before = alloc_stats();
+    let result = f();
+    (result, alloc_stats().since(before))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn test_measure_allocs_unknown_length() {
+        let mut i = 0u32;
+        let (rc, stats) = measure_allocs(|| {
+            std::iter::from_fn(|| {
+                (i < 5).then(|| {
+                    i += 1;
+                    i
+                })
+            })
+            .collect_into_rc_slice()
+        });
+
+        assert_eq!(&*rc, [1, 2, 3, 4, 5]);
+        assert_eq!(stats.collections, 1);
+        assert_eq!(stats.allocations, 1);
+        // the length is unknown, so the buffer starts out with room for 64 bytes, then it is
+        // trimmed to 5 elements
+        assert_eq!(stats.reallocations, 1);
+        assert_eq!(stats.bytes_copied, 5 * 4);
+        assert_eq!(stats.wasted_capacity, 11 * 4);
+
+        reset_alloc_stats();
+        assert_eq!(alloc_stats(), AllocStats::default());
+    }
+}
@@ -0,0 +1,128 @@
+#![cfg(target_has_atomic = "ptr")]
+use crate::arc::{into_arc, ArcBuf};
+use futures_core::Stream;
+use std::{
+    future::{poll_fn, Future},
+    pin::pin,
+    sync::Arc,
+};
+
+pub trait StreamCollectIntoArcSlice<T>: Stream<Item = T> {
+    /// Collects the stream into an `Arc<[T]>`, like `StreamExt::collect` into a `Vec<T>` followed
+    /// by `.into()` but with a single allocation, sized with the stream's size hint and grown as
+    /// needed.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use collect_into_rc_slice::*;
+    /// use futures::{executor::block_on, stream};
+    ///
+    /// let arc = block_on(stream::iter(1..=5).collect_into_arc_slice());
+    ///
+    /// assert_eq!(&*arc, &[1, 2, 3, 4, 5]);
+    /// ```
+    fn collect_into_arc_slice(self) -> impl Future<Output = Arc<[T]>>;
+}
+
+impl<T, S> StreamCollectIntoArcSlice<T> for S
+where
+    S: Stream<Item = T>,
+{
+    async fn collect_into_arc_slice(self) -> Arc<[T]> {
+        let mut stream = pin!(self);
+        let (lower_bound, upper_bound) = stream.size_hint();
+        let mut buf = ArcBuf::with_capacity(upper_bound.unwrap_or(lower_bound));
+
+        while let Some(item) = poll_fn(|cx| stream.as_mut().poll_next(cx)).await {
+            buf.push(item);
+        }
+
+        into_arc(buf)
+    }
+}
+
+pub trait TryStreamCollectIntoArcSlice<T, E>: Stream<Item = Result<T, E>> {
+    /// Collects the stream into an `Arc<[T]>`, stopping at the first error and returning it.
+    ///
+    /// The items collected before the error are dropped along with the buffer.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use collect_into_rc_slice::*;
+    /// use futures::{executor::block_on, stream};
+    ///
+    /// let ok = stream::iter(["1", "2", "3"].map(str::parse::<u8>));
+    /// assert_eq!(&*block_on(ok.try_collect_into_arc_slice()).unwrap(), &[1, 2, 3]);
+    ///
+    /// let err = stream::iter(["1", "x", "3"].map(str::parse::<u8>));
+    /// assert!(block_on(err.try_collect_into_arc_slice()).is_err());
+    /// ```
+    fn try_collect_into_arc_slice(self) -> impl Future<Output = Result<Arc<[T]>, E>>;
+}
+
+impl<T, E, S> TryStreamCollectIntoArcSlice<T, E> for S
+where
+    S: Stream<Item = Result<T, E>>,
+{
+    async fn try_collect_into_arc_slice(self) -> Result<Arc<[T]>, E> {
+        let mut stream = pin!(self);
+        let (lower_bound, upper_bound) = stream.size_hint();
+        let mut buf = ArcBuf::with_capacity(upper_bound.unwrap_or(lower_bound));
+
+        while let Some(item) = poll_fn(|cx| stream.as_mut().poll_next(cx)).await {
+            buf.push(item?);
+        }
+
+        Ok(into_arc(buf))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::{executor::block_on, stream, StreamExt};
+
+    #[test]
+    fn test_stream_collect_into_arc_slice_unknown_size() {
+        let items = stream::unfold(
+            0,
+            |i| async move { (i < 100).then(|| (i.to_string(), i + 1)) },
+        );
+        let arc = block_on(items.collect_into_arc_slice());
+
+        assert_eq!(arc.len(), 100);
+        assert!(arc.iter().enumerate().all(|(i, s)| *s == i.to_string()));
+    }
+
+    #[test]
+    fn test_stream_collect_into_arc_slice_pending() {
+        // yields to the executor before each item
+        let items = stream::iter(0..10).then(|i| async move {
+            let mut yielded = false;
+            poll_fn(|cx| {
+                if yielded {
+                    return std::task::Poll::Ready(i);
+                }
+                yielded = true;
+                cx.waker().wake_by_ref();
+                std::task::Poll::Pending
+            })
+            .await
+        });
+
+        let arc = block_on(items.collect_into_arc_slice());
+        assert_eq!(&*arc, &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9]);
+    }
+
+    #[test]
+    fn test_try_collect_into_arc_slice_drops_on_error() {
+        let live = Arc::new(());
+        let items = stream::iter(0..10).map(|i| match i {
+            7 => Err(i),
+            _ => Ok(live.clone()),
+        });
+
+        assert_eq!(block_on(items.try_collect_into_arc_slice()), Err(7));
+        assert_eq!(Arc::strong_count(&live), 1);
+    }
+}
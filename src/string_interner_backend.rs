@@ -0,0 +1,180 @@
+#![cfg(target_has_atomic = "ptr")]
+use crate::{
+    arc::{into_arc_str, ArcBuf},
+    StaticArcStr,
+};
+use std::{fmt, iter::Enumerate, marker::PhantomData, slice};
+use string_interner::{backend::Backend, DefaultSymbol, Symbol};
+
+/// A `string_interner` backend that stores every string in its own `Arc<str>`.
+///
+/// Each string is copied into a single allocation holding both the reference counts and the
+/// bytes, so interning a string never moves the ones before it the way the growing buffer of
+/// `StringBackend` does, and strings interned with `get_or_intern_static` aren't copied at all.
+/// Used on its own, the backend can also hand out the shared pointers with
+/// [`resolve_arc`](ArcStrBackend::resolve_arc), so the strings can outlive it.
+///
+/// # Examples
+/// ```rust
+/// use collect_into_rc_slice::*;
+/// use string_interner::{backend::Backend, StringInterner};
+///
+/// let mut interner = StringInterner::<ArcStrBackend>::new();
+/// let hello = interner.get_or_intern("hello");
+/// let world = interner.get_or_intern_static("world");
+///
+/// assert_eq!(interner.get_or_intern("hello"), hello);
+/// assert_eq!(interner.resolve(world), Some("world"));
+///
+/// let mut backend = ArcStrBackend::<string_interner::DefaultSymbol>::default();
+/// let symbol = backend.intern("shared");
+/// let shared = backend.resolve_arc(symbol).unwrap().clone();
+/// drop(backend);
+/// assert_eq!(shared, "shared");
+/// ```
+pub struct ArcStrBackend<S = DefaultSymbol> {
+    strings: Vec<StaticArcStr>,
+    _marker: PhantomData<fn() -> S>,
+}
+
+impl<S: Symbol> ArcStrBackend<S> {
+    /// The shared pointer to the string of `symbol`, to keep it around without the interner.
+    #[inline]
+    pub fn resolve_arc(&self, symbol: S) -> Option<&StaticArcStr> {
+        self.strings.get(symbol.to_usize())
+    }
+
+    fn push(&mut self, string: StaticArcStr) -> S {
+        let symbol =
+            S::try_from_usize(self.strings.len()).expect("the symbol type ran out of symbols");
+        self.strings.push(string);
+        symbol
+    }
+}
+
+impl<S> Default for ArcStrBackend<S> {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            strings: Vec::new(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<S> Clone for ArcStrBackend<S> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            strings: self.strings.clone(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<S> fmt::Debug for ArcStrBackend<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(&self.strings).finish()
+    }
+}
+
+impl<S: Symbol> Backend for ArcStrBackend<S> {
+    type Symbol = S;
+    type Iter<'a>
+        = Iter<'a, S>
+    where
+        Self: 'a;
+
+    #[inline]
+    fn with_capacity(cap: usize) -> Self {
+        Self {
+            strings: Vec::with_capacity(cap),
+            _marker: PhantomData,
+        }
+    }
+
+    fn intern(&mut self, string: &str) -> S {
+        let mut buf = ArcBuf::with_capacity(string.len());
+        buf.extend_from_slice(string.as_bytes());
+        // SAFETY: The buffer is a copy of a `str`.
+        self.push(StaticArcStr::Arc(unsafe { into_arc_str(buf) }))
+    }
+
+    #[inline]
+    fn intern_static(&mut self, string: &'static str) -> S {
+        self.push(StaticArcStr::Static(string))
+    }
+
+    #[inline]
+    fn shrink_to_fit(&mut self) {
+        self.strings.shrink_to_fit();
+    }
+
+    #[inline]
+    fn resolve(&self, symbol: S) -> Option<&str> {
+        self.resolve_arc(symbol).map(|string| &**string)
+    }
+
+    #[inline]
+    unsafe fn resolve_unchecked(&self, symbol: S) -> &str {
+        self.strings.get_unchecked(symbol.to_usize())
+    }
+
+    #[inline]
+    fn iter(&self) -> Iter<'_, S> {
+        Iter {
+            strings: self.strings.iter().enumerate(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+/// An iterator over the symbols and strings of an [`ArcStrBackend`].
+pub struct Iter<'a, S> {
+    strings: Enumerate<slice::Iter<'a, StaticArcStr>>,
+    _marker: PhantomData<fn() -> S>,
+}
+
+impl<'a, S: Symbol> Iterator for Iter<'a, S> {
+    type Item = (S, &'a str);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.strings.next().map(|(i, string)| {
+            let symbol = S::try_from_usize(i).expect("the symbol type ran out of symbols");
+            (symbol, &**string)
+        })
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.strings.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use string_interner::{symbol::SymbolU16, StringInterner};
+
+    #[test]
+    fn test_arc_str_backend_iter_and_static() {
+        let mut interner = StringInterner::<ArcStrBackend<SymbolU16>>::new();
+        let words = ["a", "b", "a", "c", "b"].map(|word| interner.get_or_intern(word));
+        interner.get_or_intern_static("static");
+
+        assert_eq!(words[0], words[2]);
+        assert_eq!(interner.len(), 4);
+        assert_eq!(
+            interner.into_iter().map(|(_, s)| s).collect::<Vec<_>>(),
+            ["a", "b", "c", "static"]
+        );
+
+        let mut backend = ArcStrBackend::<SymbolU16>::default();
+        let symbol = backend.intern_static("static");
+        assert!(matches!(
+            backend.resolve_arc(symbol),
+            Some(StaticArcStr::Static("static"))
+        ));
+    }
+}
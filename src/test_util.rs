@@ -0,0 +1,83 @@
+use std::{cell::Cell, marker::PhantomData};
+
+thread_local! {
+    /// The number of allocations left before they start failing, if they are set up to fail.
+    static ALLOCS_LEFT: Cell<Option<usize>> = const { Cell::new(None) };
+}
+
+/// Whether the next allocation or reallocation of the current thread should fail, counting it
+/// against the allocations that are left.
+pub(crate) fn should_fail() -> bool {
+    ALLOCS_LEFT
+        .try_with(|left| match left.get() {
+            Some(0) => true,
+            Some(n) => {
+                left.set(Some(n - 1));
+                false
+            }
+            None => false,
+        })
+        .unwrap_or(false)
+}
+
+/// Makes the allocations of the collectors and builders on the current thread fail after `n` of
+/// them succeed, until the returned guard is dropped.
+///
+/// Allocating a buffer and growing or trimming it each count as one allocation, so a failure can
+/// be set up at an exact point of a collection, e.g. to cover what happens when a collection with
+/// [`OomPolicy::Error`](crate::OomPolicy::Error) fails. Only the buffers the elements are
+/// collected into are affected, not the allocations of anything else.
+///
+/// Guards can be nested, dropping one restores the allocations that were left before it.
+pub fn fail_allocs_after(n: usize) -> FailAllocsGuard {
+    FailAllocsGuard {
+        previous: ALLOCS_LEFT.with(|left| left.replace(Some(n))),
+        _marker: PhantomData,
+    }
+}
+
+/// Makes the allocations of the current thread fail until it is dropped, see
+/// [`fail_allocs_after`].
+#[must_use = "the allocations stop failing when the guard is dropped"]
+#[derive(Debug)]
+pub struct FailAllocsGuard {
+    previous: Option<usize>,
+    // the guard only affects the thread it was made on
+    _marker: PhantomData<*const ()>,
+}
+
+impl Drop for FailAllocsGuard {
+    fn drop(&mut self) {
+        let _ = ALLOCS_LEFT.try_with(|left| left.set(self.previous));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::allocator::{Global, RawAlloc};
+    use std::alloc::Layout;
+
+    #[test]
+    fn test_fail_allocs_after() {
+        let layout = Layout::new::<[usize; 4]>();
+        let bigger = Layout::new::<[usize; 8]>();
+
+        let guard = fail_allocs_after(2);
+        let ptr = Global.alloc(layout);
+        assert!(!ptr.is_null());
+
+        // SAFETY: `ptr` was allocated with `layout`, and the failed reallocation leaves it as is.
+        unsafe {
+            let ptr = Global.realloc(ptr, layout, bigger);
+            assert!(!ptr.is_null());
+            assert!(Global.realloc(ptr, bigger, layout).is_null());
+            assert!(Global.alloc(layout).is_null());
+
+            drop(guard);
+            let ptr = Global.realloc(ptr, bigger, layout);
+            assert!(!ptr.is_null());
+            Global.dealloc(ptr, layout);
+        }
+    }
+}
@@ -0,0 +1,514 @@
+#![cfg(target_has_atomic = "ptr")]
+use crate::{
+    allocator::{Global, RawAlloc},
+    raw::{capacity_overflow, extend, Prefix, RawBuf},
+};
+use std::{
+    alloc::Layout,
+    borrow::Borrow,
+    cmp, fmt,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    mem,
+    ops::Deref,
+    process,
+    ptr::{self, NonNull},
+    slice,
+    sync::atomic::{self, AtomicUsize, Ordering},
+};
+
+/// How the strong count is laid out in the header of a [`ThinArcSlice`] or [`ThinArcStr`].
+///
+/// [`ThinArcStr`]: crate::ThinArcStr
+pub trait HeaderPadding: sealed::Sealed {
+    #[doc(hidden)]
+    type Count: Send + Sync;
+
+    #[doc(hidden)]
+    fn new_count() -> Self::Count;
+
+    #[doc(hidden)]
+    fn count(count: &Self::Count) -> &AtomicUsize;
+}
+
+/// The default header layout, the strong count and the length are packed right before the data.
+pub enum Unpadded {}
+
+impl HeaderPadding for Unpadded {
+    type Count = AtomicUsize;
+
+    #[inline]
+    fn new_count() -> AtomicUsize {
+        AtomicUsize::new(1)
+    }
+
+    #[inline]
+    fn count(count: &AtomicUsize) -> &AtomicUsize {
+        count
+    }
+}
+
+/// A header layout that gives the strong count a cache line of its own.
+///
+/// Cloning and dropping a pointer writes to the strong count, which makes every other core
+/// reading the same cache line fetch it again. With this layout the length and the data are moved
+/// past the cache line holding the strong count, so reads of the data don't contend with
+/// reference counting on other threads, at the cost of a bigger allocation.
+///
+/// # Examples
+/// ```rust
+/// use collect_into_rc_slice::*;
+///
+/// let arc: ThinArcSlice<u64, CachePadded> = (0..1024).collect();
+///
+/// assert_eq!(arc.len(), 1024);
+/// ```
+pub enum CachePadded {}
+
+/// An atomic count aligned to the size of a cache line, which is 128 bytes on `x86_64` and
+/// `aarch64` since they prefetch cache lines in pairs, and 64 bytes elsewhere.
+#[doc(hidden)]
+#[cfg_attr(any(target_arch = "x86_64", target_arch = "aarch64"), repr(align(128)))]
+#[cfg_attr(
+    not(any(target_arch = "x86_64", target_arch = "aarch64")),
+    repr(align(64))
+)]
+pub struct PaddedCount(AtomicUsize);
+
+impl HeaderPadding for CachePadded {
+    type Count = PaddedCount;
+
+    #[inline]
+    fn new_count() -> PaddedCount {
+        PaddedCount(AtomicUsize::new(1))
+    }
+
+    #[inline]
+    fn count(count: &PaddedCount) -> &AtomicUsize {
+        &count.0
+    }
+}
+
+mod sealed {
+    pub trait Sealed {}
+
+    impl Sealed for super::Unpadded {}
+    impl Sealed for super::CachePadded {}
+}
+
+#[repr(C)]
+pub(crate) struct ThinArcHeader<P: HeaderPadding> {
+    pub(crate) strong_count: P::Count,
+    pub(crate) len: usize,
+}
+
+// SAFETY: `ThinArcSlice` lays out its allocation as the header followed by the elements.
+unsafe impl<P: HeaderPadding> Prefix for ThinArcHeader<P> {
+    fn layout(data: Layout) -> (Layout, usize) {
+        // the data starts right after `len` rather than at the padded size of the header, which
+        // would leave most of another cache line empty for a `CachePadded` header
+        let (header, _) = Layout::new::<P::Count>()
+            .extend(Layout::new::<usize>())
+            .unwrap_or_else(|_| capacity_overflow());
+        extend(header, data)
+    }
+}
+
+pub(crate) type ThinArcBuf<T, P = Unpadded> = RawBuf<ThinArcHeader<P>, T>;
+
+/// A thread-safe reference-counted slice that is only one pointer wide.
+///
+/// Unlike `Arc<[T]>`, which is a fat pointer carrying the length, the length is stored in the
+/// heap allocation next to the strong count. There are no weak references.
+///
+/// The pointer is never null, so `Option<ThinArcSlice<T>>` is one pointer wide as well.
+///
+/// The header can be padded to keep the strong count on its own cache line, see [`CachePadded`].
+///
+/// # Examples
+/// ```rust
+/// use collect_into_rc_slice::*;
+///
+/// let arc: ThinArcSlice<i32> = [1, 2, 3].into_iter().collect_into_thin_arc_slice();
+///
+/// assert_eq!(&*arc, &[1, 2, 3]);
+/// assert_eq!(std::mem::size_of::<ThinArcSlice<i32>>(), std::mem::size_of::<usize>());
+/// ```
+pub struct ThinArcSlice<T, P: HeaderPadding = Unpadded> {
+    ptr: NonNull<ThinArcHeader<P>>,
+    _marker: PhantomData<T>,
+}
+
+// SAFETY: Just like `Arc<[T]>`, the elements are shared between threads.
+unsafe impl<T: Send + Sync, P: HeaderPadding> Send for ThinArcSlice<T, P> {}
+unsafe impl<T: Send + Sync, P: HeaderPadding> Sync for ThinArcSlice<T, P> {}
+
+impl<T, P: HeaderPadding> ThinArcSlice<T, P> {
+    pub(crate) fn from_buf(buf: ThinArcBuf<T, P>) -> Self {
+        let (alloc, data) = buf.into_raw_parts();
+        let ptr = alloc.cast::<ThinArcHeader<P>>();
+
+        // SAFETY: The prefix of the allocation is reserved for the fields of the header. They are
+        // written one by one, since the data may start before the end of a padded header.
+        unsafe {
+            ptr::addr_of_mut!((*ptr.as_ptr()).strong_count).write(P::new_count());
+            ptr::addr_of_mut!((*ptr.as_ptr()).len).write(data.len());
+        }
+
+        Self {
+            ptr,
+            _marker: PhantomData,
+        }
+    }
+
+    // The fields of the header are accessed on their own rather than through a reference to the
+    // whole header, which may overlap the data.
+
+    #[inline]
+    fn strong(&self) -> &AtomicUsize {
+        // SAFETY: The header is initialized and lives as long as there are strong references.
+        P::count(unsafe { &*ptr::addr_of!((*self.ptr.as_ptr()).strong_count) })
+    }
+
+    #[inline]
+    fn header_len(&self) -> usize {
+        // SAFETY: See `strong`.
+        unsafe { ptr::addr_of!((*self.ptr.as_ptr()).len).read() }
+    }
+
+    #[inline]
+    fn data_ptr(&self) -> *mut T {
+        // SAFETY: The data offset is always within the allocation.
+        unsafe {
+            self.ptr
+                .as_ptr()
+                .cast::<u8>()
+                .add(ThinArcBuf::<T, P>::data_offset()) as *mut T
+        }
+    }
+
+    /// Gets the number of pointers to this allocation.
+    #[inline]
+    pub fn strong_count(this: &Self) -> usize {
+        this.strong().load(Ordering::Acquire)
+    }
+
+    /// Returns `true` if the two pointers point to the same allocation.
+    #[inline]
+    pub fn ptr_eq(this: &Self, other: &Self) -> bool {
+        this.ptr == other.ptr
+    }
+
+    /// Consumes the pointer, returning a thin pointer to the first element.
+    ///
+    /// The allocation is leaked unless the pointer is turned back into a `ThinArcSlice` with
+    /// [`ThinArcSlice::from_raw`].
+    #[inline]
+    pub fn into_raw(this: Self) -> *const T {
+        let ptr = this.data_ptr();
+        mem::forget(this);
+        ptr
+    }
+
+    /// Constructs a `ThinArcSlice` from a pointer returned by [`ThinArcSlice::into_raw`].
+    ///
+    /// # Safety
+    /// `ptr` must have been returned by `ThinArcSlice::<T, P>::into_raw`, and each call to
+    /// `into_raw` can be matched by at most one call to `from_raw`.
+    #[inline]
+    pub unsafe fn from_raw(ptr: *const T) -> Self {
+        // SAFETY: The caller guarantees `ptr` points right after the header of a live allocation.
+        let ptr = unsafe { ptr.cast::<u8>().sub(ThinArcBuf::<T, P>::data_offset()) };
+
+        Self {
+            // SAFETY: `ptr` is derived from a non-null allocation.
+            ptr: unsafe { NonNull::new_unchecked(ptr as *mut ThinArcHeader<P>) },
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T, P: HeaderPadding> Deref for ThinArcSlice<T, P> {
+    type Target = [T];
+
+    #[inline]
+    fn deref(&self) -> &[T] {
+        // SAFETY: The allocation holds `len` initialized elements after the header.
+        unsafe { slice::from_raw_parts(self.data_ptr(), self.header_len()) }
+    }
+}
+
+impl<T, P: HeaderPadding> Clone for ThinArcSlice<T, P> {
+    #[inline]
+    fn clone(&self) -> Self {
+        // Just like `Arc`, a relaxed increment is enough since a reference already exists, and the
+        // process is aborted instead of letting the count overflow.
+        let old_count = self.strong().fetch_add(1, Ordering::Relaxed);
+        if old_count > isize::MAX as usize {
+            process::abort();
+        }
+
+        Self {
+            ptr: self.ptr,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T, P: HeaderPadding> Drop for ThinArcSlice<T, P> {
+    fn drop(&mut self) {
+        if self.strong().fetch_sub(1, Ordering::Release) == 1 {
+            // Synchronize with the other references dropping theirs, just like `Arc`.
+            atomic::fence(Ordering::Acquire);
+            let len = self.header_len();
+
+            // SAFETY:
+            // - This was the last reference, so nothing can observe the elements anymore.
+            // - The allocation was trimmed to exactly `len` elements.
+            unsafe {
+                ptr::drop_in_place(ptr::slice_from_raw_parts_mut(self.data_ptr(), len));
+                Global.dealloc(self.ptr.as_ptr().cast(), ThinArcBuf::<T, P>::layout(len));
+            }
+        }
+    }
+}
+
+impl<T, P: HeaderPadding> AsRef<[T]> for ThinArcSlice<T, P> {
+    #[inline]
+    fn as_ref(&self) -> &[T] {
+        self
+    }
+}
+
+impl<T: fmt::Debug, P: HeaderPadding> fmt::Debug for ThinArcSlice<T, P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<T: PartialEq, P: HeaderPadding> PartialEq for ThinArcSlice<T, P> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+impl<T: Eq, P: HeaderPadding> Eq for ThinArcSlice<T, P> {}
+
+impl<T: Hash, P: HeaderPadding> Hash for ThinArcSlice<T, P> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (**self).hash(state);
+    }
+}
+
+impl<T: PartialOrd, P: HeaderPadding> PartialOrd for ThinArcSlice<T, P> {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        (**self).partial_cmp(&**other)
+    }
+}
+
+impl<T: Ord, P: HeaderPadding> Ord for ThinArcSlice<T, P> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        (**self).cmp(&**other)
+    }
+}
+
+impl<T, P: HeaderPadding> Borrow<[T]> for ThinArcSlice<T, P> {
+    #[inline]
+    fn borrow(&self) -> &[T] {
+        self
+    }
+}
+
+impl<T, P: HeaderPadding> FromIterator<T> for ThinArcSlice<T, P> {
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::from_buf(ThinArcBuf::from_iter(iter.into_iter()))
+    }
+}
+
+pub trait CollectIntoThinArcSlice<T> {
+    /// Collects the iterator into a [`ThinArcSlice<T>`].
+    ///
+    /// # Examples
+    /// ```rust
+    /// use collect_into_rc_slice::*;
+    ///
+    /// let arr = [1, 2, 3, 4, 5];
+    /// let arc = arr.into_iter().collect_into_thin_arc_slice();
+    ///
+    /// assert_eq!(&*arc, &[1, 2, 3, 4, 5]);
+    /// ```
+    fn collect_into_thin_arc_slice(self) -> ThinArcSlice<T>;
+}
+
+impl<I, T> CollectIntoThinArcSlice<T> for I
+where
+    I: IntoIterator<Item = T>,
+{
+    fn collect_into_thin_arc_slice(self) -> ThinArcSlice<T> {
+        ThinArcSlice::from_buf(ThinArcBuf::from_iter(self.into_iter()))
+    }
+}
+
+// SAFETY: The pointer is the one of `into_raw` and `from_raw`, which round-trip, and it is unique
+// to the allocation since it points right after its header.
+#[cfg(feature = "arc-swap")]
+unsafe impl<T, P: HeaderPadding> arc_swap::RefCnt for ThinArcSlice<T, P> {
+    type Base = T;
+
+    #[inline]
+    fn into_ptr(me: Self) -> *mut T {
+        Self::into_raw(me) as *mut T
+    }
+
+    #[inline]
+    fn as_ptr(me: &Self) -> *mut T {
+        me.data_ptr()
+    }
+
+    #[inline]
+    unsafe fn from_ptr(ptr: *const T) -> Self {
+        // SAFETY: The caller guarantees `ptr` was returned by `into_ptr`.
+        unsafe { Self::from_raw(ptr) }
+    }
+}
+
+/// Collects `iter` into a fresh slice and atomically stores it in `swap`, returning the slice it
+/// replaced.
+///
+/// This is the usual way of hot-reloading a shared table: readers keep using the old slice until
+/// they load the new one, and the old one is freed once the last of them is done with it.
+/// `arc_swap` can only store thin pointers, so the slice is a [`ThinArcSlice`], which is still a
+/// single allocation, rather than an `Arc<[T]>`.
+///
+/// # Examples
+/// ```rust
+/// use arc_swap::ArcSwapAny;
+/// use collect_into_rc_slice::*;
+///
+/// let table = ArcSwapAny::new([1, 2, 3].into_iter().collect_into_thin_arc_slice());
+/// let reader = table.load_full();
+///
+/// let old = collect_and_store((4..=6).map(|i| i * 10), &table);
+///
+/// assert_eq!(&*table.load_full(), &[40, 50, 60]);
+/// assert!(ThinArcSlice::ptr_eq(&old, &reader));
+/// ```
+#[cfg(feature = "arc-swap")]
+pub fn collect_and_store<T, P, I>(
+    iter: I,
+    swap: &arc_swap::ArcSwapAny<ThinArcSlice<T, P>>,
+) -> ThinArcSlice<T, P>
+where
+    P: HeaderPadding,
+    I: IntoIterator<Item = T>,
+{
+    swap.swap(iter.into_iter().collect())
+}
+
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize, P: HeaderPadding> serde::Serialize for ThinArcSlice<T, P> {
+    #[inline]
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_thin_arc_slice() {
+        let mut iter = vec![String::from("a"), String::from("b")].into_iter();
+        let arc = std::iter::from_fn(move || iter.next()).collect_into_thin_arc_slice();
+        assert_eq!(&*arc, &["a", "b"]);
+
+        let arc2 = arc.clone();
+        assert_eq!(ThinArcSlice::strong_count(&arc), 2);
+        assert!(ThinArcSlice::ptr_eq(&arc, &arc2));
+
+        drop(arc);
+        assert_eq!(ThinArcSlice::strong_count(&arc2), 1);
+    }
+
+    #[test]
+    fn test_thin_arc_slice_threads() {
+        let arc: ThinArcSlice<_> = (0..100).collect();
+
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let arc = arc.clone();
+                std::thread::spawn(move || arc.iter().sum::<i32>())
+            })
+            .collect();
+
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), 4950);
+        }
+        assert_eq!(ThinArcSlice::strong_count(&arc), 1);
+    }
+
+    #[test]
+    fn test_thin_arc_slice_cache_padded() {
+        let arc: ThinArcSlice<u8, CachePadded> = (0..10).collect();
+        let data_offset = arc.as_ptr() as usize - arc.ptr.as_ptr() as usize;
+
+        // the length shares the second cache line with the data
+        assert_eq!(&*arc, &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9]);
+        assert_eq!(
+            data_offset,
+            std::mem::align_of::<PaddedCount>() + std::mem::size_of::<usize>()
+        );
+        assert_eq!(
+            ThinArcBuf::<u8, CachePadded>::layout(10).size(),
+            2 * std::mem::align_of::<PaddedCount>()
+        );
+        assert_eq!(ThinArcSlice::strong_count(&arc.clone()), 2);
+    }
+
+    #[test]
+    fn test_thin_arc_slice_raw() {
+        let rc: ThinArcSlice<String> = ["a", "b"].into_iter().map(String::from).collect();
+        let ptr = ThinArcSlice::into_raw(rc.clone());
+
+        // SAFETY: `ptr` was just returned by `into_raw`.
+        let back = unsafe { ThinArcSlice::from_raw(ptr) };
+        assert!(ThinArcSlice::ptr_eq(&rc, &back));
+        assert_eq!(ThinArcSlice::strong_count(&rc), 2);
+        assert_eq!(
+            std::mem::size_of::<Option<ThinArcSlice<String>>>(),
+            std::mem::size_of::<usize>()
+        );
+    }
+
+    #[cfg(feature = "arc-swap")]
+    #[test]
+    fn test_thin_arc_slice_arc_swap() {
+        let swap = arc_swap::ArcSwapAny::new(ThinArcSlice::<String>::from_iter([]));
+        let guard = swap.load();
+
+        let old = collect_and_store(["a", "b"].map(String::from), &swap);
+        assert!(old.is_empty());
+        assert!(guard.is_empty());
+        assert_eq!(&**swap.load(), ["a", "b"]);
+
+        drop(guard);
+        assert_eq!(ThinArcSlice::strong_count(&old), 1);
+        assert_eq!(ThinArcSlice::strong_count(&swap.load_full()), 2);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_thin_arc_slice_serialize() {
+        let slice: ThinArcSlice<_, CachePadded> = [(1, 'a'), (2, 'b')].into_iter().collect();
+
+        assert_eq!(
+            serde_json::to_string(&slice).unwrap(),
+            r#"[[1,"a"],[2,"b"]]"#
+        );
+    }
+}
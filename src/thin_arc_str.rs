@@ -0,0 +1,242 @@
+#![cfg(target_has_atomic = "ptr")]
+use crate::{
+    into_char::IntoChar,
+    thin_arc_slice::{HeaderPadding, ThinArcBuf, ThinArcSlice, Unpadded},
+};
+use std::{
+    borrow::Borrow,
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+    ops::Deref,
+    str,
+};
+
+/// A thread-safe reference-counted string that is only one pointer wide.
+///
+/// This is the string counterpart of [`ThinArcSlice`], the length is stored in the heap allocation
+/// next to the strong count. Like [`ThinArcSlice`], the header can be padded to a cache line with
+/// [`CachePadded`](crate::CachePadded).
+///
+/// # Examples
+/// ```rust
+/// use collect_into_rc_slice::*;
+///
+/// let s: ThinArcStr = ["Hello", ", ", "world!"].into_iter().collect();
+///
+/// assert_eq!(&*s, "Hello, world!");
+/// assert_eq!(std::mem::size_of::<ThinArcStr>(), std::mem::size_of::<usize>());
+/// ```
+pub struct ThinArcStr<P: HeaderPadding = Unpadded>(ThinArcSlice<u8, P>);
+
+impl<P: HeaderPadding> ThinArcStr<P> {
+    /// Gets the number of pointers to this allocation.
+    #[inline]
+    pub fn strong_count(this: &Self) -> usize {
+        ThinArcSlice::strong_count(&this.0)
+    }
+
+    /// Returns `true` if the two pointers point to the same allocation.
+    #[inline]
+    pub fn ptr_eq(this: &Self, other: &Self) -> bool {
+        ThinArcSlice::ptr_eq(&this.0, &other.0)
+    }
+
+    /// Consumes the pointer, returning a thin pointer to the first byte of the string.
+    ///
+    /// The allocation is leaked unless the pointer is turned back into a `ThinArcStr` with
+    /// [`ThinArcStr::from_raw`].
+    #[inline]
+    pub fn into_raw(this: Self) -> *const u8 {
+        ThinArcSlice::into_raw(this.0)
+    }
+
+    /// Constructs a `ThinArcStr` from a pointer returned by [`ThinArcStr::into_raw`].
+    ///
+    /// # Safety
+    /// `ptr` must have been returned by `ThinArcStr::into_raw`, and each call to `into_raw` can be
+    /// matched by at most one call to `from_raw`.
+    #[inline]
+    pub unsafe fn from_raw(ptr: *const u8) -> Self {
+        // SAFETY: The caller guarantees `ptr` came from a `ThinArcStr`.
+        Self(unsafe { ThinArcSlice::from_raw(ptr) })
+    }
+
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        // SAFETY: The bytes were only ever written from `char`s and `str`s.
+        unsafe { str::from_utf8_unchecked(&self.0) }
+    }
+}
+
+impl<P: HeaderPadding> Clone for ThinArcStr<P> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<P: HeaderPadding> Deref for ThinArcStr<P> {
+    type Target = str;
+
+    #[inline]
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<P: HeaderPadding> AsRef<str> for ThinArcStr<P> {
+    #[inline]
+    fn as_ref(&self) -> &str {
+        self
+    }
+}
+
+impl<P: HeaderPadding> fmt::Debug for ThinArcStr<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl<P: HeaderPadding> fmt::Display for ThinArcStr<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self.as_str(), f)
+    }
+}
+
+impl<P: HeaderPadding> PartialEq for ThinArcStr<P> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl<P: HeaderPadding> Eq for ThinArcStr<P> {}
+
+impl<P: HeaderPadding> PartialOrd for ThinArcStr<P> {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<P: HeaderPadding> Ord for ThinArcStr<P> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+impl<P: HeaderPadding> Hash for ThinArcStr<P> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state);
+    }
+}
+
+impl<P: HeaderPadding> Borrow<str> for ThinArcStr<P> {
+    #[inline]
+    fn borrow(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<P: HeaderPadding> PartialEq<str> for ThinArcStr<P> {
+    #[inline]
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl<P: HeaderPadding> PartialEq<&str> for ThinArcStr<P> {
+    #[inline]
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl<P: HeaderPadding> FromIterator<char> for ThinArcStr<P> {
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = char>>(iter: I) -> Self {
+        Self(ThinArcSlice::from_buf(ThinArcBuf::from_chars(
+            iter.into_iter(),
+        )))
+    }
+}
+
+impl<'a, P: HeaderPadding> FromIterator<&'a str> for ThinArcStr<P> {
+    fn from_iter<I: IntoIterator<Item = &'a str>>(iter: I) -> Self {
+        let iter = iter.into_iter();
+        let mut buf = ThinArcBuf::with_capacity(iter.size_hint().0);
+
+        for s in iter {
+            buf.extend_from_slice(s.as_bytes());
+        }
+
+        Self(ThinArcSlice::from_buf(buf))
+    }
+}
+
+pub trait CollectIntoThinArcStr {
+    /// Collects the iterator into a [`ThinArcStr`].
+    ///
+    /// # Examples
+    /// ```rust
+    /// use collect_into_rc_slice::*;
+    ///
+    /// let s = "Hello, world!".chars().collect_into_thin_arc_str();
+    ///
+    /// assert_eq!(&*s, "Hello, world!");
+    /// ```
+    fn collect_into_thin_arc_str(self) -> ThinArcStr;
+}
+
+impl<T> CollectIntoThinArcStr for T
+where
+    T: IntoIterator,
+    T::Item: IntoChar,
+{
+    fn collect_into_thin_arc_str(self) -> ThinArcStr {
+        self.into_iter().map(IntoChar::into_char).collect()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<P: HeaderPadding> serde::Serialize for ThinArcStr<P> {
+    #[inline]
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_collect_into_thin_arc_str() {
+        let s = "Hello, world!".chars().collect_into_thin_arc_str();
+
+        assert_eq!(&*s, "Hello, world!");
+        assert_eq!(s.len(), 13);
+        assert_eq!(ThinArcStr::strong_count(&s), 1);
+    }
+
+    #[test]
+    fn test_thin_arc_str_set() {
+        let a: ThinArcStr = ["foo", "bar"].into_iter().collect();
+        let b = "foobar".chars().collect_into_thin_arc_str();
+        let set: HashSet<_> = [a, b.clone()].into_iter().collect();
+
+        assert_eq!(set.len(), 1);
+        assert!(set.contains(&b));
+    }
+
+    #[test]
+    fn test_thin_arc_str_cache_padded() {
+        let s: ThinArcStr<crate::CachePadded> = "Hello, world!".chars().collect();
+
+        assert_eq!(&*s, "Hello, world!");
+        assert_eq!(ThinArcStr::strong_count(&s.clone()), 2);
+    }
+}
@@ -0,0 +1,315 @@
+use crate::{
+    allocator::{Global, RawAlloc},
+    raw::{Prefix, RawBuf},
+};
+use std::{
+    borrow::Borrow,
+    cell::Cell,
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    mem,
+    ops::Deref,
+    process,
+    ptr::{self, NonNull},
+    slice,
+};
+
+#[repr(C)]
+pub(crate) struct ThinRcHeader {
+    pub(crate) strong_count: Cell<usize>,
+    pub(crate) len: usize,
+}
+
+// SAFETY: `ThinRcSlice` lays out its allocation as the header followed by the elements.
+unsafe impl Prefix for ThinRcHeader {}
+
+pub(crate) type ThinRcBuf<T> = RawBuf<ThinRcHeader, T>;
+
+/// A single-threaded reference-counted slice that is only one pointer wide.
+///
+/// Unlike `Rc<[T]>`, which is a fat pointer carrying the length, the length is stored in the
+/// heap allocation next to the strong count. There are no weak references.
+///
+/// The pointer is never null, so `Option<ThinRcSlice<T>>` is one pointer wide as well.
+///
+/// # Examples
+/// ```rust
+/// use collect_into_rc_slice::*;
+///
+/// let rc: ThinRcSlice<i32> = [1, 2, 3].into_iter().collect_into_thin_rc_slice();
+///
+/// assert_eq!(&*rc, &[1, 2, 3]);
+/// assert_eq!(std::mem::size_of::<ThinRcSlice<i32>>(), std::mem::size_of::<usize>());
+/// ```
+pub struct ThinRcSlice<T> {
+    ptr: NonNull<ThinRcHeader>,
+    _marker: PhantomData<T>,
+}
+
+impl<T> ThinRcSlice<T> {
+    pub(crate) fn from_buf(buf: ThinRcBuf<T>) -> Self {
+        let (alloc, data) = buf.into_raw_parts();
+        let ptr = alloc.cast::<ThinRcHeader>();
+
+        // SAFETY: The prefix of the allocation is reserved for the header.
+        unsafe {
+            ptr::write(
+                ptr.as_ptr(),
+                ThinRcHeader {
+                    strong_count: Cell::new(1),
+                    len: data.len(),
+                },
+            );
+        }
+
+        Self {
+            ptr,
+            _marker: PhantomData,
+        }
+    }
+
+    #[inline]
+    fn header(&self) -> &ThinRcHeader {
+        // SAFETY: The header is initialized and lives as long as there are strong references.
+        unsafe { self.ptr.as_ref() }
+    }
+
+    #[inline]
+    fn data_ptr(&self) -> *mut T {
+        // SAFETY: The data offset is always within the allocation.
+        unsafe {
+            self.ptr
+                .as_ptr()
+                .cast::<u8>()
+                .add(ThinRcBuf::<T>::data_offset()) as *mut T
+        }
+    }
+
+    /// Gets the number of pointers to this allocation.
+    #[inline]
+    pub fn strong_count(this: &Self) -> usize {
+        this.header().strong_count.get()
+    }
+
+    /// Returns `true` if the two pointers point to the same allocation.
+    #[inline]
+    pub fn ptr_eq(this: &Self, other: &Self) -> bool {
+        this.ptr == other.ptr
+    }
+
+    /// Consumes the pointer, returning a thin pointer to the first element.
+    ///
+    /// The allocation is leaked unless the pointer is turned back into a `ThinRcSlice` with
+    /// [`ThinRcSlice::from_raw`].
+    #[inline]
+    pub fn into_raw(this: Self) -> *const T {
+        let ptr = this.data_ptr();
+        mem::forget(this);
+        ptr
+    }
+
+    /// Constructs a `ThinRcSlice` from a pointer returned by [`ThinRcSlice::into_raw`].
+    ///
+    /// # Safety
+    /// `ptr` must have been returned by `ThinRcSlice::<T>::into_raw`, and each call to `into_raw`
+    /// can be matched by at most one call to `from_raw`.
+    #[inline]
+    pub unsafe fn from_raw(ptr: *const T) -> Self {
+        // SAFETY: The caller guarantees `ptr` points right after the header of a live allocation.
+        let ptr = unsafe { ptr.cast::<u8>().sub(ThinRcBuf::<T>::data_offset()) };
+
+        Self {
+            // SAFETY: `ptr` is derived from a non-null allocation.
+            ptr: unsafe { NonNull::new_unchecked(ptr as *mut ThinRcHeader) },
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T> Deref for ThinRcSlice<T> {
+    type Target = [T];
+
+    #[inline]
+    fn deref(&self) -> &[T] {
+        // SAFETY: The allocation holds `len` initialized elements after the header.
+        unsafe { slice::from_raw_parts(self.data_ptr(), self.header().len) }
+    }
+}
+
+impl<T> Clone for ThinRcSlice<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        let strong_count = &self.header().strong_count;
+
+        // Just like `Rc`, abort instead of letting the count overflow.
+        if strong_count.get() == usize::MAX {
+            process::abort();
+        }
+        strong_count.set(strong_count.get() + 1);
+
+        Self {
+            ptr: self.ptr,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T> Drop for ThinRcSlice<T> {
+    fn drop(&mut self) {
+        let strong_count = &self.header().strong_count;
+        strong_count.set(strong_count.get() - 1);
+
+        if strong_count.get() == 0 {
+            let len = self.header().len;
+
+            // SAFETY:
+            // - This was the last reference, so nothing can observe the elements anymore.
+            // - The allocation was trimmed to exactly `len` elements.
+            unsafe {
+                ptr::drop_in_place(ptr::slice_from_raw_parts_mut(self.data_ptr(), len));
+                Global.dealloc(self.ptr.as_ptr().cast(), ThinRcBuf::<T>::layout(len));
+            }
+        }
+    }
+}
+
+impl<T> AsRef<[T]> for ThinRcSlice<T> {
+    #[inline]
+    fn as_ref(&self) -> &[T] {
+        self
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for ThinRcSlice<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<T: PartialEq> PartialEq for ThinRcSlice<T> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+impl<T: Eq> Eq for ThinRcSlice<T> {}
+
+impl<T: Hash> Hash for ThinRcSlice<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (**self).hash(state);
+    }
+}
+
+impl<T: PartialOrd> PartialOrd for ThinRcSlice<T> {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        (**self).partial_cmp(&**other)
+    }
+}
+
+impl<T: Ord> Ord for ThinRcSlice<T> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        (**self).cmp(&**other)
+    }
+}
+
+impl<T> Borrow<[T]> for ThinRcSlice<T> {
+    #[inline]
+    fn borrow(&self) -> &[T] {
+        self
+    }
+}
+
+impl<T> FromIterator<T> for ThinRcSlice<T> {
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::from_buf(ThinRcBuf::from_iter(iter.into_iter()))
+    }
+}
+
+pub trait CollectIntoThinRcSlice<T> {
+    /// Collects the iterator into a [`ThinRcSlice<T>`].
+    ///
+    /// # Examples
+    /// ```rust
+    /// use collect_into_rc_slice::*;
+    ///
+    /// let arr = [1, 2, 3, 4, 5];
+    /// let rc = arr.into_iter().collect_into_thin_rc_slice();
+    ///
+    /// assert_eq!(&*rc, &[1, 2, 3, 4, 5]);
+    /// ```
+    fn collect_into_thin_rc_slice(self) -> ThinRcSlice<T>;
+}
+
+impl<I, T> CollectIntoThinRcSlice<T> for I
+where
+    I: IntoIterator<Item = T>,
+{
+    fn collect_into_thin_rc_slice(self) -> ThinRcSlice<T> {
+        ThinRcSlice::from_buf(ThinRcBuf::from_iter(self.into_iter()))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize> serde::Serialize for ThinRcSlice<T> {
+    #[inline]
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_thin_rc_slice() {
+        let mut iter = vec![String::from("a"), String::from("b")].into_iter();
+        let rc = std::iter::from_fn(move || iter.next()).collect_into_thin_rc_slice();
+        assert_eq!(&*rc, &["a", "b"]);
+
+        let rc2 = rc.clone();
+        assert_eq!(ThinRcSlice::strong_count(&rc), 2);
+        assert!(ThinRcSlice::ptr_eq(&rc, &rc2));
+
+        drop(rc);
+        assert_eq!(ThinRcSlice::strong_count(&rc2), 1);
+    }
+
+    #[test]
+    fn test_thin_rc_slice_empty() {
+        let rc: ThinRcSlice<u64> = std::iter::empty().collect();
+        assert!(rc.is_empty());
+    }
+
+    #[test]
+    fn test_thin_rc_slice_raw() {
+        let rc: ThinRcSlice<String> = ["a", "b"].into_iter().map(String::from).collect();
+        let ptr = ThinRcSlice::into_raw(rc.clone());
+
+        // SAFETY: `ptr` was just returned by `into_raw`.
+        let back = unsafe { ThinRcSlice::from_raw(ptr) };
+        assert!(ThinRcSlice::ptr_eq(&rc, &back));
+        assert_eq!(ThinRcSlice::strong_count(&rc), 2);
+        assert_eq!(
+            std::mem::size_of::<Option<ThinRcSlice<String>>>(),
+            std::mem::size_of::<usize>()
+        );
+    }
+
+    #[test]
+    fn test_thin_rc_slice_map_key() {
+        let map: std::collections::BTreeMap<ThinRcSlice<u8>, char> =
+            [((1..3).collect(), 'a'), ((0..2).collect(), 'b')]
+                .into_iter()
+                .collect();
+
+        assert_eq!(map.get(&[1, 2][..]), Some(&'a'));
+        assert_eq!(map.values().collect::<String>(), "ba");
+    }
+}
@@ -0,0 +1,237 @@
+use crate::{
+    into_char::IntoChar,
+    thin_rc_slice::{ThinRcBuf, ThinRcSlice},
+};
+use std::{
+    borrow::Borrow,
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+    ops::Deref,
+    str,
+};
+
+/// A single-threaded reference-counted string that is only one pointer wide.
+///
+/// This is the string counterpart of [`ThinRcSlice`], the length is stored in the heap allocation
+/// next to the strong count.
+///
+/// # Examples
+/// ```rust
+/// use collect_into_rc_slice::*;
+///
+/// let s: ThinRcStr = ["Hello", ", ", "world!"].into_iter().collect();
+///
+/// assert_eq!(&*s, "Hello, world!");
+/// assert_eq!(std::mem::size_of::<ThinRcStr>(), std::mem::size_of::<usize>());
+/// ```
+#[derive(Clone)]
+pub struct ThinRcStr(ThinRcSlice<u8>);
+
+impl ThinRcStr {
+    /// Gets the number of pointers to this allocation.
+    #[inline]
+    pub fn strong_count(this: &Self) -> usize {
+        ThinRcSlice::strong_count(&this.0)
+    }
+
+    /// Returns `true` if the two pointers point to the same allocation.
+    #[inline]
+    pub fn ptr_eq(this: &Self, other: &Self) -> bool {
+        ThinRcSlice::ptr_eq(&this.0, &other.0)
+    }
+
+    /// Consumes the pointer, returning a thin pointer to the first byte of the string.
+    ///
+    /// The allocation is leaked unless the pointer is turned back into a `ThinRcStr` with
+    /// [`ThinRcStr::from_raw`].
+    #[inline]
+    pub fn into_raw(this: Self) -> *const u8 {
+        ThinRcSlice::into_raw(this.0)
+    }
+
+    /// Constructs a `ThinRcStr` from a pointer returned by [`ThinRcStr::into_raw`].
+    ///
+    /// # Safety
+    /// `ptr` must have been returned by `ThinRcStr::into_raw`, and each call to `into_raw` can be
+    /// matched by at most one call to `from_raw`.
+    #[inline]
+    pub unsafe fn from_raw(ptr: *const u8) -> Self {
+        // SAFETY: The caller guarantees `ptr` came from a `ThinRcStr`.
+        Self(unsafe { ThinRcSlice::from_raw(ptr) })
+    }
+
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        // SAFETY: The bytes were only ever written from `char`s and `str`s.
+        unsafe { str::from_utf8_unchecked(&self.0) }
+    }
+}
+
+impl Deref for ThinRcStr {
+    type Target = str;
+
+    #[inline]
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl AsRef<str> for ThinRcStr {
+    #[inline]
+    fn as_ref(&self) -> &str {
+        self
+    }
+}
+
+impl fmt::Debug for ThinRcStr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl fmt::Display for ThinRcStr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self.as_str(), f)
+    }
+}
+
+impl PartialEq for ThinRcStr {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for ThinRcStr {}
+
+impl PartialOrd for ThinRcStr {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ThinRcStr {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+impl Hash for ThinRcStr {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state);
+    }
+}
+
+impl Borrow<str> for ThinRcStr {
+    #[inline]
+    fn borrow(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl PartialEq<str> for ThinRcStr {
+    #[inline]
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for ThinRcStr {
+    #[inline]
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl FromIterator<char> for ThinRcStr {
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = char>>(iter: I) -> Self {
+        Self(ThinRcSlice::from_buf(ThinRcBuf::from_chars(
+            iter.into_iter(),
+        )))
+    }
+}
+
+impl<'a> FromIterator<&'a str> for ThinRcStr {
+    fn from_iter<I: IntoIterator<Item = &'a str>>(iter: I) -> Self {
+        let iter = iter.into_iter();
+        let mut buf = ThinRcBuf::with_capacity(iter.size_hint().0);
+
+        for s in iter {
+            buf.extend_from_slice(s.as_bytes());
+        }
+
+        Self(ThinRcSlice::from_buf(buf))
+    }
+}
+
+pub trait CollectIntoThinRcStr {
+    /// Collects the iterator into a [`ThinRcStr`].
+    ///
+    /// # Examples
+    /// ```rust
+    /// use collect_into_rc_slice::*;
+    ///
+    /// let s = "Hello, world!".chars().collect_into_thin_rc_str();
+    ///
+    /// assert_eq!(&*s, "Hello, world!");
+    /// ```
+    fn collect_into_thin_rc_str(self) -> ThinRcStr;
+}
+
+impl<T> CollectIntoThinRcStr for T
+where
+    T: IntoIterator,
+    T::Item: IntoChar,
+{
+    fn collect_into_thin_rc_str(self) -> ThinRcStr {
+        self.into_iter().map(IntoChar::into_char).collect()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for ThinRcStr {
+    #[inline]
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_collect_into_thin_rc_str() {
+        let s = "Hello, world!".chars().collect_into_thin_rc_str();
+
+        assert_eq!(&*s, "Hello, world!");
+        assert_eq!(s.len(), 13);
+        assert_eq!(ThinRcStr::strong_count(&s), 1);
+    }
+
+    #[test]
+    fn test_thin_rc_str_set() {
+        let a: ThinRcStr = ["foo", "bar"].into_iter().collect();
+        let b = "foobar".chars().collect_into_thin_rc_str();
+        let set: HashSet<_> = [a, b.clone()].into_iter().collect();
+
+        assert_eq!(set.len(), 1);
+        assert!(set.contains(&b));
+    }
+
+    #[test]
+    fn test_thin_rc_str_map_key() {
+        let map: std::collections::HashMap<ThinRcStr, u32> =
+            [("one".chars().collect(), 1), ("two".chars().collect(), 2)]
+                .into_iter()
+                .collect();
+
+        assert_eq!(map.get("two"), Some(&2));
+        assert!(map.keys().any(|k| *k == "one"));
+    }
+}
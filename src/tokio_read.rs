@@ -0,0 +1,196 @@
+#![cfg(target_has_atomic = "ptr")]
+use crate::{
+    arc::{into_arc, into_arc_str, ArcBuf},
+    read::{invalid_utf8, PROBE_SIZE},
+    ArcSubstr,
+};
+use std::{
+    future::poll_fn,
+    io,
+    pin::Pin,
+    str,
+    sync::Arc,
+    task::{ready, Poll},
+};
+use tokio::io::{AsyncBufRead, AsyncRead, ReadBuf};
+
+/// Reads all of `reader` into an `Arc<[u8]>`, like `AsyncReadExt::read_to_end` followed by
+/// `.into()` but without going through a `Vec<u8>`.
+///
+/// The reader fills the spare capacity of the `Arc` allocation directly, without it being zeroed
+/// first, and the allocation is trimmed once at the end of the stream.
+///
+/// # Examples
+/// ```rust
+/// use collect_into_rc_slice::*;
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() -> std::io::Result<()> {
+/// let arc = read_to_arc_bytes_async(&b"hello world"[..]).await?;
+///
+/// assert_eq!(&*arc, b"hello world");
+/// # Ok(())
+/// # }
+/// ```
+pub async fn read_to_arc_bytes_async<R: AsyncRead + Unpin>(mut reader: R) -> io::Result<Arc<[u8]>> {
+    let mut buf = ArcBuf::with_capacity(0);
+
+    loop {
+        // probe for the end of the stream before growing a full buffer
+        if buf.len() == buf.capacity() {
+            let mut probe = [0; PROBE_SIZE];
+            let n = poll_read(&mut reader, ReadBuf::new(&mut probe)).await?;
+            if n == 0 {
+                break;
+            }
+            buf.extend_from_slice(&probe[..n]);
+            continue;
+        }
+
+        let n = poll_read(&mut reader, ReadBuf::uninit(buf.spare_capacity_mut())).await?;
+        if n == 0 {
+            break;
+        }
+        // SAFETY: The reader initialized the first `n` bytes of the spare capacity.
+        unsafe { buf.set_len(buf.len() + n) };
+    }
+
+    Ok(into_arc(buf))
+}
+
+/// Reads all of `reader` into a single `Arc<str>` and splits it into lines, like
+/// `AsyncBufReadExt::lines` but returning substrings that share that allocation instead of a
+/// `String` per line.
+///
+/// Lines are split like with `str::lines`, so the line endings, `\n` or `\r\n`, aren't part of
+/// the lines.
+///
+/// # Errors
+/// Fails with an error of kind `InvalidData` wrapping an
+/// [`InvalidUtf8Error`](crate::InvalidUtf8Error) if the input is not valid UTF-8, and with the
+/// error of the reader if it fails.
+///
+/// # Examples
+/// ```rust
+/// use collect_into_rc_slice::*;
+/// use std::sync::Arc;
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() -> std::io::Result<()> {
+/// let lines = read_to_arc_lines_async(&b"GET /\r\nHost: example.com\r\n"[..]).await?;
+///
+/// assert_eq!(lines, ["GET /", "Host: example.com"]);
+/// assert!(Arc::ptr_eq(lines[0].parent(), lines[1].parent()));
+/// # Ok(())
+/// # }
+/// ```
+pub async fn read_to_arc_lines_async<R: AsyncBufRead + Unpin>(
+    mut reader: R,
+) -> io::Result<Vec<ArcSubstr>> {
+    let mut buf = ArcBuf::with_capacity(0);
+
+    loop {
+        let n = poll_fn(|cx| {
+            let available = ready!(Pin::new(&mut reader).poll_fill_buf(cx))?;
+            buf.extend_from_slice(available);
+            let n = available.len();
+            Pin::new(&mut reader).consume(n);
+            Poll::Ready(Ok::<_, io::Error>(n))
+        })
+        .await?;
+
+        if n == 0 {
+            break;
+        }
+    }
+
+    if let Err(err) = str::from_utf8(buf.as_slice()) {
+        return Err(invalid_utf8(err.valid_up_to()));
+    }
+    // SAFETY: The buffer was just validated as UTF-8.
+    let text = ArcSubstr::new(unsafe { into_arc_str(buf) });
+
+    Ok(text.lines().collect())
+}
+
+/// Reads from `reader` into `buf` once, returning how many bytes were read into it.
+async fn poll_read<R: AsyncRead + Unpin>(
+    reader: &mut R,
+    mut buf: ReadBuf<'_>,
+) -> io::Result<usize> {
+    let start = buf.filled().as_ptr();
+
+    poll_fn(|cx| {
+        ready!(Pin::new(&mut *reader).poll_read(cx, &mut buf))?;
+        // the filled part is only known to be in our buffer if the reader didn't swap it
+        assert_eq!(
+            start,
+            buf.filled().as_ptr(),
+            "the reader replaced the buffer"
+        );
+        Poll::Ready(Ok(buf.filled().len()))
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::read::Chunked;
+    use std::{io::Read, task::Context};
+
+    /// A [`Chunked`] reader returning `Pending` before each read.
+    struct Pending<'a> {
+        reader: Chunked<'a>,
+        pending: bool,
+    }
+
+    impl<'a> Pending<'a> {
+        fn new(data: &'a [u8], chunk: usize) -> Self {
+            Self {
+                reader: Chunked::new(data, chunk),
+                pending: false,
+            }
+        }
+    }
+
+    impl AsyncRead for Pending<'_> {
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &mut ReadBuf<'_>,
+        ) -> Poll<io::Result<()>> {
+            self.pending = !self.pending;
+            if self.pending {
+                cx.waker().wake_by_ref();
+                return Poll::Pending;
+            }
+
+            let n = self.reader.read(buf.initialize_unfilled())?;
+            buf.advance(n);
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_read_to_arc_bytes_async_chunked() {
+        let data: Vec<u8> = (0..=255).cycle().take(1000).collect();
+        let reader = Pending::new(&data, 7);
+
+        assert_eq!(&*read_to_arc_bytes_async(reader).await.unwrap(), &data[..]);
+        assert!(read_to_arc_bytes_async(&b""[..]).await.unwrap().is_empty());
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_read_to_arc_lines_async() {
+        let text = "first\r\n\nthird ✓\nlast".repeat(50);
+        let reader = tokio::io::BufReader::with_capacity(16, Pending::new(text.as_bytes(), 5));
+
+        let lines = read_to_arc_lines_async(reader).await.unwrap();
+        assert_eq!(lines, text.lines().collect::<Vec<_>>());
+        assert_eq!(lines[0].parent().len(), text.len());
+
+        let err = read_to_arc_lines_async(&b"ok\n\xff"[..]).await.unwrap_err();
+        assert_eq!(err.to_string(), "invalid UTF-8 at byte offset 3");
+    }
+}
@@ -0,0 +1,114 @@
+#![cfg(feature = "triomphe")]
+use crate::{
+    into_char::IntoChar,
+    raw::{Prefix, RawBuf},
+};
+use std::{ptr, sync::atomic::AtomicUsize};
+use triomphe::Arc;
+
+/// The header of a `triomphe::Arc` allocation, which only has a strong count.
+#[repr(C)]
+struct TriompheInner {
+    count: AtomicUsize,
+}
+
+// SAFETY: `triomphe::Arc` lays out its allocation as an `ArcInner` with a single count.
+unsafe impl Prefix for TriompheInner {}
+
+type TriompheBuf<T> = RawBuf<TriompheInner, T>;
+
+fn into_triomphe<T>(buf: TriompheBuf<T>) -> Arc<[T]> {
+    let (alloc, data) = buf.into_raw_parts();
+
+    // SAFETY:
+    // - The prefix of the allocation is reserved for the count.
+    // - `data` is a valid pointer to a `[T]` located at the heap
+    // - `data` is part of an ArcInner with proper metadata.
+    unsafe {
+        ptr::write(
+            alloc.as_ptr() as *mut TriompheInner,
+            TriompheInner {
+                count: AtomicUsize::new(1),
+            },
+        );
+
+        Arc::from_raw(data)
+    }
+}
+
+pub trait CollectIntoTriompheSlice<T> {
+    /// Collects the iterator into a `triomphe::Arc<[T]>`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use collect_into_rc_slice::*;
+    ///
+    /// let arr = [1, 2, 3, 4, 5];
+    /// let arc: triomphe::Arc<[i32]> = arr.into_iter().collect_into_triomphe_slice();
+    ///
+    /// assert_eq!(&*arc, &[1, 2, 3, 4, 5]);
+    /// ```
+    fn collect_into_triomphe_slice(self) -> Arc<[T]>;
+}
+
+impl<I, T> CollectIntoTriompheSlice<T> for I
+where
+    I: IntoIterator<Item = T>,
+{
+    fn collect_into_triomphe_slice(self) -> Arc<[T]> {
+        into_triomphe(TriompheBuf::from_iter(self.into_iter()))
+    }
+}
+
+pub trait CollectIntoTriompheStr {
+    /// Collects the iterator into a `triomphe::Arc<str>`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use collect_into_rc_slice::*;
+    ///
+    /// let s: triomphe::Arc<str> = "Hello, world!".chars().collect_into_triomphe_str();
+    ///
+    /// assert!(s.as_ref() == "Hello, world!");
+    /// ```
+    fn collect_into_triomphe_str(self) -> Arc<str>;
+}
+
+impl<T> CollectIntoTriompheStr for T
+where
+    T: IntoIterator,
+    T::Item: IntoChar,
+{
+    fn collect_into_triomphe_str(self) -> Arc<str> {
+        let arc = into_triomphe(TriompheBuf::from_chars(
+            self.into_iter().map(IntoChar::into_char),
+        ));
+
+        // SAFETY: The buffer is made up of UTF-8 encoded `char`s.
+        unsafe { Arc::from_raw(Arc::into_raw(arc) as *const str) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collect_into_triomphe_slice() {
+        let arc = vec![1, 2, 3, 4, 5]
+            .into_iter()
+            .collect_into_triomphe_slice();
+
+        assert_eq!(&*arc, &[1, 2, 3, 4, 5]);
+        assert!(arc.is_unique());
+    }
+
+    #[test]
+    fn test_collect_into_triomphe_str() {
+        let mut str = "Hello, world!".chars();
+        let s = std::iter::from_fn(move || str.next()).collect_into_triomphe_str();
+
+        assert!(s.as_ref() == "Hello, world!");
+        assert_eq!(Arc::count(&s), 1);
+    }
+}
@@ -0,0 +1,212 @@
+use crate::rc::{into_rc, RcBuf};
+#[cfg(target_has_atomic = "ptr")]
+use std::sync::Arc;
+use std::{
+    mem::{self, ManuallyDrop},
+    ptr::{self, NonNull},
+    rc::Rc,
+};
+
+/// Returns a mutable reference to the elements of `this`, cloning them into a new `Rc<[T]>` first
+/// if it isn't the only pointer to them, like `Rc::make_mut` does for sized values.
+///
+/// If there are other `Rc`s or `Weak`s pointing to the elements, they keep pointing to the old
+/// ones. The new allocation is made with the exact length of the slice.
+///
+/// There are no helpers for a uniquely owned `Arc<[T]>` or `Arc<str>`: `Arc::get_mut` already
+/// returns its contents only when there are no other `Arc`s or `Weak`s, and `Arc::make_mut` clones
+/// them otherwise.
+///
+/// # Examples
+/// ```rust
+/// use collect_into_rc_slice::*;
+/// use std::rc::Rc;
+///
+/// let mut a: Rc<[i32]> = (1..=3).collect_into_rc_slice();
+/// let b = a.clone();
+///
+/// make_mut_slice(&mut a)[0] = 10;
+/// make_mut_slice(&mut a)[1] = 20;
+///
+/// assert_eq!(&*a, &[10, 20, 3]);
+/// assert_eq!(&*b, &[1, 2, 3]);
+/// ```
+pub fn make_mut_slice<T: Clone>(this: &mut Rc<[T]>) -> &mut [T] {
+    if Rc::get_mut(this).is_none() {
+        *this = into_rc(RcBuf::from_iter(this.iter().cloned()));
+    }
+
+    Rc::get_mut(this).expect("the slice was just made unique")
+}
+
+/// Moves the elements of `this` out into a `Vec<T>` if it is the only pointer to them, with no
+/// other `Rc`s or `Weak`s. Otherwise `this` is returned as is.
+///
+/// The elements are moved with a single copy rather than cloned, and the `Rc` allocation is freed.
+///
+/// # Examples
+/// ```rust
+/// use collect_into_rc_slice::*;
+///
+/// let rc = ["a", "b"].into_iter().map(String::from).collect_into_rc_slice();
+/// let shared = rc.clone();
+///
+/// let rc = try_unwrap_into_vec(rc).unwrap_err();
+/// drop(shared);
+///
+/// let mut vec = try_unwrap_into_vec(rc).unwrap();
+/// vec.push("c".to_string());
+/// assert_eq!(vec, ["a", "b", "c"]);
+/// ```
+pub fn try_unwrap_into_vec<T>(mut this: Rc<[T]>) -> Result<Vec<T>, Rc<[T]>> {
+    if Rc::get_mut(&mut this).is_none() {
+        return Err(this);
+    }
+
+    // SAFETY: `ManuallyDrop<T>` has the same layout as `T`.
+    let items = unsafe { Rc::from_raw(Rc::into_raw(this) as *const [ManuallyDrop<T>]) };
+
+    let mut vec = Vec::with_capacity(items.len());
+    // SAFETY:
+    // - The slice is uniquely owned, and its elements are never dropped by the `Rc` anymore, so
+    //   they are moved into the `Vec`.
+    // - The `Vec` has room for all of them and can't overlap with the slice.
+    unsafe {
+        ptr::copy_nonoverlapping(items.as_ptr() as *const T, vec.as_mut_ptr(), items.len());
+        vec.set_len(items.len());
+    }
+
+    Ok(vec)
+}
+
+/// Keeps only the elements of `this` for which `f` returns `true`, like `Vec::retain`.
+///
+/// If `this` is the only pointer to the elements, with no other `Rc`s or `Weak`s, they are
+/// filtered in place and the allocation is shrunk to the elements that are left. Otherwise the
+/// elements that are kept are cloned into a new `Rc<[T]>`, and the other pointers keep pointing to
+/// the old ones.
+///
+/// # Examples
+/// ```rust
+/// use collect_into_rc_slice::*;
+///
+/// let mut rc = (1..=10).collect_into_rc_slice();
+/// rc_slice_retain(&mut rc, |i| i % 3 == 0);
+///
+/// assert_eq!(&*rc, &[3, 6, 9]);
+/// ```
+pub fn rc_slice_retain<T: Clone, F: FnMut(&T) -> bool>(this: &mut Rc<[T]>, mut f: F) {
+    if Rc::get_mut(this).is_none() {
+        *this = into_rc(RcBuf::from_iter(
+            this.iter().filter(|item| f(item)).cloned(),
+        ));
+        return;
+    }
+
+    // `this` is left with an empty slice if `f` panics
+    let rc = mem::replace(this, Rc::from([]));
+    let len = rc.len();
+    let data = Rc::into_raw(rc) as *const T as *mut u8;
+    // SAFETY:
+    // - The data is preceded by the reference counts in the same allocation.
+    // - `Rc` allocates its slices with the layout of an `RcBuf` with no spare capacity.
+    // - The `Rc` was uniquely owned, and it is never used or dropped again.
+    let mut buf = unsafe {
+        let ptr = NonNull::new_unchecked(data.sub(RcBuf::<T>::data_offset()));
+        RcBuf::from_raw_parts(ptr, len)
+    };
+
+    buf.retain(f);
+    *this = into_rc(buf);
+}
+
+/// Copies the string of `this` into a `String` if it is the only pointer to it, with no other
+/// `Arc`s or `Weak`s, freeing the `Arc` allocation. Otherwise `this` is returned as is.
+///
+/// # Examples
+/// ```rust
+/// use collect_into_rc_slice::*;
+///
+/// let arc = "hello".chars().collect_into_arc_str();
+/// let shared = arc.clone();
+///
+/// let arc = try_unwrap_into_string(arc).unwrap_err();
+/// drop(shared);
+///
+/// let mut string = try_unwrap_into_string(arc).unwrap();
+/// string.push_str(", world");
+/// assert_eq!(string, "hello, world");
+/// ```
+#[cfg(target_has_atomic = "ptr")]
+pub fn try_unwrap_into_string(mut this: Arc<str>) -> Result<String, Arc<str>> {
+    match Arc::get_mut(&mut this) {
+        Some(s) => Ok(String::from(&*s)),
+        None => Err(this),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_make_mut_slice_unique() {
+        let mut rc: Rc<[String]> = Rc::from(["a".to_string(), "b".to_string()]);
+        let ptr = rc.as_ptr();
+
+        make_mut_slice(&mut rc)[1].push('!');
+        assert_eq!(rc.as_ptr(), ptr);
+        assert_eq!(&*rc, ["a", "b!"]);
+    }
+
+    #[test]
+    fn test_make_mut_slice_weak() {
+        let mut rc: Rc<[u8]> = Rc::from(&b"abc"[..]);
+        let weak = Rc::downgrade(&rc);
+
+        make_mut_slice(&mut rc).reverse();
+        assert_eq!(&*rc, b"cba");
+        // nothing else kept the old elements alive
+        assert!(weak.upgrade().is_none());
+    }
+
+    #[test]
+    fn test_try_unwrap_into_vec_moves() {
+        let live = Rc::new(());
+        let rc: Rc<[Rc<()>]> = into_rc(RcBuf::from_iter(std::iter::repeat_n(live.clone(), 3)));
+        let weak = Rc::downgrade(&rc);
+
+        let rc = try_unwrap_into_vec(rc).unwrap_err();
+        drop(weak);
+
+        let vec = try_unwrap_into_vec(rc).unwrap();
+        assert_eq!(Rc::strong_count(&live), 4);
+        drop(vec);
+        assert_eq!(Rc::strong_count(&live), 1);
+    }
+
+    #[cfg(target_has_atomic = "ptr")]
+    #[test]
+    fn test_try_unwrap_into_string_weak() {
+        let arc: Arc<str> = Arc::from("abc");
+        let weak = Arc::downgrade(&arc);
+
+        let arc = try_unwrap_into_string(arc).unwrap_err();
+        assert_eq!(&*arc, "abc");
+        drop(weak);
+
+        assert_eq!(try_unwrap_into_string(arc).unwrap(), "abc");
+    }
+
+    #[test]
+    fn test_rc_slice_retain_unique_and_shared() {
+        let mut rc: Rc<[String]> = (0..8).map(|i| i.to_string()).collect();
+        rc_slice_retain(&mut rc, |s| s != "3");
+        assert_eq!(&*rc, ["0", "1", "2", "4", "5", "6", "7"]);
+
+        let shared = rc.clone();
+        rc_slice_retain(&mut rc, |s| s.parse::<u8>().unwrap() > 4);
+        assert_eq!(&*rc, ["5", "6", "7"]);
+        assert_eq!(shared.len(), 7);
+    }
+}
@@ -0,0 +1,62 @@
+use crate::rc::{into_rc, RcBuf};
+use std::rc::Rc;
+
+pub trait UnzipIntoRcSlices<A, B> {
+    /// Splits an iterator of pairs into an `Rc<[A]>` of the first elements and an `Rc<[B]>` of
+    /// the second ones, like `Iterator::unzip` into two `Vec`s without the `Vec`s.
+    ///
+    /// Both allocations are sized with the iterator's size hint and grown together as needed,
+    /// while the iterator is consumed once.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use collect_into_rc_slice::*;
+    ///
+    /// let rows = [("ada", 36), ("grace", 85), ("alan", 41)];
+    /// let (names, ages) = rows.into_iter().unzip_into_rc_slices();
+    ///
+    /// assert_eq!(&*names, ["ada", "grace", "alan"]);
+    /// assert_eq!(&*ages, [36, 85, 41]);
+    /// ```
+    fn unzip_into_rc_slices(self) -> (Rc<[A]>, Rc<[B]>);
+}
+
+impl<A, B, I> UnzipIntoRcSlices<A, B> for I
+where
+    I: IntoIterator<Item = (A, B)>,
+{
+    fn unzip_into_rc_slices(self) -> (Rc<[A]>, Rc<[B]>) {
+        let iter = self.into_iter();
+        let (lower_bound, upper_bound) = iter.size_hint();
+        let cap = upper_bound.unwrap_or(lower_bound);
+        let mut a = RcBuf::with_capacity(cap);
+        let mut b = RcBuf::with_capacity(cap);
+
+        for (item_a, item_b) in iter {
+            a.push(item_a);
+            b.push(item_b);
+        }
+
+        (into_rc(a), into_rc(b))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unzip_into_rc_slices_unknown_size() {
+        let (evens, odds) = (0..100)
+            .filter(|i| i % 2 == 0)
+            .map(|i| (i.to_string(), i + 1))
+            .unzip_into_rc_slices();
+
+        assert_eq!(evens.len(), 50);
+        assert_eq!(odds.len(), 50);
+        assert!(evens
+            .iter()
+            .zip(&*odds)
+            .all(|(e, o)| *e == (o - 1).to_string()));
+    }
+}
@@ -0,0 +1,40 @@
+#![cfg(target_has_atomic = "ptr")]
+use crate::{
+    arc::{into_arc, ArcBuf},
+    ArcBytes,
+};
+use js_sys::Uint8Array;
+use std::sync::Arc;
+
+/// Copies the contents of a JavaScript `Uint8Array` into an `Arc<[u8]>`, e.g. the bytes of a
+/// `fetch` response or a file picked in the browser.
+///
+/// The allocation is made once at the length of the array and the bytes are copied straight into
+/// it from the JavaScript side, without a `Vec<u8>` in between.
+///
+/// # Examples
+/// ```rust,no_run
+/// use collect_into_rc_slice::*;
+/// use js_sys::Uint8Array;
+///
+/// let array = Uint8Array::from(&b"from the browser"[..]);
+/// let arc = uint8_array_to_arc_slice(&array);
+///
+/// assert_eq!(&*arc, b"from the browser");
+/// ```
+pub fn uint8_array_to_arc_slice(array: &Uint8Array) -> Arc<[u8]> {
+    let len = array.length() as usize;
+    let mut buf = ArcBuf::with_capacity(len);
+    array.copy_to_uninit(&mut buf.spare_capacity_mut()[..len]);
+
+    // SAFETY: The `len` bytes were just copied into the buffer.
+    unsafe { buf.set_len(len) };
+    into_arc(buf)
+}
+
+/// Copies the contents of a JavaScript `Uint8Array` into an [`ArcBytes`], like
+/// [`uint8_array_to_arc_slice`].
+#[inline]
+pub fn uint8_array_to_arc_bytes(array: &Uint8Array) -> ArcBytes {
+    ArcBytes::new(uint8_array_to_arc_slice(array))
+}